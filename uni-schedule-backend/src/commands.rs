@@ -1,77 +1,279 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use tauri::State;
-use tokio::sync::RwLock;
+use tokio::sync::{RwLock, RwLockWriteGuard};
 
 use uni_schedule_core::schedule::{
-  QueryOptions, Schedule, ScheduleId, ScheduleLevel, ScheduleManager,
+  ExclusiveScope, QueryOptions, Schedule, ScheduleId, ScheduleLevel, ScheduleManager, SortOrder,
 };
 
-use crate::storage::{SledStorage, Storage};
+use crate::storage::{SledStorage, Storage, StorageError};
 
 /// Shared application state containing the schedule manager and storage.
+///
+/// The store is any [`Storage`] implementation, so alternative backends
+/// (or [`MockStorage`](crate::storage::MockStorage) in tests) can be swapped
+/// in without touching the commands or the manager.
 pub struct AppState {
   pub manager: RwLock<ScheduleManager>,
-  pub storage: RwLock<SledStorage>,
+  pub storage: RwLock<Box<dyn Storage + Send + Sync>>,
+  /// Set between [`AppState::begin_bulk`] and [`AppState::end_bulk`].
+  /// Only changed while holding the `storage` lock.
+  bulk: AtomicBool,
 }
 
 impl AppState {
-  pub fn new(storage: SledStorage) -> Self {
+  /// Load the persisted state from `storage`.
+  ///
+  /// Fails if stored data exists but cannot be read, instead of starting
+  /// empty and overwriting it on the first change.
+  pub fn new(storage: impl Storage + Send + Sync + 'static) -> Result<Self, StorageError> {
     // Start with a default manager, then load persisted state
     let mut mgr = ScheduleManager::new();
-    storage.load(&mut mgr);
-    Self {
+    storage.load(&mut mgr)?;
+    Ok(Self {
       manager: RwLock::new(mgr),
-      storage: RwLock::new(storage),
+      storage: RwLock::new(Box::new(storage)),
+      bulk: AtomicBool::new(false),
+    })
+  }
+
+  /// Stop persisting after every mutating command, e.g. for a large
+  /// import. Until [`AppState::end_bulk`], commands change only the
+  /// in-memory manager, so a crash in between loses those changes.
+  /// Waits for a write in progress to finish. Calling it again is a no-op.
+  pub async fn begin_bulk(&self) {
+    let _storage = self.storage.write().await;
+    self.bulk.store(true, Ordering::Relaxed);
+  }
+
+  /// Leave bulk mode and write the whole current state to the store in
+  /// one commit. Does nothing outside bulk mode.
+  ///
+  /// If the write fails the state stays in bulk mode with memory intact,
+  /// so the caller can retry.
+  pub async fn end_bulk(&self) -> Result<(), String> {
+    let mut storage = self.storage.write().await;
+    if !self.bulk.load(Ordering::Relaxed) {
+      return Ok(());
+    }
+    let mgr = self.manager.read().await;
+    storage.commit(&mgr).map_err(|e| e.to_string())?;
+    self.bulk.store(false, Ordering::Relaxed);
+    Ok(())
+  }
+
+  /// Switch to `storage` and replace the in-memory state with what it
+  /// holds.
+  ///
+  /// Changes made in bulk mode are first written to the current store, so
+  /// they are not lost with the switch. If that write fails nothing is
+  /// switched and bulk mode stays on, as with [`AppState::end_bulk`].
+  pub async fn replace_storage(
+    &self,
+    storage: impl Storage + Send + Sync + 'static,
+  ) -> Result<(), String> {
+    let mut loaded = ScheduleManager::new();
+    storage.load(&mut loaded).map_err(|e| e.to_string())?;
+
+    let (mut s, mut m) = lock_for_write(self).await;
+    if self.bulk.load(Ordering::Relaxed) {
+      s.commit(&m).map_err(|e| e.to_string())?;
+      self.bulk.store(false, Ordering::Relaxed);
     }
+    *m = loaded;
+    *s = Box::new(storage);
+    Ok(())
+  }
+
+  /// Copy of `mgr` for [`commit`] to restore if persisting fails. Skipped
+  /// in bulk mode, where nothing is persisted per command.
+  fn backup(&self, mgr: &ScheduleManager) -> Option<ScheduleManager> {
+    (!self.bulk.load(Ordering::Relaxed)).then(|| mgr.clone())
   }
 }
 
+/// Point the app at a different storage directory.
+///
+/// Intended to be called once at startup (tests, portable installs). The
+/// current in-memory state is replaced with whatever is persisted under
+/// `path`; see [`AppState::replace_storage`] for pending bulk changes.
+#[tauri::command]
+pub async fn set_storage_path(state: State<'_, AppState>, path: String) -> Result<(), String> {
+  let storage = SledStorage::try_open(Some(PathBuf::from(path))).map_err(|e| e.to_string())?;
+  state.replace_storage(storage).await
+}
+
 // Request/response DTOs exposed to the frontend.
+//
+// Ids are `ScheduleId` (`uuid::Uuid`) on both sides of the boundary, which
+// serde reads and writes as canonical UUID strings, so JavaScript never sees
+// them as numbers. A malformed id fails argument deserialization before the
+// command runs, and the caller gets the uuid parse error naming the
+// argument.
 #[derive(Debug, Deserialize)]
+#[cfg_attr(
+  feature = "ts-bindings",
+  derive(ts_rs::TS),
+  ts(export, optional_fields = nullable)
+)]
 pub struct CreateScheduleReq {
   pub start: DateTime<Utc>,
   pub end: DateTime<Utc>,
   pub level: ScheduleLevel,
   pub exclusive: bool,
   pub name: String,
+  #[serde(default)]
+  pub description: Option<String>,
+  #[serde(default)]
+  #[cfg_attr(feature = "ts-bindings", ts(as = "Option<_>", optional))]
+  pub tags: Vec<String>,
+  #[serde(default)]
+  #[cfg_attr(feature = "ts-bindings", ts(as = "Option<_>", optional))]
+  pub priority: u8,
+  #[serde(default)]
+  pub color: Option<String>,
+  #[serde(default)]
+  #[cfg_attr(feature = "ts-bindings", ts(as = "Option<_>", optional))]
+  pub exclusive_scope: ExclusiveScope,
   pub parents: Vec<ScheduleId>,
 }
 
 #[derive(Debug, Serialize)]
+#[cfg_attr(feature = "ts-bindings", derive(ts_rs::TS), ts(export))]
 pub struct CreateScheduleRes {
   pub id: ScheduleId,
 }
 
+/// The schedule and parent set described by `req`. Shared by every command
+/// taking a `CreateScheduleReq`, so validating and creating see the same
+/// schedule.
+fn new_schedule(req: CreateScheduleReq) -> (Schedule, HashSet<ScheduleId>) {
+  let schedule = Schedule::new_with_meta(
+    req.start,
+    req.end,
+    req.level,
+    req.exclusive,
+    req.name,
+    req.description,
+    req.tags,
+  )
+  .with_priority(req.priority)
+  .with_color(req.color)
+  .with_exclusive_scope(req.exclusive_scope);
+  (schedule, req.parents.into_iter().collect())
+}
+
+/// Create a schedule and return just its id.
 #[tauri::command]
-pub async fn create_schedule(
+pub async fn create_schedule_id(
   state: State<'_, AppState>,
   req: CreateScheduleReq,
 ) -> Result<CreateScheduleRes, String> {
-  let schedule = Schedule::new(req.start, req.end, req.level, req.exclusive, req.name);
-  let parents: HashSet<ScheduleId> = req.parents.into_iter().collect();
+  let (schedule, parents) = new_schedule(req);
 
-  let mut mgr = state.manager.write().await;
+  let (storage, mut mgr) = lock_for_write(&state).await;
+  let backup = state.backup(&mgr);
   match mgr.create_schedule(schedule, parents) {
     Ok(id) => {
-      // persist synchronously
-      let snapshot_mgr = &*mgr; // borrow for snapshot
-      let mut s = state.storage.write().await;
-      s.persist_snapshot(snapshot_mgr);
+      commit(&state, storage, mgr, backup).await?;
       Ok(CreateScheduleRes { id })
     }
     Err(e) => Err(e.to_string()),
   }
 }
 
+/// Create a schedule and return it with its resolved relations, saving the
+/// frontend a follow-up `get_schedule` call.
+#[tauri::command]
+pub async fn create_schedule(
+  state: State<'_, AppState>,
+  req: CreateScheduleReq,
+) -> Result<ScheduleNode, String> {
+  let (schedule, parents) = new_schedule(req);
+
+  let (storage, mut mgr) = lock_for_write(&state).await;
+  let backup = state.backup(&mgr);
+  match mgr.create_schedule(schedule, parents) {
+    Ok(id) => {
+      let node = schedule_node(&mgr, id).ok_or_else(|| "created schedule not found".to_string());
+      commit(&state, storage, mgr, backup).await?;
+      node
+    }
+    Err(e) => Err(e.to_string()),
+  }
+}
+
+/// Validate a proposed schedule without creating it, for live feedback.
+#[tauri::command]
+pub async fn validate_schedule(
+  state: State<'_, AppState>,
+  req: CreateScheduleReq,
+) -> Result<(), String> {
+  let (schedule, parents) = new_schedule(req);
+
+  let mgr = state.manager.read().await;
+  mgr
+    .validate_create(&schedule, &parents)
+    .map_err(|e| e.to_string())
+}
+
 #[derive(Debug, Deserialize)]
+#[cfg_attr(
+  feature = "ts-bindings",
+  derive(ts_rs::TS),
+  ts(export, optional_fields = nullable)
+)]
+pub struct BatchCreateItem {
+  /// Optional caller-chosen id so later items in the same batch can
+  /// reference this one as a parent.
+  #[serde(default)]
+  pub id: Option<ScheduleId>,
+  #[serde(flatten)]
+  pub schedule: CreateScheduleReq,
+}
+
+/// Create many schedules in one call. Either all are created or none.
+#[tauri::command]
+pub async fn batch_create_schedules(
+  state: State<'_, AppState>,
+  req: Vec<BatchCreateItem>,
+) -> Result<Vec<ScheduleId>, String> {
+  let items = req
+    .into_iter()
+    .map(|item| {
+      let (schedule, parents) = new_schedule(item.schedule);
+      (item.id, schedule, parents)
+    })
+    .collect();
+
+  let (storage, mut mgr) = lock_for_write(&state).await;
+  let backup = state.backup(&mgr);
+  match mgr.create_schedules(items) {
+    Ok(ids) => {
+      commit(&state, storage, mgr, backup).await?;
+      Ok(ids)
+    }
+    Err(e) => Err(e.to_string()),
+  }
+}
+
+#[derive(Debug, Deserialize)]
+#[cfg_attr(
+  feature = "ts-bindings",
+  derive(ts_rs::TS),
+  ts(export, optional_fields = nullable)
+)]
 pub struct DeleteScheduleReq {
   pub id: ScheduleId,
 }
 
 #[derive(Debug, Serialize)]
+#[cfg_attr(feature = "ts-bindings", derive(ts_rs::TS), ts(export))]
 pub struct DeleteScheduleRes {
   pub removed: Vec<ScheduleId>,
 }
@@ -81,29 +283,157 @@ pub async fn delete_schedule(
   state: State<'_, AppState>,
   req: DeleteScheduleReq,
 ) -> Result<DeleteScheduleRes, String> {
-  let mut mgr = state.manager.write().await;
+  let (storage, mut mgr) = lock_for_write(&state).await;
+  let backup = state.backup(&mgr);
   match mgr.delete_schedule(req.id) {
     Ok(set) => {
       let removed: Vec<ScheduleId> = set.into_iter().collect();
-      let snapshot_mgr = &*mgr;
-      let mut s = state.storage.write().await;
-      s.persist_snapshot(snapshot_mgr);
+      commit(&state, storage, mgr, backup).await?;
       Ok(DeleteScheduleRes { removed })
     }
     Err(e) => Err(e.to_string()),
   }
 }
 
+/// Delete every id in `req` (with cascades) and persist once. Unknown ids
+/// are ignored.
+#[tauri::command]
+pub async fn delete_schedules(
+  state: State<'_, AppState>,
+  req: Vec<ScheduleId>,
+) -> Result<DeleteScheduleRes, String> {
+  let (storage, mut mgr) = lock_for_write(&state).await;
+  let backup = state.backup(&mgr);
+  match mgr.delete_schedules(&req) {
+    Ok(set) => {
+      let removed: Vec<ScheduleId> = set.into_iter().collect();
+      commit(&state, storage, mgr, backup).await?;
+      Ok(DeleteScheduleRes { removed })
+    }
+    Err(e) => Err(e.to_string()),
+  }
+}
+
+/// Replace the parents of `id` with `parents` (empty for a root) and return
+/// the updated node, e.g. after dragging a schedule into another group.
+#[tauri::command]
+pub async fn reparent_schedule(
+  state: State<'_, AppState>,
+  id: ScheduleId,
+  parents: Vec<ScheduleId>,
+) -> Result<ScheduleNode, String> {
+  let (storage, mut mgr) = lock_for_write(&state).await;
+  let backup = state.backup(&mgr);
+  match mgr.reparent(id, parents.into_iter().collect()) {
+    Ok(()) => {
+      let node = schedule_node(&mgr, id).ok_or_else(|| "Schedule not found".to_string());
+      commit(&state, storage, mgr, backup).await?;
+      node
+    }
+    Err(e) => Err(e.to_string()),
+  }
+}
+
+/// Remove all schedules and persist the now-empty state.
+#[tauri::command]
+pub async fn clear_schedules(state: State<'_, AppState>) -> Result<(), String> {
+  let (storage, mut mgr) = lock_for_write(&state).await;
+  let backup = state.backup(&mgr);
+  mgr.clear();
+  commit(&state, storage, mgr, backup).await?;
+  Ok(())
+}
+
+#[derive(Debug, Deserialize)]
+#[cfg_attr(
+  feature = "ts-bindings",
+  derive(ts_rs::TS),
+  ts(export, optional_fields = nullable)
+)]
+pub struct UpdateScheduleReq {
+  pub id: ScheduleId,
+  pub start: DateTime<Utc>,
+  pub end: DateTime<Utc>,
+  pub level: ScheduleLevel,
+  pub exclusive: bool,
+  pub name: String,
+  #[serde(default)]
+  pub description: Option<String>,
+  #[serde(default)]
+  #[cfg_attr(feature = "ts-bindings", ts(as = "Option<_>", optional))]
+  pub tags: Vec<String>,
+  #[serde(default)]
+  #[cfg_attr(feature = "ts-bindings", ts(as = "Option<_>", optional))]
+  pub priority: u8,
+  #[serde(default)]
+  pub color: Option<String>,
+  #[serde(default)]
+  #[cfg_attr(feature = "ts-bindings", ts(as = "Option<_>", optional))]
+  pub exclusive_scope: ExclusiveScope,
+}
+
+/// Edit an existing schedule in place, keeping its id and relations.
+///
+/// Edits that would leave children outside the new range or conflict with
+/// other schedules are rejected with the mapped `ScheduleError` message.
+#[tauri::command]
+pub async fn update_schedule(
+  state: State<'_, AppState>,
+  req: UpdateScheduleReq,
+) -> Result<(), String> {
+  let schedule = Schedule::new_with_meta(
+    req.start,
+    req.end,
+    req.level,
+    req.exclusive,
+    req.name,
+    req.description,
+    req.tags,
+  )
+  .with_priority(req.priority)
+  .with_color(req.color)
+  .with_exclusive_scope(req.exclusive_scope);
+
+  let (storage, mut mgr) = lock_for_write(&state).await;
+  let backup = state.backup(&mgr);
+  match mgr.update_schedule(req.id, schedule) {
+    Ok(()) => {
+      commit(&state, storage, mgr, backup).await?;
+      Ok(())
+    }
+    Err(e) => Err(e.to_string()),
+  }
+}
+
 #[derive(Debug, Deserialize, Default)]
+#[cfg_attr(
+  feature = "ts-bindings",
+  derive(ts_rs::TS),
+  ts(export, optional_fields = nullable)
+)]
 pub struct QueryReq {
   pub name: Option<String>,
+  pub tag: Option<String>,
+  #[serde(default)]
+  #[cfg_attr(feature = "ts-bindings", ts(as = "Option<_>", optional))]
+  pub tags_any: Vec<String>,
+  #[serde(default)]
+  #[cfg_attr(feature = "ts-bindings", ts(as = "Option<_>", optional))]
+  pub tags_all: Vec<String>,
   pub start: Option<DateTime<Utc>>,
   pub stop: Option<DateTime<Utc>>,
   pub level: Option<ScheduleLevel>,
+  pub level_min: Option<ScheduleLevel>,
+  pub level_max: Option<ScheduleLevel>,
   pub exclusive: Option<bool>,
+  pub sort: Option<SortOrder>,
+  /// Name of a matcher registered on the manager; see
+  /// `QueryOptions::named_matcher`.
+  pub named_matcher: Option<String>,
 }
 
 #[derive(Debug, Serialize)]
+#[cfg_attr(feature = "ts-bindings", derive(ts_rs::TS), ts(export))]
 pub struct QueryItem {
   pub id: ScheduleId,
   pub start: DateTime<Utc>,
@@ -111,6 +441,57 @@ pub struct QueryItem {
   pub level: ScheduleLevel,
   pub exclusive: bool,
   pub name: String,
+  pub description: Option<String>,
+  pub tags: Vec<String>,
+  pub priority: u8,
+  pub color: Option<String>,
+  pub exclusive_scope: ExclusiveScope,
+}
+
+/// One page of a larger result set.
+#[derive(Debug, Serialize)]
+#[cfg_attr(feature = "ts-bindings", derive(ts_rs::TS), ts(export))]
+pub struct Page<T> {
+  pub items: Vec<T>,
+  /// Number of matches across all pages.
+  pub total: usize,
+  /// Zero-based index of this page.
+  pub page: usize,
+  pub page_size: usize,
+}
+
+fn query_options(req: QueryReq) -> QueryOptions {
+  QueryOptions {
+    name: req.name,
+    tag: req.tag,
+    tags_any: req.tags_any,
+    tags_all: req.tags_all,
+    start: req.start,
+    stop: req.stop,
+    level: req.level,
+    level_min: req.level_min,
+    level_max: req.level_max,
+    exclusive: req.exclusive,
+    sort: req.sort,
+    named_matcher: req.named_matcher,
+    ..Default::default()
+  }
+}
+
+fn query_item(id: ScheduleId, s: &Schedule) -> QueryItem {
+  QueryItem {
+    id,
+    start: s.start(),
+    end: s.end(),
+    level: s.level(),
+    exclusive: s.exclusive(),
+    name: s.name().to_string(),
+    description: s.description.clone(),
+    tags: s.tags.clone(),
+    priority: s.priority(),
+    color: s.color.clone(),
+    exclusive_scope: s.exclusive_scope(),
+  }
 }
 
 #[tauri::command]
@@ -119,27 +500,71 @@ pub async fn query_schedules(
   req: QueryReq,
 ) -> Result<Vec<QueryItem>, String> {
   let mgr = state.manager.read().await;
-  let opts = QueryOptions {
-    name: req.name,
-    start: req.start,
-    stop: req.stop,
-    level: req.level,
-    exclusive: req.exclusive,
-    matcher: None,
-  };
+  let res = mgr.query_schedule(query_options(req));
+  Ok(res.iter().map(|(id, s)| query_item(*id, s)).collect())
+}
+
+/// Like `query_schedules`, but returns only page `page` (zero-based) of
+/// `page_size` items together with the total match count, so large result
+/// sets can be rendered incrementally. Results are ordered by start time
+/// unless `req.sort` says otherwise, so pages are stable between calls.
+#[tauri::command]
+pub async fn query_schedules_paged(
+  state: State<'_, AppState>,
+  req: QueryReq,
+  page: usize,
+  page_size: usize,
+) -> Result<Page<QueryItem>, String> {
+  if page_size == 0 {
+    return Err("page_size must be greater than zero".to_string());
+  }
+  let mut opts = query_options(req);
+  opts.sort.get_or_insert(SortOrder::StartAsc);
+
+  let mgr = state.manager.read().await;
   let res = mgr.query_schedule(opts);
   let items = res
+    .iter()
+    .skip(page.saturating_mul(page_size))
+    .take(page_size)
+    .map(|(id, s)| query_item(*id, s))
+    .collect();
+  Ok(Page {
+    items,
+    total: res.len(),
+    page,
+    page_size,
+  })
+}
+
+#[derive(Debug, Serialize)]
+#[cfg_attr(feature = "ts-bindings", derive(ts_rs::TS), ts(export))]
+pub struct DayBucket {
+  /// UTC date as `YYYY-MM-DD`.
+  pub date: String,
+  pub schedules: Vec<QueryItem>,
+}
+
+/// Schedules overlapping `[start, end)` bucketed per UTC day, one bucket for
+/// every day in the range. A schedule spanning several days is listed under
+/// each of them. Windows longer than `MAX_DAY_BUCKETS` days are rejected.
+#[tauri::command]
+pub async fn query_days(
+  state: State<'_, AppState>,
+  start: DateTime<Utc>,
+  end: DateTime<Utc>,
+) -> Result<Vec<DayBucket>, String> {
+  let mgr = state.manager.read().await;
+  let days = mgr
+    .schedules_by_day(start, end)
+    .map_err(|e| e.to_string())?
     .into_iter()
-    .map(|(id, s)| QueryItem {
-      id,
-      start: s.start(),
-      end: s.end(),
-      level: s.level(),
-      exclusive: s.exclusive(),
-      name: s.name().to_string(),
+    .map(|(date, schedules)| DayBucket {
+      date: date.to_string(),
+      schedules: schedules.iter().map(|(id, s)| query_item(*id, s)).collect(),
     })
     .collect();
-  Ok(items)
+  Ok(days)
 }
 
 #[tauri::command]
@@ -148,23 +573,337 @@ pub async fn get_schedule(
   id: ScheduleId,
 ) -> Result<Option<QueryItem>, String> {
   let mgr = state.manager.read().await;
-  let opt = mgr.get_schedule(id).cloned();
-  Ok(opt.map(|s| QueryItem {
+  Ok(mgr.get_schedule(id).map(|s| query_item(id, s)))
+}
+
+/// Cheap existence check, e.g. to drop stale ids after a sync without
+/// fetching the records.
+#[tauri::command]
+pub async fn schedule_exists(state: State<'_, AppState>, id: ScheduleId) -> Result<bool, String> {
+  let mgr = state.manager.read().await;
+  Ok(mgr.contains(id))
+}
+
+#[derive(Debug, Serialize)]
+#[cfg_attr(feature = "ts-bindings", derive(ts_rs::TS), ts(export))]
+pub struct ScheduleNode {
+  pub id: ScheduleId,
+  pub start: DateTime<Utc>,
+  pub end: DateTime<Utc>,
+  pub level: ScheduleLevel,
+  pub exclusive: bool,
+  pub name: String,
+  pub description: Option<String>,
+  pub tags: Vec<String>,
+  pub priority: u8,
+  pub color: Option<String>,
+  pub exclusive_scope: ExclusiveScope,
+  pub parents: Vec<ScheduleId>,
+  pub children: Vec<ScheduleId>,
+}
+
+/// Fetch a schedule and all of its transitive children, with relations, in
+/// one call.
+///
+/// Nodes are ordered parents-before-children: a node only appears once
+/// every parent of it that lies inside the subtree has been emitted, so the
+/// UI can build the tree incrementally. Returns an empty list when `id`
+/// does not exist.
+#[tauri::command]
+pub async fn get_subtree(
+  state: State<'_, AppState>,
+  id: ScheduleId,
+) -> Result<Vec<ScheduleNode>, String> {
+  let mgr = state.manager.read().await;
+  if mgr.get_schedule(id).is_none() {
+    return Ok(Vec::new());
+  }
+  let mut members = vec![id];
+  members.extend(mgr.descendants(id));
+  let in_tree: HashSet<ScheduleId> = members.iter().copied().collect();
+
+  // Kahn's algorithm restricted to the subtree; BFS order alone is not
+  // enough when a node is reachable through paths of different lengths.
+  let mut pending: HashMap<ScheduleId, usize> = members
+    .iter()
+    .map(|&m| {
+      let n = mgr
+        .parent_relations()
+        .get(&m)
+        .map(|p| p.iter().filter(|p| in_tree.contains(p)).count())
+        .unwrap_or(0);
+      (m, n)
+    })
+    .collect();
+  let mut ready = VecDeque::from([id]);
+  let mut nodes = Vec::with_capacity(members.len());
+  while let Some(cur) = ready.pop_front() {
+    let Some(node) = schedule_node(&mgr, cur) else {
+      continue;
+    };
+    for child in &node.children {
+      if let Some(n) = pending.get_mut(child) {
+        *n -= 1;
+        if *n == 0 {
+          ready.push_back(*child);
+        }
+      }
+    }
+    nodes.push(node);
+  }
+  Ok(nodes)
+}
+
+type StorageGuard<'a> = RwLockWriteGuard<'a, Box<dyn Storage + Send + Sync>>;
+
+/// Take the locks a mutating command needs: storage first, then manager.
+///
+/// The storage lock is held until the command has persisted its change,
+/// which serializes writers and lets [`commit`] hand the manager back to
+/// readers during disk I/O.
+async fn lock_for_write(
+  state: &AppState,
+) -> (StorageGuard<'_>, RwLockWriteGuard<'_, ScheduleManager>) {
+  let storage = state.storage.write().await;
+  let manager = state.manager.write().await;
+  (storage, manager)
+}
+
+/// Persist the manager after a successful mutation.
+///
+/// The whole state is written in one store transaction, so cascaded
+/// deletes and relation changes land together. The manager lock is
+/// downgraded for the write, so queries are not blocked by disk I/O; only
+/// other writers wait, on `storage`. If the write fails the in-memory state
+/// is restored from `backup`, taken just before the mutation, so memory and
+/// disk stay consistent. No other writer can have run in between, but
+/// readers may briefly have seen the change that was rolled back.
+///
+/// In bulk mode nothing is written and `backup` is `None`; the held
+/// `storage` lock keeps the mode from changing in between.
+async fn commit<'a>(
+  state: &'a AppState,
+  mut storage: StorageGuard<'a>,
+  mgr: RwLockWriteGuard<'a, ScheduleManager>,
+  backup: Option<ScheduleManager>,
+) -> Result<(), String> {
+  if state.bulk.load(Ordering::Relaxed) {
+    return Ok(());
+  }
+  let mgr = mgr.downgrade();
+  if let Err(e) = storage.commit(&mgr) {
+    drop(mgr);
+    if let Some(backup) = backup {
+      *state.manager.write().await = backup;
+    }
+    return Err(e.to_string());
+  }
+  Ok(())
+}
+
+/// Suspend per-command persistence for a bulk import; see
+/// [`AppState::begin_bulk`].
+#[tauri::command]
+pub async fn begin_bulk(state: State<'_, AppState>) -> Result<(), String> {
+  state.begin_bulk().await;
+  Ok(())
+}
+
+/// Persist everything changed since `begin_bulk` in one write and resume
+/// per-command persistence; see [`AppState::end_bulk`].
+#[tauri::command]
+pub async fn end_bulk(state: State<'_, AppState>) -> Result<(), String> {
+  state.end_bulk().await
+}
+
+/// Assemble the DTO for `id` including its direct parents and children.
+fn schedule_node(mgr: &ScheduleManager, id: ScheduleId) -> Option<ScheduleNode> {
+  let (s, parents, children) = mgr.get_schedule_with_relations(id)?;
+  Some(ScheduleNode {
     id,
     start: s.start(),
     end: s.end(),
     level: s.level(),
     exclusive: s.exclusive(),
     name: s.name().to_string(),
-  }))
+    description: s.description.clone(),
+    tags: s.tags.clone(),
+    priority: s.priority(),
+    color: s.color.clone(),
+    exclusive_scope: s.exclusive_scope(),
+    parents,
+    children,
+  })
+}
+
+/// Distinct tags across all schedules, sorted, for a tag picker.
+#[tauri::command]
+pub async fn list_tags(state: State<'_, AppState>) -> Result<Vec<String>, String> {
+  let mgr = state.manager.read().await;
+  Ok(mgr.all_tags().into_iter().collect())
 }
 
 /// Helper to register all Tauri command handlers on a `tauri::Builder`.
 pub fn register<R: tauri::Runtime>(builder: tauri::Builder<R>) -> tauri::Builder<R> {
   builder.invoke_handler(tauri::generate_handler![
+    set_storage_path,
+    begin_bulk,
+    end_bulk,
     create_schedule,
+    create_schedule_id,
+    validate_schedule,
+    batch_create_schedules,
     delete_schedule,
+    delete_schedules,
+    clear_schedules,
+    reparent_schedule,
+    update_schedule,
     query_schedules,
+    query_schedules_paged,
+    query_days,
     get_schedule,
+    schedule_exists,
+    get_subtree,
+    list_tags,
   ])
 }
+
+#[cfg(test)]
+mod tests {
+  use std::sync::atomic::AtomicUsize;
+  use std::sync::Arc;
+
+  use chrono::Duration;
+
+  use super::*;
+  use crate::storage::MockStorage;
+
+  /// Counts commits and fails every one of them when `fail` is set.
+  struct CountingStorage {
+    commits: Arc<AtomicUsize>,
+    fail: bool,
+  }
+
+  impl CountingStorage {
+    fn new(fail: bool) -> (Self, Arc<AtomicUsize>) {
+      let commits = Arc::new(AtomicUsize::new(0));
+      let storage = Self {
+        commits: commits.clone(),
+        fail,
+      };
+      (storage, commits)
+    }
+  }
+
+  impl Storage for CountingStorage {
+    fn load(&self, _manager: &mut ScheduleManager) -> Result<(), StorageError> {
+      Ok(())
+    }
+
+    fn commit(&mut self, _manager: &ScheduleManager) -> Result<(), StorageError> {
+      self.commits.fetch_add(1, Ordering::Relaxed);
+      if self.fail {
+        return Err(StorageError::Serialize("disk full".into()));
+      }
+      Ok(())
+    }
+  }
+
+  fn block_on<F: std::future::Future>(f: F) -> F::Output {
+    tokio::runtime::Runtime::new().unwrap().block_on(f)
+  }
+
+  /// Create one schedule the way the mutating commands do.
+  async fn create_one(state: &AppState) -> Result<ScheduleId, String> {
+    let (storage, mut mgr) = lock_for_write(state).await;
+    let backup = state.backup(&mgr);
+    let start = Utc::now();
+    let schedule = Schedule::new(start, start + Duration::hours(1), 0, false, "a".into());
+    let id = mgr
+      .create_schedule(schedule, HashSet::new())
+      .map_err(|e| e.to_string())?;
+    commit(state, storage, mgr, backup).await?;
+    Ok(id)
+  }
+
+  #[test]
+  fn failed_commit_restores_the_previous_state() {
+    block_on(async {
+      let (storage, commits) = CountingStorage::new(true);
+      let state = AppState::new(storage).unwrap();
+
+      assert!(create_one(&state).await.is_err());
+      assert_eq!(commits.load(Ordering::Relaxed), 1);
+      assert!(state.manager.read().await.is_empty());
+    });
+  }
+
+  #[test]
+  fn bulk_mode_defers_writes_until_end_bulk() {
+    block_on(async {
+      let (storage, commits) = CountingStorage::new(false);
+      let state = AppState::new(storage).unwrap();
+
+      state.begin_bulk().await;
+      create_one(&state).await.unwrap();
+      create_one(&state).await.unwrap();
+      assert_eq!(commits.load(Ordering::Relaxed), 0);
+
+      state.end_bulk().await.unwrap();
+      assert_eq!(commits.load(Ordering::Relaxed), 1);
+      create_one(&state).await.unwrap();
+      assert_eq!(commits.load(Ordering::Relaxed), 2);
+      assert_eq!(state.manager.read().await.len(), 3);
+    });
+  }
+
+  #[test]
+  fn failed_end_bulk_keeps_changes_and_bulk_mode() {
+    block_on(async {
+      let (storage, commits) = CountingStorage::new(true);
+      let state = AppState::new(storage).unwrap();
+
+      state.begin_bulk().await;
+      let id = create_one(&state).await.unwrap();
+      assert!(state.end_bulk().await.is_err());
+      assert!(state.bulk.load(Ordering::Relaxed));
+      assert!(state.manager.read().await.get_schedule(id).is_some());
+
+      // Still deferred: no backup is taken and nothing is written
+      create_one(&state).await.unwrap();
+      assert_eq!(commits.load(Ordering::Relaxed), 1);
+      assert_eq!(state.manager.read().await.len(), 2);
+    });
+  }
+
+  #[test]
+  fn replace_storage_flushes_bulk_changes_first() {
+    block_on(async {
+      let (storage, commits) = CountingStorage::new(false);
+      let state = AppState::new(storage).unwrap();
+
+      state.begin_bulk().await;
+      create_one(&state).await.unwrap();
+      state.replace_storage(MockStorage::new()).await.unwrap();
+
+      assert_eq!(commits.load(Ordering::Relaxed), 1);
+      assert!(!state.bulk.load(Ordering::Relaxed));
+      assert!(state.manager.read().await.is_empty());
+    });
+  }
+
+  #[test]
+  fn replace_storage_is_refused_when_the_flush_fails() {
+    block_on(async {
+      let (storage, _) = CountingStorage::new(true);
+      let state = AppState::new(storage).unwrap();
+
+      state.begin_bulk().await;
+      let id = create_one(&state).await.unwrap();
+      assert!(state.replace_storage(MockStorage::new()).await.is_err());
+
+      assert!(state.bulk.load(Ordering::Relaxed));
+      assert!(state.manager.read().await.get_schedule(id).is_some());
+    });
+  }
+}
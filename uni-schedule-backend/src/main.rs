@@ -1,12 +1,15 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
-use uni_schedule_lib::commands::{register, AppState};
+use uni_schedule_lib::commands::{AppState, register};
 use uni_schedule_lib::storage::SledStorage;
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
-  let storage = SledStorage::open(None);
-  let state = AppState::new(storage);
+  // Allow alternate installs and tests to relocate the database.
+  let base_dir = std::env::var_os("UNI_SCHEDULE_DATA_DIR").map(std::path::PathBuf::from);
+  let storage = SledStorage::open(base_dir);
+  // Refuse to start on unreadable data rather than overwrite it
+  let state = AppState::new(storage).expect("failed to load stored schedules");
 
   let builder = tauri::Builder::default()
     .plugin(tauri_plugin_opener::init())
@@ -1,15 +1,141 @@
 use std::path::PathBuf;
 
-use uni_schedule_core::schedule::ScheduleManager;
+use thiserror::Error;
+use uni_schedule_core::schedule::{Schedule, ScheduleManager, Snapshot, SNAPSHOT_VERSION};
+
+/// Key under which the serialized manager is stored.
+const MANAGER_KEY: &str = "manager";
+/// Key holding the layout version of the value under `MANAGER_KEY`, as a
+/// big-endian `u32`. Databases written before versioning lack it; see
+/// [`UNTAGGED_VERSION`].
+const VERSION_KEY: &str = "version";
+
+/// Layout of databases without a version tag: the first build that
+/// persisted the manager already stored description, tags, priority and
+/// color, and wrote no tag until the layout became versioned.
+const UNTAGGED_VERSION: u32 = 2;
+
+/// Layout version written by [`Storage::commit`].
+///
+/// Policy for changing the persisted shape (e.g. a new `Schedule` field):
+/// bincode is not self-describing, so `#[serde(default)]` alone cannot read
+/// old data. Before changing the shape, copy the current layout into a
+/// frozen `vN` module below, bump this constant, and add an arm to
+/// [`migrate`] that decodes the old layout and fills in defaults. Old
+/// databases are upgraded on load and rewritten in the new layout by the
+/// next commit.
+pub const STORAGE_VERSION: u32 = 3;
+
+/// Failure to persist the manager.
+#[derive(Debug, Error)]
+pub enum StorageError {
+  #[error("failed to serialize schedules: {0}")]
+  Serialize(String),
+  #[error("failed to write schedules: {0}")]
+  Db(#[from] sled::Error),
+  #[error("failed to read schedules stored with layout v{version}: {reason}")]
+  Migrate { version: u32, reason: String },
+  #[error("schedules were stored by a newer version (layout v{0})")]
+  UnsupportedVersion(u32),
+  #[error("stored layout version is malformed")]
+  MalformedVersion,
+}
+
+/// Layouts of earlier storage versions, frozen so old data stays readable.
+mod v2 {
+  use std::collections::{HashMap, HashSet};
+
+  use chrono::{DateTime, Utc};
+  use serde::{Deserialize, Serialize};
+  use uni_schedule_core::schedule::{ScheduleId, ScheduleLevel};
+
+  /// `Schedule` before the exclusive scope was added.
+  #[derive(Serialize, Deserialize)]
+  pub struct Schedule {
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub level: ScheduleLevel,
+    pub exclusive: bool,
+    pub name: String,
+    pub description: Option<String>,
+    pub tags: Vec<String>,
+    pub priority: u8,
+    pub color: Option<String>,
+  }
+
+  /// The serialized `ScheduleManager` of that time.
+  #[derive(Serialize, Deserialize)]
+  pub struct Manager {
+    pub schedules: HashMap<ScheduleId, Schedule>,
+    pub parent_relations: HashMap<ScheduleId, HashSet<ScheduleId>>,
+    #[allow(dead_code)]
+    pub child_relations: HashMap<ScheduleId, HashSet<ScheduleId>>,
+    #[allow(dead_code)]
+    pub level_index: HashMap<ScheduleLevel, HashSet<ScheduleId>>,
+  }
+}
+
+/// Decode a manager stored with layout `version`, upgrading older layouts
+/// to the current one with default values for fields they lack.
+pub fn migrate(version: u32, bytes: &[u8]) -> Result<ScheduleManager, StorageError> {
+  let failed = |reason: String| StorageError::Migrate { version, reason };
+  match version {
+    2 => {
+      let old: v2::Manager = bincode::deserialize(bytes).map_err(|e| failed(e.to_string()))?;
+      let schedules = old
+        .schedules
+        .into_iter()
+        .map(|(id, s)| {
+          let schedule = Schedule::new_with_meta(
+            s.start,
+            s.end,
+            s.level,
+            s.exclusive,
+            s.name,
+            s.description,
+            s.tags,
+          )
+          .with_priority(s.priority)
+          .with_color(s.color);
+          (id, schedule)
+        })
+        .collect();
+      ScheduleManager::from_snapshot(Snapshot {
+        version: SNAPSHOT_VERSION,
+        schedules,
+        parents: old.parent_relations,
+      })
+      .map_err(|e| failed(e.to_string()))
+    }
+    STORAGE_VERSION => bincode::deserialize(bytes).map_err(|e| failed(e.to_string())),
+    newer if newer > STORAGE_VERSION => Err(StorageError::UnsupportedVersion(newer)),
+    unknown => Err(failed(format!("no layout v{unknown} was ever written"))),
+  }
+}
 
 /// Persistence abstraction for the schedule manager.
 ///
-/// Note: Methods are infallible by design to keep the trait simple; implementations
-/// should handle errors internally (log or best-effort). Consumers can snapshot
-/// the manager via serde and pass it to `save` without moving the live instance.
+/// The manager itself knows nothing about storage; the backend holds a
+/// `Box<dyn Storage>` next to it, so another store (SQLite, a remote
+/// service) only needs to implement this trait.
+///
+/// Note: `save` is infallible by design to keep the trait simple;
+/// implementations should handle errors internally (log or best-effort).
+/// Mutating commands use `commit` instead, which reports failure so the caller
+/// can roll back its in-memory change.
 pub trait Storage {
   fn save(&mut self, manager: ScheduleManager);
-  fn load(&self, manager: &mut ScheduleManager);
+
+  /// Replace `manager` with the stored state, leaving it untouched when
+  /// nothing is stored yet.
+  ///
+  /// Stored data that cannot be read is an error rather than an empty
+  /// manager: the next `commit` would otherwise overwrite it.
+  fn load(&self, manager: &mut ScheduleManager) -> Result<(), StorageError>;
+
+  /// Write the complete state of `manager` in a single transaction: either
+  /// every schedule and relation is stored or the previous state is kept.
+  fn commit(&mut self, manager: &ScheduleManager) -> Result<(), StorageError>;
 }
 
 /// Sled-based persistent storage. The entire `ScheduleManager` is serialized
@@ -22,71 +148,69 @@ impl SledStorage {
   /// Open or create the storage at the provided base directory. When `base_dir`
   /// is None, a platform-specific local data directory is used.
   pub fn open(base_dir: Option<PathBuf>) -> Self {
+    Self::try_open(base_dir).expect("failed to open sled database")
+  }
+
+  /// Fallible variant of [`SledStorage::open`] for paths supplied at runtime.
+  pub fn try_open(base_dir: Option<PathBuf>) -> sled::Result<Self> {
     let base = base_dir
       .or_else(|| dirs::data_local_dir())
       .unwrap_or_else(|| std::env::current_dir().unwrap())
       .join("uni-schedule");
     let _ = std::fs::create_dir_all(&base);
     let path = base.join("db");
-    let db = sled::open(path).expect("failed to open sled database");
-    Self { db }
-  }
-
-  // /// Helper to persist a snapshot of a live manager by cloning via serde.
-  // /// This avoids moving the actual manager, which is useful when the `Storage`
-  // /// trait consumes the argument.
-  // pub fn persist_snapshot(&mut self, manager_ref: &ScheduleManager) {
-  //   match bincode::serialize(manager_ref) {
-  //     Ok(bytes) => match bincode::deserialize::<ScheduleManager>(&bytes) {
-  //       Ok(snapshot) => self.save(snapshot),
-  //       Err(e) => eprintln!("storage: failed to clone manager for persist: {e}"),
-  //     },
-  //     Err(e) => eprintln!("storage: failed to serialize manager for persist: {e}"),
-  //   }
-  // }
+    let db = sled::open(path)?;
+    Ok(Self { db })
+  }
 }
 
 impl Storage for SledStorage {
   fn save(&mut self, manager: ScheduleManager) {
-    // match bincode::serialize(&manager) {
-    //   Ok(bytes) => {
-    //     if let Err(e) = self.db.insert("manager", bytes.as_slice()) {
-    //       eprintln!("storage: failed to write to db: {e}");
-    //     }
-    //     if let Err(e) = self.db.flush() {
-    //       eprintln!("storage: failed to flush db: {e}");
-    //     }
-    //   }
-    //   Err(e) => eprintln!("storage: failed to serialize manager: {e}"),
-    // }
-
-    todo!("Implement SledStorage::save");
-  }
-
-  fn load(&self, manager: &mut ScheduleManager) {
-    // match self.db.get("manager") {
-    //   Ok(Some(ivec)) => match bincode::deserialize::<ScheduleManager>(&ivec) {
-    //     Ok(loaded) => {
-    //       *manager = loaded;
-    //     }
-    //     Err(e) => eprintln!("storage: failed to deserialize manager: {e}"),
-    //   },
-    //   Ok(None) => {
-    //     // No prior state; keep the provided default instance
-    //   }
-    //   Err(e) => eprintln!("storage: failed to read from db: {e}"),
-    // }
-
-    todo!("Implement SledStorage::load");
+    if let Err(e) = self.commit(&manager) {
+      eprintln!("storage: {e}");
+    }
+  }
+
+  fn load(&self, manager: &mut ScheduleManager) -> Result<(), StorageError> {
+    let version = match self.db.get(VERSION_KEY)? {
+      Some(ivec) => <[u8; 4]>::try_from(ivec.as_ref())
+        .map(u32::from_be_bytes)
+        .map_err(|_| StorageError::MalformedVersion)?,
+      None => UNTAGGED_VERSION,
+    };
+    // No prior state keeps the provided default instance
+    if let Some(ivec) = self.db.get(MANAGER_KEY)? {
+      let mut loaded = migrate(version, &ivec)?;
+      // A partial write can leave a relation on only one side
+      let repaired = loaded.reconcile_relations();
+      if repaired > 0 {
+        eprintln!("warning: repaired {repaired} inconsistent schedule relation(s) on load");
+      }
+      *manager = loaded;
+    }
+    Ok(())
+  }
+
+  fn commit(&mut self, manager: &ScheduleManager) -> Result<(), StorageError> {
+    // The whole manager lives under one key, and it is written together
+    // with its layout version in one atomic batch; the flush makes it
+    // durable before returning.
+    let bytes = bincode::serialize(manager).map_err(|e| StorageError::Serialize(e.to_string()))?;
+    let mut batch = sled::Batch::default();
+    batch.insert(MANAGER_KEY, bytes);
+    batch.insert(VERSION_KEY, STORAGE_VERSION.to_be_bytes().to_vec());
+    self.db.apply_batch(batch)?;
+    self.db.flush()?;
+    Ok(())
   }
 }
 
 /// Simple in-memory storage useful for tests and ephemeral runs.
 ///
-/// Stores the manager inside a `RefCell<Option<ScheduleManager>>`. The
-/// `save` method replaces the stored value. The `load` method clones the
-/// stored manager by serializing with `bincode` and deserializing a fresh
-/// instance so `ScheduleManager` is not required to implement `Clone`.
+/// Keeps the last saved manager in memory and never touches disk. `save`
+/// and `commit` replace the stored value; `load` clones it into the caller's
+/// manager.
+#[derive(Default)]
 pub struct MockStorage {
   stored: Option<ScheduleManager>,
 }
@@ -103,9 +227,141 @@ impl Storage for MockStorage {
     self.stored = Some(manager);
   }
 
-  fn load(&self, manager: &mut ScheduleManager) {
+  fn load(&self, manager: &mut ScheduleManager) -> Result<(), StorageError> {
     if let Some(stored) = &self.stored {
-      manager.clone_from(&stored);
+      manager.clone_from(stored);
     }
+    Ok(())
+  }
+
+  fn commit(&mut self, manager: &ScheduleManager) -> Result<(), StorageError> {
+    self.stored = Some(manager.clone());
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::collections::{HashMap, HashSet};
+
+  use chrono::{Duration, Utc};
+  use uni_schedule_core::schedule::{ExclusiveScope, ScheduleId};
+  use uuid::Uuid;
+
+  use super::*;
+
+  /// A fresh database under the system temp directory.
+  fn temp_storage() -> SledStorage {
+    let dir = std::env::temp_dir().join(format!("uni-schedule-test-{}", Uuid::now_v7()));
+    SledStorage::try_open(Some(dir)).unwrap()
+  }
+
+  /// Bytes of a one-schedule manager in the v2 layout.
+  fn v2_bytes(id: ScheduleId) -> Vec<u8> {
+    let start = Utc::now();
+    let schedule = v2::Schedule {
+      start,
+      end: start + Duration::hours(1),
+      level: 1,
+      exclusive: true,
+      name: "standup".into(),
+      description: Some("daily".into()),
+      tags: vec!["team".into()],
+      priority: 3,
+      color: Some("#ff0000".into()),
+    };
+    bincode::serialize(&v2::Manager {
+      schedules: HashMap::from([(id, schedule)]),
+      parent_relations: HashMap::from([(id, HashSet::new())]),
+      child_relations: HashMap::new(),
+      level_index: HashMap::from([(1, HashSet::from([id]))]),
+    })
+    .unwrap()
+  }
+
+  #[test]
+  fn migrate_v2_keeps_metadata_and_defaults_scope() {
+    let id = Uuid::now_v7();
+    let mgr = migrate(2, &v2_bytes(id)).unwrap();
+    let s = mgr.get_schedule(id).unwrap();
+    assert_eq!(s.name(), "standup");
+    assert_eq!(s.description(), Some("daily"));
+    assert_eq!(s.tags(), ["team".to_string()]);
+    assert_eq!(s.priority(), 3);
+    assert_eq!(s.color(), Some("#ff0000"));
+    assert_eq!(s.exclusive_scope(), ExclusiveScope::default());
+  }
+
+  #[test]
+  fn migrate_current_version_round_trips() {
+    let mut mgr = ScheduleManager::new();
+    let start = Utc::now();
+    let schedule = Schedule::new(start, start + Duration::hours(1), 0, true, "a".into())
+      .with_exclusive_scope(ExclusiveScope::AllLevels);
+    let id = mgr
+      .create_schedule(schedule.clone(), HashSet::new())
+      .unwrap();
+
+    let bytes = bincode::serialize(&mgr).unwrap();
+    let back = migrate(STORAGE_VERSION, &bytes).unwrap();
+    assert_eq!(back.get_schedule(id), Some(&schedule));
+  }
+
+  #[test]
+  fn migrate_rejects_unknown_versions() {
+    assert!(matches!(
+      migrate(STORAGE_VERSION + 1, &[]),
+      Err(StorageError::UnsupportedVersion(_))
+    ));
+    assert!(matches!(
+      migrate(1, &[]),
+      Err(StorageError::Migrate { version: 1, .. })
+    ));
+  }
+
+  #[test]
+  fn untagged_data_loads_as_v2() {
+    let id = Uuid::now_v7();
+    let storage = temp_storage();
+    storage.db.insert(MANAGER_KEY, v2_bytes(id)).unwrap();
+
+    let mut mgr = ScheduleManager::new();
+    storage.load(&mut mgr).unwrap();
+    assert_eq!(mgr.get_schedule(id).unwrap().tags(), ["team".to_string()]);
+  }
+
+  #[test]
+  fn unreadable_data_fails_to_load() {
+    let storage = temp_storage();
+    storage.db.insert(MANAGER_KEY, vec![1, 2, 3]).unwrap();
+    storage
+      .db
+      .insert(VERSION_KEY, (STORAGE_VERSION + 1).to_be_bytes().to_vec())
+      .unwrap();
+
+    let mut mgr = ScheduleManager::new();
+    assert!(matches!(
+      storage.load(&mut mgr),
+      Err(StorageError::UnsupportedVersion(_))
+    ));
+    assert!(mgr.is_empty());
+  }
+
+  #[test]
+  fn commit_then_load_round_trips() {
+    let mut storage = temp_storage();
+    let mut mgr = ScheduleManager::new();
+    let start = Utc::now();
+    let id = mgr
+      .create_schedule(
+        Schedule::new(start, start + Duration::hours(2), 0, false, "b".into()),
+        HashSet::new(),
+      )
+      .unwrap();
+    storage.commit(&mgr).unwrap();
+
+    let mut loaded = ScheduleManager::new();
+    storage.load(&mut loaded).unwrap();
+    assert_eq!(loaded.get_schedule(id), mgr.get_schedule(id));
   }
 }
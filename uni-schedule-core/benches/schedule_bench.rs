@@ -22,7 +22,7 @@ fn bench_create_and_query(c: &mut Criterion) {
         }
 
         // run a query that overlaps roughly half of them
-        let qstart = start + Duration::hours((n as i64));
+        let qstart = start + Duration::hours(n as i64);
         let qend = qstart + Duration::hours(20);
         let opts = uni_schedule_core::schedule::QueryOptions::builder()
           .start(qstart)
@@ -37,5 +37,42 @@ fn bench_create_and_query(c: &mut Criterion) {
   group.finish();
 }
 
-criterion_group!(benches, bench_create_and_query);
+/// Load 10k schedules into a fresh manager versus a pre-sized one.
+fn bench_load_presized(c: &mut Criterion) {
+  const N: usize = 10_000;
+  let start = Utc::now();
+  let schedules: Vec<Schedule> = (0..N)
+    .map(|i| {
+      let s = start + Duration::hours((i as i64) * 2);
+      Schedule::new(s, s + Duration::hours(1), 10, false, format!("task-{i}"))
+    })
+    .collect();
+
+  let mut group = c.benchmark_group("load_10k");
+  group.bench_function("new", |b| {
+    b.iter(|| {
+      let mut mgr = ScheduleManager::new();
+      for schedule in &schedules {
+        mgr
+          .create_schedule(schedule.clone(), HashSet::new())
+          .unwrap();
+      }
+      std::hint::black_box(mgr.len());
+    })
+  });
+  group.bench_function("with_capacity", |b| {
+    b.iter(|| {
+      let mut mgr = ScheduleManager::with_capacity(N);
+      for schedule in &schedules {
+        mgr
+          .create_schedule(schedule.clone(), HashSet::new())
+          .unwrap();
+      }
+      std::hint::black_box(mgr.len());
+    })
+  });
+  group.finish();
+}
+
+criterion_group!(benches, bench_create_and_query, bench_load_presized);
 criterion_main!(benches);
@@ -0,0 +1,75 @@
+/// Quote a field per RFC 4180 when it contains a delimiter, quote or line
+/// break.
+pub(crate) fn escape_field(field: &str) -> String {
+  if field.contains([',', '"', '\n', '\r']) {
+    format!("\"{}\"", field.replace('"', "\"\""))
+  } else {
+    field.to_string()
+  }
+}
+
+/// Split a CSV document into records of fields per RFC 4180.
+///
+/// Quoted fields may contain commas, doubled quotes and line breaks.
+/// Blank lines are skipped. On failure returns the one-based line number
+/// where the problem was found and a reason.
+pub(crate) fn parse_records(input: &str) -> Result<Vec<Vec<String>>, (usize, String)> {
+  let mut records = Vec::new();
+  let mut record = Vec::new();
+  let mut field = String::new();
+  let mut in_quotes = false;
+  let mut field_started = false;
+  let mut line = 1;
+  let mut chars = input.chars().peekable();
+
+  while let Some(c) = chars.next() {
+    if in_quotes {
+      match c {
+        '"' if chars.peek() == Some(&'"') => {
+          chars.next();
+          field.push('"');
+        }
+        '"' => in_quotes = false,
+        '\n' => {
+          line += 1;
+          field.push(c);
+        }
+        _ => field.push(c),
+      }
+      continue;
+    }
+    match c {
+      '"' if !field_started => {
+        in_quotes = true;
+        field_started = true;
+      }
+      '"' => return Err((line, "unexpected quote inside unquoted field".to_string())),
+      ',' => {
+        record.push(std::mem::take(&mut field));
+        field_started = false;
+      }
+      '\r' if chars.peek() == Some(&'\n') => {}
+      '\n' => {
+        if field_started || !record.is_empty() {
+          record.push(std::mem::take(&mut field));
+          records.push(std::mem::take(&mut record));
+        }
+        field_started = false;
+        line += 1;
+      }
+      _ => {
+        field.push(c);
+        field_started = true;
+      }
+    }
+  }
+
+  if in_quotes {
+    return Err((line, "unterminated quoted field".to_string()));
+  }
+  if field_started || !record.is_empty() {
+    record.push(field);
+    records.push(record);
+  }
+  Ok(records)
+}
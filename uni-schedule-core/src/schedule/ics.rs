@@ -0,0 +1,123 @@
+use chrono::{DateTime, NaiveDateTime, Utc};
+
+/// A VEVENT reduced to the fields the manager imports.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct IcsEvent {
+  pub summary: String,
+  pub start: DateTime<Utc>,
+  pub end: DateTime<Utc>,
+}
+
+/// Fields collected while inside a VEVENT.
+#[derive(Default)]
+struct PartialEvent {
+  summary: Option<String>,
+  start: Option<DateTime<Utc>>,
+  end: Option<DateTime<Utc>>,
+}
+
+/// Parse every VEVENT in an iCalendar document.
+///
+/// Only UTC date-times (`YYYYMMDDTHHMMSSZ`) are accepted. Floating times,
+/// `TZID`-qualified times and all-day `DATE` values are rejected rather
+/// than guessed. On failure returns the zero-based index of the offending
+/// event and a human-readable reason.
+pub(crate) fn parse_events(ics: &str) -> Result<Vec<IcsEvent>, (usize, String)> {
+  let mut events = Vec::new();
+  let mut current: Option<PartialEvent> = None;
+
+  for line in unfold(ics) {
+    let Some((head, value)) = line.split_once(':') else {
+      continue;
+    };
+    let mut parts = head.split(';');
+    let name = parts.next().unwrap_or_default().to_ascii_uppercase();
+    let params: Vec<&str> = parts.collect();
+    let index = events.len();
+
+    match (name.as_str(), current.as_mut()) {
+      ("BEGIN", None) if value.eq_ignore_ascii_case("VEVENT") => {
+        current = Some(PartialEvent::default());
+      }
+      ("END", Some(_)) if value.eq_ignore_ascii_case("VEVENT") => {
+        let Some(ev) = current.take() else {
+          continue;
+        };
+        let start = ev.start.ok_or((index, "missing DTSTART".to_string()))?;
+        let end = ev.end.ok_or((index, "missing DTEND".to_string()))?;
+        events.push(IcsEvent {
+          summary: ev.summary.unwrap_or_default(),
+          start,
+          end,
+        });
+      }
+      ("SUMMARY", Some(ev)) => ev.summary = Some(unescape(value)),
+      ("DTSTART", Some(ev)) => {
+        ev.start = Some(parse_utc(&params, value).map_err(|r| (index, format!("DTSTART: {r}")))?)
+      }
+      ("DTEND", Some(ev)) => {
+        ev.end = Some(parse_utc(&params, value).map_err(|r| (index, format!("DTEND: {r}")))?)
+      }
+      _ => {}
+    }
+  }
+
+  if current.is_some() {
+    return Err((events.len(), "unterminated VEVENT".to_string()));
+  }
+  Ok(events)
+}
+
+/// Join folded content lines (RFC 5545 §3.1): a line starting with a space
+/// or tab continues the previous one.
+fn unfold(ics: &str) -> Vec<String> {
+  let mut lines: Vec<String> = Vec::new();
+  for raw in ics.lines() {
+    let raw = raw.trim_end_matches('\r');
+    match (raw.strip_prefix([' ', '\t']), lines.last_mut()) {
+      (Some(rest), Some(prev)) => prev.push_str(rest),
+      _ => lines.push(raw.to_string()),
+    }
+  }
+  lines
+}
+
+/// Parse a UTC `DATE-TIME` value, rejecting anything that needs a timezone
+/// database or would have to be assumed.
+fn parse_utc(params: &[&str], value: &str) -> Result<DateTime<Utc>, String> {
+  for p in params {
+    let upper = p.to_ascii_uppercase();
+    if upper.starts_with("TZID=") {
+      return Err(format!(
+        "timezone-qualified time {value:?} is not supported"
+      ));
+    }
+    if upper == "VALUE=DATE" {
+      return Err(format!("all-day date {value:?} is not supported"));
+    }
+  }
+  let Some(naive) = value.strip_suffix('Z') else {
+    return Err(format!("floating time {value:?} has no timezone"));
+  };
+  NaiveDateTime::parse_from_str(naive, "%Y%m%dT%H%M%S")
+    .map(|dt| dt.and_utc())
+    .map_err(|e| format!("invalid date-time {value:?}: {e}"))
+}
+
+/// Undo TEXT escaping (`\\`, `\;`, `\,`, `\n`).
+fn unescape(value: &str) -> String {
+  let mut out = String::with_capacity(value.len());
+  let mut chars = value.chars();
+  while let Some(c) = chars.next() {
+    if c != '\\' {
+      out.push(c);
+      continue;
+    }
+    match chars.next() {
+      Some('n' | 'N') => out.push('\n'),
+      Some(other) => out.push(other),
+      None => out.push('\\'),
+    }
+  }
+  out
+}
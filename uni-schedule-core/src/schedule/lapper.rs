@@ -1,7 +1,7 @@
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Duration, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeSet;
-use uuid::Uuid;
+use std::ops::Bound;
 
 use super::ScheduleId;
 
@@ -9,8 +9,15 @@ use super::ScheduleId;
 ///
 /// Intervals are half-open: an interval `[start, stop)` contains times t
 /// with `start <= t < stop`. Intervals implement `Ord` and `Eq` so they
-/// can be sorted and kept in snapshot vectors.
-#[derive(PartialEq, Eq, PartialOrd, Ord, Debug, Clone, Serialize, Deserialize)]
+/// can be sorted and kept in snapshot vectors, and `Hash` so they can be
+/// collected into hash sets.
+///
+/// Ordering is lexicographic by `(start, stop, val)`: earlier starts first,
+/// then earlier stops, then the smaller id. This is the iteration order of
+/// [`Lapper::iter`] and the order `partition_point` searches over sorted
+/// slices rely on; the derive depends on the field declaration order, so
+/// the fields must not be reordered.
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Clone, Serialize, Deserialize)]
 pub struct Interval {
   pub start: DateTime<Utc>,
   pub stop: DateTime<Utc>,
@@ -38,20 +45,77 @@ impl Interval {
   }
 }
 
+/// How a [`Lapper`] treats interval and query endpoints.
+///
+/// In `HalfOpen` mode (the default) `[start, stop)` excludes `stop`, so
+/// back-to-back intervals do not overlap and a query with `start >= stop`
+/// is empty. In `Closed` mode `[start, stop]` includes both ends: intervals
+/// that merely touch overlap, and `start == stop` is a valid point query;
+/// only `start > stop` is empty. The mode affects the overlap queries
+/// ([`Lapper::find`], [`Lapper::find_rev`], [`Lapper::point_query`],
+/// [`Lapper::has_overlap`], [`Lapper::count_overlaps`],
+/// [`Lapper::remove_in_range`] and [`Lapper::overlaps_batch`]); the
+/// coverage helpers measure lengths of time and are the same in both modes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum IntervalMode {
+  #[default]
+  HalfOpen,
+  Closed,
+}
+
+impl IntervalMode {
+  /// Whether the query `[start, stop]`/`[start, stop)` contains no instant.
+  fn is_empty(self, start: DateTime<Utc>, stop: DateTime<Utc>) -> bool {
+    match self {
+      IntervalMode::HalfOpen => start >= stop,
+      IntervalMode::Closed => start > stop,
+    }
+  }
+
+  /// Whether an interval starting at `t` lies entirely after a query ending
+  /// at `stop`.
+  fn starts_after(self, t: DateTime<Utc>, stop: DateTime<Utc>) -> bool {
+    match self {
+      IntervalMode::HalfOpen => t >= stop,
+      IntervalMode::Closed => t > stop,
+    }
+  }
+
+  /// Whether an interval ending at `t` lies entirely before a query
+  /// starting at `start`.
+  fn ends_before(self, t: DateTime<Utc>, start: DateTime<Utc>) -> bool {
+    match self {
+      IntervalMode::HalfOpen => t <= start,
+      IntervalMode::Closed => t < start,
+    }
+  }
+
+  /// Whether `iv` overlaps the query range under this mode.
+  fn overlaps(self, iv: &Interval, start: DateTime<Utc>, stop: DateTime<Utc>) -> bool {
+    !self.starts_after(iv.start, stop) && !self.ends_before(iv.stop, start)
+  }
+}
+
 /// An interval index that supports overlap queries and coverage checks.
 ///
 /// `Lapper` keeps an augmented binary search tree of `Interval` nodes for
 /// efficient overlap iteration and also maintains a sorted set of intervals
 /// for certain linear algorithms. The tree is the authoritative structure for
 /// lookups; the sorted set is updated incrementally on insert/remove operations.
+/// Overlap queries are half-open unless another [`IntervalMode`] is chosen
+/// with [`Lapper::with_mode`].
 #[derive(Debug, Clone)]
 pub struct Lapper {
   /// Sorted set of intervals (sorted by (start, stop, val)).
   /// Uses BTreeSet for O(log n) insertions and efficient iteration.
-  pub intervals: BTreeSet<Interval>,
+  /// Outside the crate, use [`Lapper::iter`] / [`Lapper::range`].
+  pub(crate) intervals: BTreeSet<Interval>,
 
   /// Root of the augmented BST used for fast overlap queries.
   root: Option<Box<Node>>,
+
+  /// Endpoint semantics of overlap queries; see [`Lapper::with_mode`].
+  mode: IntervalMode,
 }
 
 /// Internal node of the augmented binary search tree.
@@ -91,15 +155,15 @@ impl Node {
   /// values of the left and right children.
   fn update_max(&mut self) {
     let mut m = self.iv.stop;
-    if let Some(ref l) = self.left {
-      if l.max > m {
-        m = l.max;
-      }
+    if let Some(ref l) = self.left
+      && l.max > m
+    {
+      m = l.max;
     }
-    if let Some(ref r) = self.right {
-      if r.max > m {
-        m = r.max;
-      }
+    if let Some(ref r) = self.right
+      && r.max > m
+    {
+      m = r.max;
     }
     self.max = m;
   }
@@ -121,6 +185,52 @@ impl Node {
     self.height = 1 + hl.max(hr);
   }
 
+  /// Recursively check `max`, `height` and AVL balance for this subtree,
+  /// appending its intervals in order to `out`. Returns this node's height.
+  fn check<'a>(&'a self, out: &mut Vec<&'a Interval>) -> Result<i32, String> {
+    let hl = match &self.left {
+      Some(l) => l.check(out)?,
+      None => 0,
+    };
+    out.push(&self.iv);
+    let hr = match &self.right {
+      Some(r) => r.check(out)?,
+      None => 0,
+    };
+
+    let expected_max = [
+      Some(self.iv.stop),
+      self.left.as_ref().map(|l| l.max),
+      self.right.as_ref().map(|r| r.max),
+    ]
+    .into_iter()
+    .flatten()
+    .max()
+    .unwrap_or(self.iv.stop);
+    if self.max != expected_max {
+      return Err(format!(
+        "node {:?}: max is {} but subtree max is {}",
+        self.iv, self.max, expected_max
+      ));
+    }
+    if self.height != 1 + hl.max(hr) {
+      return Err(format!(
+        "node {:?}: height is {} but children give {}",
+        self.iv,
+        self.height,
+        1 + hl.max(hr)
+      ));
+    }
+    if (hl - hr).abs() > 1 {
+      return Err(format!(
+        "node {:?}: balance factor {} is outside [-1, 1]",
+        self.iv,
+        hl - hr
+      ));
+    }
+    Ok(self.height)
+  }
+
   /// Balance factor = height(left) - height(right).
   fn balance_factor(&self) -> i32 {
     Node::height(&self.left) - Node::height(&self.right)
@@ -234,6 +344,7 @@ impl Node {
   ///
   /// The `removed_flag` is true when a node equal to `elem` was found and
   /// removed. The returned subtree is rebalanced when necessary.
+  #[allow(clippy::boxed_local)]
   fn remove(self: Box<Self>, elem: &Interval) -> (Option<Box<Node>>, bool) {
     use std::cmp::Ordering::*;
     let mut node = *self;
@@ -300,8 +411,8 @@ impl Node {
       return (node.iv, right);
     }
     // Recurse left until we find the leftmost node. On unwinding,
-    // update metadata for nodes along the path so heights and max
-    // remain correct for future operations.
+    // rebalance nodes along the path: shrinking a left subtree can leave
+    // an ancestor right-heavy, and rebalancing also refreshes height/max.
     debug_assert!(
       node.left.is_some(),
       "left child must exist when recursing in take_min"
@@ -315,9 +426,7 @@ impl Node {
       .expect("left child must exist when recursing in take_min");
     let (min_iv, new_left) = Node::take_min(left_child);
     node.left = new_left;
-    node.update_height();
-    node.update_max();
-    (min_iv, Some(node))
+    (min_iv, Some(node.rebalance()))
   }
 
   // inorder_collect removed (unused)
@@ -334,14 +443,21 @@ pub struct OverlapIter<'a> {
   stack: Vec<&'a Node>,
   start: DateTime<Utc>,
   stop: DateTime<Utc>,
+  mode: IntervalMode,
 }
 
 impl<'a> OverlapIter<'a> {
-  /// Create a new overlap iterator for the half-open range `[start, stop)`.
+  /// Create a new overlap iterator for the range from `start` to `stop`,
+  /// with endpoints treated according to `mode`.
   ///
   /// If `root` is `Some`, the iterator is initialized to traverse the
   /// leftmost chain so iteration yields intervals in order.
-  fn new(root: Option<&'a Node>, start: DateTime<Utc>, stop: DateTime<Utc>) -> Self {
+  fn new(
+    root: Option<&'a Node>,
+    start: DateTime<Utc>,
+    stop: DateTime<Utc>,
+    mode: IntervalMode,
+  ) -> Self {
     // Algorithm: Use an explicit stack to perform an in-order traversal
     // over the BST while applying subtree pruning. We push the left
     // chain from the root so the next node to visit is at the top of
@@ -351,6 +467,7 @@ impl<'a> OverlapIter<'a> {
       stack: Vec::new(),
       start,
       stop,
+      mode,
     };
     if let Some(r) = root {
       it.push_left_chain(r);
@@ -391,7 +508,7 @@ impl<'a> Iterator for OverlapIter<'a> {
   ///
   /// - If `node.max < self.start`: Skip entire subtree (no interval in
   ///   this subtree can overlap since all intervals end before query starts)
-  /// - If `node.iv.start >= self.stop`: Skip this node and remaining nodes
+  /// - If `node.iv.start >= self.stop` (`>` in closed mode): Skip this node
   ///   (all subsequent nodes start at or after query ends)
   /// - Uses strict inequality (`<`) for `max` comparison to handle edge case
   ///   where intervals end exactly at query start time
@@ -407,12 +524,12 @@ impl<'a> Iterator for OverlapIter<'a> {
         continue;
       }
 
-      if node.iv.start >= self.stop {
+      if self.mode.starts_after(node.iv.start, self.stop) {
         // node and all to its right start at/after stop, skip
         continue;
       }
 
-      if node.iv.overlap(self.start, self.stop) {
+      if self.mode.overlaps(&node.iv, self.start, self.stop) {
         return Some(&node.iv);
       }
       // otherwise continue
@@ -421,6 +538,78 @@ impl<'a> Iterator for OverlapIter<'a> {
   }
 }
 
+/// Iterator over intervals that overlap a query range, in descending
+/// `(start, stop, val)` order.
+///
+/// Mirror image of [`OverlapIter`]: the tree is walked right-to-left and
+/// the same `max`-based pruning applies.
+pub struct RevOverlapIter<'a> {
+  stack: Vec<&'a Node>,
+  start: DateTime<Utc>,
+  stop: DateTime<Utc>,
+  mode: IntervalMode,
+}
+
+impl<'a> RevOverlapIter<'a> {
+  fn new(
+    root: Option<&'a Node>,
+    start: DateTime<Utc>,
+    stop: DateTime<Utc>,
+    mode: IntervalMode,
+  ) -> Self {
+    let mut it = RevOverlapIter {
+      stack: Vec::new(),
+      start,
+      stop,
+      mode,
+    };
+    if let Some(r) = root {
+      it.push_right_chain(r);
+    }
+    it
+  }
+
+  /// Push a node and its right descendants so the top of the stack is the
+  /// next node in reverse order.
+  ///
+  /// A node with `max < start` is skipped together with its whole subtree.
+  /// Descent to the right stops at a node starting after the query (at or
+  /// after `stop` in half-open mode), since everything to its right starts
+  /// even later.
+  fn push_right_chain(&mut self, mut node: &'a Node) {
+    loop {
+      if node.max < self.start {
+        break;
+      }
+      self.stack.push(node);
+      if self.mode.starts_after(node.iv.start, self.stop) {
+        break;
+      }
+      if let Some(ref r) = node.right {
+        node = r.as_ref();
+      } else {
+        break;
+      }
+    }
+  }
+}
+
+impl<'a> Iterator for RevOverlapIter<'a> {
+  type Item = &'a Interval;
+
+  fn next(&mut self) -> Option<Self::Item> {
+    while let Some(node) = self.stack.pop() {
+      if let Some(ref l) = node.left {
+        self.push_right_chain(l.as_ref());
+      }
+      if self.mode.overlaps(&node.iv, self.start, self.stop) {
+        return Some(&node.iv);
+      }
+    }
+    None
+  }
+}
+
 impl Lapper {
   /// Create a new `Lapper` from an initial list of intervals.
   ///
@@ -436,10 +625,18 @@ impl Lapper {
         .cloned()
         .map(Node::new)
         .map(Box::new);
-      return Lapper { intervals, root };
+      return Lapper {
+        intervals,
+        root,
+        mode: IntervalMode::default(),
+      };
     }
     let root = Self::build_balanced(&intervals);
-    Lapper { intervals, root }
+    Lapper {
+      intervals,
+      root,
+      mode: IntervalMode::default(),
+    }
   }
 
   /// Build a `Lapper` from an arbitrary (possibly unsorted) vector of intervals.
@@ -464,6 +661,7 @@ impl Lapper {
       return Lapper {
         intervals: interval_set,
         root,
+        mode: IntervalMode::default(),
       };
     }
     // Build directly from the BTreeSet to avoid an extra sort/collect here.
@@ -471,6 +669,7 @@ impl Lapper {
     Lapper {
       intervals: interval_set,
       root,
+      mode: IntervalMode::default(),
     }
   }
 
@@ -482,22 +681,55 @@ impl Lapper {
       return None;
     }
     let sorted: Vec<_> = intervals.iter().cloned().collect();
+    Self::build_from_slice(&sorted)
+  }
 
-    fn build_from_slice(slice: &[Interval]) -> Option<Box<Node>> {
-      if slice.is_empty() {
-        return None;
-      }
-      let mid = slice.len() / 2;
-      let mut node = Box::new(Node::new(slice[mid].clone()));
-      node.left = build_from_slice(&slice[..mid]);
-      node.right = build_from_slice(&slice[mid + 1..]);
-      // Recompute height/max based on children.
-      node.update_height();
-      node.update_max();
-      Some(node)
+  /// Internal: build a height-balanced tree from a sorted slice by picking
+  /// the middle element as the root, recursively.
+  fn build_from_slice(slice: &[Interval]) -> Option<Box<Node>> {
+    if slice.is_empty() {
+      return None;
     }
+    let mid = slice.len() / 2;
+    let mut node = Box::new(Node::new(slice[mid].clone()));
+    node.left = Self::build_from_slice(&slice[..mid]);
+    node.right = Self::build_from_slice(&slice[mid + 1..]);
+    // Recompute height/max based on children.
+    node.update_height();
+    node.update_max();
+    Some(node)
+  }
 
-    build_from_slice(&sorted)
+  /// Build a `Lapper` from intervals that are already sorted by
+  /// `(start, stop, val)` and contain no duplicates.
+  ///
+  /// Skips the sort performed by [`Lapper::from_vec`]: the tree is built
+  /// straight from the slice in O(n). The precondition is only checked by
+  /// a debug assertion; violating it in release builds yields an index
+  /// whose tree and set disagree (see [`Lapper::validate`]).
+  pub fn from_sorted_unique(intervals: Vec<Interval>) -> Self {
+    debug_assert!(
+      intervals.windows(2).all(|w| w[0] < w[1]),
+      "from_sorted_unique requires strictly increasing intervals"
+    );
+    let root = Self::build_from_slice(&intervals);
+    Lapper {
+      intervals: BTreeSet::from_iter(intervals),
+      root,
+      mode: IntervalMode::default(),
+    }
+  }
+
+  /// Use `mode` for overlap queries. Lappers are half-open unless built
+  /// with this; the stored intervals are unchanged.
+  pub fn with_mode(mut self, mode: IntervalMode) -> Self {
+    self.mode = mode;
+    self
+  }
+
+  /// The endpoint semantics used by overlap queries.
+  pub fn mode(&self) -> IntervalMode {
+    self.mode
   }
 
   // rebuild_snapshots removed (unused)
@@ -569,7 +801,147 @@ impl Lapper {
     }
     false
   }
-  /// Find intervals that overlap the query range `[start, stop)`.
+
+  /// Remove every interval overlapping `[start, stop)` (`[start, stop]` in
+  /// closed mode) and return them in sorted order.
+  ///
+  /// Counterpart to [`Lapper::insert_batch`]: instead of rebalancing after
+  /// each removal, the BST is rebuilt once from the surviving intervals.
+  ///
+  /// # Complexity
+  /// O(k log n + n) where k is the number of removed intervals.
+  pub fn remove_in_range(&mut self, start: DateTime<Utc>, stop: DateTime<Utc>) -> Vec<Interval> {
+    if self.mode.is_empty(start, stop) {
+      return Vec::new();
+    }
+    let mut removed: Vec<Interval> = self.find(start, stop).cloned().collect();
+    if removed.is_empty() {
+      return removed;
+    }
+    for iv in &removed {
+      self.intervals.remove(iv);
+    }
+    self.root = Self::build_balanced(&self.intervals);
+    removed.sort();
+    removed
+  }
+
+  /// Replace the value of the interval `(start, stop, old)` with `new`.
+  ///
+  /// Because `val` is part of the ordering key the interval is removed and
+  /// reinserted internally; this keeps the BTreeSet and BST consistent in a
+  /// single call. Returns `false` (leaving the index untouched) when no
+  /// such interval exists.
+  pub fn update_val(
+    &mut self,
+    start: DateTime<Utc>,
+    stop: DateTime<Utc>,
+    old: ScheduleId,
+    new: ScheduleId,
+  ) -> bool {
+    let old_iv = Interval {
+      start,
+      stop,
+      val: old,
+    };
+    if !self.remove(&old_iv) {
+      return false;
+    }
+    self.insert(Interval {
+      start,
+      stop,
+      val: new,
+    });
+    true
+  }
+  /// Check internal invariants, returning a description of the first
+  /// violation found.
+  ///
+  /// Verifies that every node's `max` and `height` are correct, that each
+  /// node is AVL-balanced, and that an in-order walk of the BST yields
+  /// exactly the intervals of the sorted set (which also proves the BST
+  /// ordering). Intended for debugging and regression tests; O(n).
+  pub fn validate(&self) -> Result<(), String> {
+    let mut in_order = Vec::with_capacity(self.intervals.len());
+    if let Some(root) = &self.root {
+      root.check(&mut in_order)?;
+    }
+    if in_order.len() != self.intervals.len() {
+      return Err(format!(
+        "tree holds {} intervals but the set holds {}",
+        in_order.len(),
+        self.intervals.len()
+      ));
+    }
+    for (node_iv, set_iv) in in_order.into_iter().zip(&self.intervals) {
+      if node_iv != set_iv {
+        return Err(format!(
+          "tree and set disagree: tree has {node_iv:?} where set has {set_iv:?}"
+        ));
+      }
+    }
+    Ok(())
+  }
+
+  /// Number of nodes in the BST. Always equal to the number of stored
+  /// intervals for a consistent index.
+  pub fn node_count(&self) -> usize {
+    let mut count = 0;
+    let mut stack: Vec<&Node> = self.root.as_deref().into_iter().collect();
+    while let Some(node) = stack.pop() {
+      count += 1;
+      stack.extend(node.left.as_deref());
+      stack.extend(node.right.as_deref());
+    }
+    count
+  }
+
+  /// Height of the BST (0 when empty, 1 for a single node).
+  pub fn height(&self) -> usize {
+    Node::height(&self.root) as usize
+  }
+
+  /// Rebuild a perfectly balanced tree from the current intervals.
+  ///
+  /// AVL balancing keeps the height within ~1.44 log n, but long runs of
+  /// inserts and removals can leave it taller than necessary. This is the
+  /// same construction used by `from_vec` and deserialization, exposed for
+  /// on-demand compaction. O(n).
+  pub fn rebuild(&mut self) {
+    self.root = Self::build_balanced(&self.intervals);
+  }
+
+  /// Iterate all intervals in `(start, stop, val)` order without
+  /// allocating.
+  pub fn iter(&self) -> impl Iterator<Item = &Interval> + '_ {
+    self.intervals.iter()
+  }
+
+  /// Iterate intervals whose `start` lies in `[start, stop)`, in order.
+  ///
+  /// Unlike [`Lapper::find`] this ignores where intervals end: an interval
+  /// that began before `start` is not yielded even if it is still running.
+  /// Useful for "events starting today" style queries.
+  pub fn range(
+    &self,
+    start: DateTime<Utc>,
+    stop: DateTime<Utc>,
+  ) -> impl Iterator<Item = &Interval> + '_ {
+    // Smallest possible key with this start, so every interval starting
+    // at `start` is included regardless of its stop/val.
+    let lower = Interval {
+      start,
+      stop: DateTime::<Utc>::MIN_UTC,
+      val: ScheduleId::nil(),
+    };
+    self
+      .intervals
+      .range((Bound::Included(lower), Bound::Unbounded))
+      .take_while(move |iv| iv.start < stop)
+  }
+
+  /// Find intervals that overlap the query range `[start, stop)`, or
+  /// `[start, stop]` in closed mode.
   ///
   /// Returns an `OverlapIter` that borrows the tree and yields
   /// `&Interval` references without allocating a `Vec`.
@@ -578,7 +950,65 @@ impl Lapper {
     // entire subtrees whose `max` end-time is strictly less than the
     // query `start`. This yields only intervals that might overlap
     // the query range and avoids allocating temporary vectors.
-    OverlapIter::new(self.root.as_deref(), start, stop)
+    OverlapIter::new(self.root.as_deref(), start, stop, self.mode)
+  }
+
+  /// Find intervals that fully contain the query range, i.e.
+  /// `iv.start <= start && iv.stop >= stop`, in ascending start order.
+  ///
+  /// Runs the pruned [`Lapper::find`] traversal and stops at the first
+  /// interval starting after `start`. Yields nothing for an empty query,
+  /// matching `has_overlap`.
+  pub fn find_containing(
+    &self,
+    start: DateTime<Utc>,
+    stop: DateTime<Utc>,
+  ) -> impl Iterator<Item = &Interval> + '_ {
+    let empty = self.mode.is_empty(start, stop);
+    self
+      .find(start, stop)
+      .take_while(move |iv| !empty && iv.start <= start)
+      .filter(move |iv| iv.stop >= stop)
+  }
+
+  /// Find intervals that lie entirely inside the query range, i.e.
+  /// `iv.start >= start && iv.stop <= stop`, in ascending start order.
+  /// Yields nothing for an empty query.
+  pub fn find_contained_in(
+    &self,
+    start: DateTime<Utc>,
+    stop: DateTime<Utc>,
+  ) -> impl Iterator<Item = &Interval> + '_ {
+    let empty = self.mode.is_empty(start, stop);
+    self
+      .find(start, stop)
+      .filter(move |iv| !empty && iv.start >= start && iv.stop <= stop)
+  }
+
+  /// Like [`Lapper::find`] but yields overlapping intervals in descending
+  /// start order, e.g. for "most recent first" views.
+  pub fn find_rev(&self, start: DateTime<Utc>, stop: DateTime<Utc>) -> RevOverlapIter<'_> {
+    RevOverlapIter::new(self.root.as_deref(), start, stop, self.mode)
+  }
+
+  /// Find intervals containing the instant `t`, i.e. `start <= t < stop`.
+  ///
+  /// A point equal to an interval's exclusive `stop` is not contained.
+  /// Timestamps have nanosecond resolution, so this is equivalent to
+  /// `find(t, t + 1ns)` without callers having to pick an epsilon; at the
+  /// last representable instant, where `t + 1ns` overflows, it queries
+  /// `[t, MAX_UTC]`, which no half-open interval can contain. In closed
+  /// mode this is `find(t, t)`, so `stop` itself is contained.
+  pub fn point_query(&self, t: DateTime<Utc>) -> OverlapIter<'_> {
+    match self.mode {
+      IntervalMode::HalfOpen => {
+        let next = t
+          .checked_add_signed(Duration::nanoseconds(1))
+          .unwrap_or(DateTime::<Utc>::MAX_UTC);
+        self.find(t, next)
+      }
+      IntervalMode::Closed => self.find(t, t),
+    }
   }
 
   // `lower_bound` removed: use `slice.partition_point(|iv| iv.start < start)` directly
@@ -592,12 +1022,296 @@ impl Lapper {
   /// schedule validation where *any* overlap is disallowed (in contrast to
   /// `is_covered` which checks full coverage). Implementation delegates to
   /// the BST-backed iterator and stops after finding the first overlap.
+  ///
+  /// Empty queries overlap nothing. In half-open mode that is any query
+  /// with `start >= stop`, so a zero-length range never overlaps. In closed
+  /// mode `[t, t]` is the single instant `t` and overlaps every interval
+  /// containing it, including one ending at `t`; only `start > stop` is
+  /// rejected.
   pub fn has_overlap(&self, start: DateTime<Utc>, stop: DateTime<Utc>) -> bool {
-    if start >= stop {
+    if self.mode.is_empty(start, stop) {
       return false;
     }
     self.find(start, stop).next().is_some()
   }
+
+  /// Count intervals that overlap the half-open range `[start, stop)`.
+  ///
+  /// Uses the same pruned traversal as [`Lapper::find`] without collecting
+  /// into a `Vec`. Returns 0 for an empty query, matching `has_overlap`.
+  pub fn count_overlaps(&self, start: DateTime<Utc>, stop: DateTime<Utc>) -> usize {
+    if self.mode.is_empty(start, stop) {
+      return 0;
+    }
+    self.find(start, stop).count()
+  }
+
+  /// For each `(start, stop)` in `ranges`, whether any interval overlaps
+  /// it; the same answers as calling [`Lapper::has_overlap`] per range.
+  ///
+  /// Instead of one tree descent per range, the ranges are visited in start
+  /// order (sorted internally unless they already are) while a single
+  /// cursor walks the [`Lapper::merge_overlapping`] spans, so a batch costs
+  /// O(n + m log m) for n intervals and m ranges. Results are in input
+  /// order.
+  pub fn overlaps_batch(&self, ranges: &[(DateTime<Utc>, DateTime<Utc>)]) -> Vec<bool> {
+    let spans = self.merge_overlapping();
+    let mut order: Vec<usize> = (0..ranges.len()).collect();
+    if !ranges.is_sorted_by_key(|r| r.0) {
+      order.sort_by_key(|&i| ranges[i].0);
+    }
+
+    let mut out = vec![false; ranges.len()];
+    let mut j = 0;
+    for i in order {
+      let (start, stop) = ranges[i];
+      // Spans are disjoint and sorted, so their stops increase too: those
+      // ending by `start` cannot overlap this or any later range.
+      while j < spans.len() && self.mode.ends_before(spans[j].1, start) {
+        j += 1;
+      }
+      out[i] = !self.mode.is_empty(start, stop)
+        && j < spans.len()
+        && !self.mode.starts_after(spans[j].0, stop);
+    }
+    out
+  }
+
+  /// Number of intervals containing each of `count` sample points
+  /// `start, start + step, start + 2 * step, ...`.
+  ///
+  /// Containment is half-open in either [`IntervalMode`]: a point equal to
+  /// an interval's `stop` is not counted. With a positive `step` this is a
+  /// single sweep over the sorted starts and stops (O(n log n + count));
+  /// other steps fall back to a scan of every interval per sample. Samples
+  /// past the representable time range count 0.
+  pub fn coverage_profile(&self, start: DateTime<Utc>, step: Duration, count: usize) -> Vec<usize> {
+    let points = std::iter::successors(Some(start), |p| p.checked_add_signed(step));
+    let mut profile: Vec<usize> = if step <= Duration::zero() {
+      points
+        .take(count)
+        .map(|p| {
+          self
+            .intervals
+            .iter()
+            .filter(|iv| iv.start <= p && p < iv.stop)
+            .count()
+        })
+        .collect()
+    } else {
+      let mut stops: Vec<DateTime<Utc>> = self.intervals.iter().map(|iv| iv.stop).collect();
+      stops.sort_unstable();
+      let mut starts = self.intervals.iter().map(|iv| iv.start).peekable();
+      let (mut started, mut stopped) = (0usize, 0usize);
+      points
+        .take(count)
+        .map(|p| {
+          while starts.next_if(|s| *s <= p).is_some() {
+            started += 1;
+          }
+          while stopped < stops.len() && stops[stopped] <= p {
+            stopped += 1;
+          }
+          started.saturating_sub(stopped)
+        })
+        .collect()
+    };
+    profile.resize(count, 0);
+    profile
+  }
+
+  /// Return the first interval (in `(start, stop, val)` order) whose
+  /// `start >= t`, or `None` if every interval starts before `t`.
+  ///
+  /// Descends the BST in O(log n): a node starting at or after `t` becomes
+  /// the current candidate and the search continues left for a smaller one.
+  pub fn nearest_after(&self, t: DateTime<Utc>) -> Option<&Interval> {
+    let mut best = None;
+    let mut cur = self.root.as_deref();
+    while let Some(node) = cur {
+      if node.iv.start >= t {
+        best = Some(&node.iv);
+        cur = node.left.as_deref();
+      } else {
+        cur = node.right.as_deref();
+      }
+    }
+    best
+  }
+
+  /// Return the last interval (in `(start, stop, val)` order) whose
+  /// `start < t`, or `None` if no interval starts before `t`.
+  ///
+  /// Mirror of [`Lapper::nearest_after`]: candidates are taken while
+  /// descending right.
+  pub fn nearest_before(&self, t: DateTime<Utc>) -> Option<&Interval> {
+    let mut best = None;
+    let mut cur = self.root.as_deref();
+    while let Some(node) = cur {
+      if node.iv.start < t {
+        best = Some(&node.iv);
+        cur = node.right.as_deref();
+      } else {
+        cur = node.left.as_deref();
+      }
+    }
+    best
+  }
+
+  /// Coalesce overlapping intervals into maximal half-open spans.
+  ///
+  /// Walks the sorted `intervals` set and merges any interval whose start
+  /// lies strictly before the current span's stop. Adjacent intervals
+  /// (where one's `stop` equals the next's `start`) are kept separate to
+  /// stay consistent with the half-open semantics used elsewhere.
+  ///
+  /// The result is sorted by start time; an empty lapper yields an empty
+  /// vector.
+  pub fn merge_overlapping(&self) -> Vec<(DateTime<Utc>, DateTime<Utc>)> {
+    let mut merged: Vec<(DateTime<Utc>, DateTime<Utc>)> = Vec::new();
+    for iv in &self.intervals {
+      match merged.last_mut() {
+        Some((_, stop)) if iv.start < *stop => {
+          if iv.stop > *stop {
+            *stop = iv.stop;
+          }
+        }
+        _ => merged.push((iv.start, iv.stop)),
+      }
+    }
+    merged
+  }
+
+  /// Time ranges covered by both `self` and `other`.
+  ///
+  /// Works on each side's [`Lapper::merge_overlapping`] union with a
+  /// linear two-pointer sweep; ids are dropped since only coverage
+  /// matters. Returns maximal, sorted, non-overlapping half-open ranges
+  /// (touching pieces are joined).
+  pub fn intersect(&self, other: &Lapper) -> Vec<(DateTime<Utc>, DateTime<Utc>)> {
+    let (a, b) = (self.merge_overlapping(), other.merge_overlapping());
+    let mut out = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() && j < b.len() {
+      let lo = a[i].0.max(b[j].0);
+      let hi = a[i].1.min(b[j].1);
+      if lo < hi {
+        Self::push_span(&mut out, lo, hi);
+      }
+      // Advance whichever span ends first; the other may still overlap
+      // the next one.
+      if a[i].1 < b[j].1 {
+        i += 1;
+      } else {
+        j += 1;
+      }
+    }
+    out
+  }
+
+  /// Time ranges covered by `self` but not by `other`, in the same shape
+  /// as [`Lapper::intersect`].
+  pub fn difference(&self, other: &Lapper) -> Vec<(DateTime<Utc>, DateTime<Utc>)> {
+    let b = other.merge_overlapping();
+    let mut out = Vec::new();
+    let mut j = 0;
+    for (start, stop) in self.merge_overlapping() {
+      // Spans of `other` ending before this one cannot affect later ones.
+      while j < b.len() && b[j].1 <= start {
+        j += 1;
+      }
+      let mut cursor = start;
+      for &(s, e) in b[j..].iter().take_while(|(s, _)| *s < stop) {
+        if s > cursor {
+          Self::push_span(&mut out, cursor, s);
+        }
+        cursor = cursor.max(e);
+      }
+      if cursor < stop {
+        Self::push_span(&mut out, cursor, stop);
+      }
+    }
+    out
+  }
+
+  /// Append `[start, stop)` to sorted `spans`, joining it to the last span
+  /// when they touch.
+  fn push_span(
+    spans: &mut Vec<(DateTime<Utc>, DateTime<Utc>)>,
+    start: DateTime<Utc>,
+    stop: DateTime<Utc>,
+  ) {
+    match spans.last_mut() {
+      Some((_, last)) if *last == start => *last = stop,
+      _ => spans.push((start, stop)),
+    }
+  }
+
+  /// Total length of the union of all intervals.
+  ///
+  /// Overlapping intervals are merged first via
+  /// [`Lapper::merge_overlapping`] so shared time is counted once. Returns
+  /// `Duration::zero()` for an empty lapper.
+  pub fn covered_duration(&self) -> Duration {
+    self
+      .merge_overlapping()
+      .into_iter()
+      .fold(Duration::zero(), |acc, (s, e)| acc + (e - s))
+  }
+
+  /// Total covered length clamped to the half-open window `[start, stop)`.
+  ///
+  /// Useful for per-day utilization metrics. Returns `Duration::zero()`
+  /// when `start >= stop`.
+  pub fn covered_duration_in(&self, start: DateTime<Utc>, stop: DateTime<Utc>) -> Duration {
+    if start >= stop {
+      return Duration::zero();
+    }
+    self
+      .merge_overlapping()
+      .into_iter()
+      .filter(|&(s, e)| s < stop && e > start)
+      .fold(Duration::zero(), |acc, (s, e)| {
+        acc + (e.min(stop) - s.max(start))
+      })
+  }
+
+  /// Return the half-open sub-ranges of `[start, stop)` not covered by any
+  /// interval in the index.
+  ///
+  /// Occupied intervals are merged and clamped to the query window before
+  /// the complement is computed. Gaps are returned in ascending order. A
+  /// fully free window yields the single `[start, stop)`; a fully covered
+  /// window (or `start >= stop`) yields an empty vector.
+  pub fn gaps(
+    &self,
+    start: DateTime<Utc>,
+    stop: DateTime<Utc>,
+  ) -> Vec<(DateTime<Utc>, DateTime<Utc>)> {
+    let mut out = Vec::new();
+    if start >= stop {
+      return out;
+    }
+    let mut cursor = start;
+    for (s, e) in self.merge_overlapping() {
+      if e <= start {
+        continue;
+      }
+      if s >= stop {
+        break;
+      }
+      if s > cursor {
+        out.push((cursor, s));
+      }
+      if e > cursor {
+        cursor = e;
+      }
+    }
+    if cursor < stop {
+      out.push((cursor, stop));
+    }
+    out
+  }
 }
 
 // Custom serialization to ensure BST consistency
@@ -613,8 +1327,16 @@ impl Serialize for Lapper {
     // Serialize the BTreeSet as a vector to maintain compatibility
     // and will be rebuilt during deserialization
     // let intervals_vec: Vec<_> = self.intervals.iter().cloned().collect();
-    let mut state = serializer.serialize_struct("Lapper", 1)?;
+    // The default half-open mode is omitted, keeping the format of lappers
+    // written before modes existed.
+    let half_open = self.mode == IntervalMode::HalfOpen;
+    let mut state = serializer.serialize_struct("Lapper", if half_open { 1 } else { 2 })?;
     state.serialize_field("intervals", &self.intervals)?;
+    if half_open {
+      state.skip_field("mode")?;
+    } else {
+      state.serialize_field("mode", &self.mode)?;
+    }
     state.end()
   }
 }
@@ -631,6 +1353,8 @@ impl<'de> Deserialize<'de> for Lapper {
     #[derive(Deserialize)]
     struct Helper {
       intervals: Vec<Interval>,
+      #[serde(default)]
+      mode: IntervalMode,
     }
 
     let helper = Helper::deserialize(deserializer)?;
@@ -639,6 +1363,7 @@ impl<'de> Deserialize<'de> for Lapper {
     Ok(Lapper {
       intervals: interval_set,
       root,
+      mode: helper.mode,
     })
   }
 }
@@ -1,7 +1,9 @@
-use chrono::{DateTime, Utc};
+use chrono::{
+  DateTime, Duration, FixedOffset, LocalResult, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc,
+};
 use serde::{Deserialize, Serialize};
 use std::{
-  collections::{BTreeMap, HashMap, HashSet},
+  collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque},
   path::PathBuf,
   sync::Arc,
 };
@@ -9,7 +11,11 @@ use thiserror::Error;
 use typed_builder::TypedBuilder;
 use uuid::Uuid;
 
-use super::{ScheduleId, lapper::Lapper};
+use super::{
+  ScheduleId, csv, ics,
+  lapper::{Interval, Lapper},
+  recurrence::Recurrence,
+};
 
 /// Errors returned by schedule operations.
 ///
@@ -23,6 +29,10 @@ pub enum ScheduleError {
   #[error("Start time is later than end time")]
   StartAfterEnd,
 
+  /// The schedule's start and end times are equal.
+  #[error("Start time equals end time")]
+  ZeroLengthRange,
+
   /// The schedule's level is not lower than its parent schedule's level.
   /// Parents must have a strictly lower numeric level than their children.
   #[error("Schedule level is too high compared to parent")]
@@ -37,21 +47,190 @@ pub enum ScheduleError {
   #[error("Parent not found")]
   ParentNotFound,
 
+  /// The requested parents cannot all contain one child: `parent` shares
+  /// no time with the parents before it (in start order).
+  #[error("Invalid parent set: parent {parent} shares no time with the other parents")]
+  InvalidParentSet { parent: ScheduleId },
+
   /// The schedule's time range would overlap with an existing
   /// schedule in a way that violates exclusivity or level constraints.
   #[error("Time range overlaps with existing schedule")]
   TimeRangeOverlaps,
 
+  /// The schedule does not fit inside the daily window allowed for its
+  /// level (see `ScheduleManager::set_allowed_window`).
+  #[error("Time range is outside the allowed daily window for its level")]
+  OutsideAllowedWindow,
+
+  /// The schedule is shorter than the minimum duration set for its level
+  /// (see `ScheduleManager::set_duration_bounds`).
+  #[error("Schedule is shorter than the minimum duration for its level")]
+  DurationTooShort,
+
+  /// The schedule is longer than the maximum duration set for its level.
+  #[error("Schedule is longer than the maximum duration for its level")]
+  DurationTooLong,
+
   /// The requested schedule ID was not found.
   #[error("Schedule not found")]
   ScheduleNotFound,
   /// ID generation failed after multiple attempts (extremely unlikely)
   #[error("Duplicate schedule id generation failure")]
   DuplicateId,
+
+  /// Linking the requested parents would make a schedule its own ancestor.
+  #[error("Parent relationship would create a cycle")]
+  CycleDetected,
+
+  /// Linking the requested parents would make a chain of schedules longer
+  /// than the manager's configured `max_depth`.
+  #[error("Schedule hierarchy would exceed the maximum depth")]
+  MaxDepthExceeded,
+
+  /// `undo` was called with an empty history.
+  #[error("Nothing to undo")]
+  NothingToUndo,
+
+  /// `redo` was called with nothing previously undone.
+  #[error("Nothing to redo")]
+  NothingToRedo,
+
+  /// The recurrence rule cannot be expanded (for example a zero interval).
+  #[error("Invalid recurrence rule")]
+  InvalidRecurrence,
+
+  /// Shifting a schedule would move it outside the representable time
+  /// range.
+  #[error("Shifted time range is out of range")]
+  TimeOutOfRange,
+
+  /// A day-bucketed query spans more than [`MAX_DAY_BUCKETS`] days.
+  #[error("Window spans more than {} days", MAX_DAY_BUCKETS)]
+  WindowTooLong,
+
+  /// A local wall-clock time maps to two instants in its timezone (for
+  /// example during the repeated hour when clocks fall back).
+  #[error("Local time is ambiguous in the given timezone")]
+  AmbiguousLocalTime,
+
+  /// A local wall-clock time does not exist in its timezone (for example
+  /// inside the skipped hour when clocks spring forward).
+  #[error("Local time does not exist in the given timezone")]
+  NonexistentLocalTime,
+
+  /// An iCalendar document could not be parsed. `event` is the zero-based
+  /// index of the offending VEVENT.
+  #[error("Invalid iCalendar event {event}: {reason}")]
+  InvalidIcs { event: usize, reason: String },
+
+  /// A CSV document could not be parsed. `line` is one-based.
+  #[error("Invalid CSV at line {line}: {reason}")]
+  InvalidCsv { line: usize, reason: String },
+
+  /// A snapshot was written in a format version this build cannot read.
+  #[error("Unsupported snapshot version {0}")]
+  UnsupportedSnapshotVersion(u32),
+
+  /// A parsed iCalendar event was rejected by schedule validation.
+  #[error("iCalendar event {event} ({summary:?}) rejected: {source}")]
+  IcsEventRejected {
+    event: usize,
+    summary: String,
+    #[source]
+    source: Box<ScheduleError>,
+  },
+}
+
+/// Error returned by [`ScheduleManager::try_create_schedule`].
+///
+/// For `TimeRangeOverlaps` the ids of the blocking schedules are listed in
+/// `conflicts`; for every other error the list is empty.
+#[derive(Debug, Error, PartialEq, Eq)]
+#[error("{error}")]
+pub struct CreateConflict {
+  pub error: ScheduleError,
+  pub conflicts: Vec<ScheduleId>,
 }
 
 pub type ScheduleLevel = u32;
 
+/// Lifecycle notification delivered to a `ScheduleManager` observer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ScheduleEvent {
+  /// A schedule was inserted.
+  Created(ScheduleId),
+  /// A schedule's data or relations changed.
+  Updated(ScheduleId),
+  /// A schedule was removed (fired once per id, including cascades).
+  Deleted(ScheduleId),
+}
+
+/// Callback registered with [`ScheduleManager::set_observer`].
+pub type ScheduleObserver = Arc<dyn Fn(&ScheduleEvent) + Send + Sync>;
+
+/// Predicate used by `QueryOptions::matcher` to filter schedules.
+pub type ScheduleMatcher = Arc<dyn Fn(&Schedule) -> bool + Send + Sync>;
+
+/// How `QueryOptions::name` is compared against a schedule's name.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum NameMatch {
+  /// Case-sensitive substring match (the historical behaviour).
+  #[default]
+  Contains,
+  /// Substring match ignoring case.
+  ContainsCaseInsensitive,
+  /// The whole name must equal the filter.
+  Exact,
+  /// The filter must appear delimited by non-alphanumeric characters or
+  /// the ends of the name, so "sql" does not match "postgresql".
+  WholeWord,
+}
+
+impl NameMatch {
+  /// Returns true if `name` satisfies `filter` under this mode.
+  pub fn matches(self, name: &str, filter: &str) -> bool {
+    match self {
+      NameMatch::Contains => name.contains(filter),
+      NameMatch::ContainsCaseInsensitive => fold_case(name).contains(&fold_case(filter)),
+      NameMatch::Exact => name == filter,
+      NameMatch::WholeWord => {
+        let is_boundary = |c: Option<char>| c.is_none_or(|c| !c.is_alphanumeric());
+        name.match_indices(filter).any(|(i, m)| {
+          is_boundary(name[..i].chars().next_back())
+            && is_boundary(name[i + m.len()..].chars().next())
+        })
+      }
+    }
+  }
+}
+
+/// Result ordering for `QueryOptions::sort`. Ties are broken by start time
+/// and then id so the order is total and paging is stable.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts-bindings", derive(ts_rs::TS), ts(export))]
+pub enum SortOrder {
+  /// Earliest start first.
+  StartAsc,
+  /// Latest start first.
+  StartDesc,
+  /// Highest `priority` first.
+  PriorityDesc,
+}
+
+/// Which qualifying gap `ScheduleManager::find_free_slot` picks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum FitStrategy {
+  /// Start of the earliest gap that fits.
+  #[default]
+  Earliest,
+  /// Start of the gap with the least room to spare, reducing
+  /// fragmentation; ties go to the earliest gap.
+  BestFit,
+  /// End of the latest gap that fits, so the slot ends as late as the
+  /// window allows.
+  LatestWithinWindow,
+}
+
 /// Options to query schedules. Designed to be extensible: a custom matcher
 /// can be provided via `matcher` for future fields/complex filters.
 ///
@@ -72,61 +251,258 @@ pub type ScheduleLevel = u32;
 ///     .name("task".to_string())
 ///     .build();
 /// ```
-#[derive(Serialize, Deserialize, Clone, TypedBuilder)]
+#[derive(Serialize, Deserialize, Clone, Default, TypedBuilder)]
 #[builder(field_defaults(default))]
 pub struct QueryOptions {
   #[builder(default, setter(into, strip_option))]
   pub name: Option<String>,
+  /// Comparison mode used for `name`. Defaults to `NameMatch::Contains`.
+  #[serde(default)]
+  #[builder(default)]
+  pub name_match: NameMatch,
+  /// Keep schedules that end after `start`. Open-ended schedules never end,
+  /// so they match any `start`.
   #[builder(default, setter(into, strip_option))]
   pub start: Option<DateTime<Utc>>,
+  /// Keep schedules that start before `stop`. With both bounds, schedules
+  /// overlapping `[start, stop)` match, so an open-ended schedule is
+  /// included by every window that ends after it starts.
   #[builder(default, setter(into, strip_option))]
   pub stop: Option<DateTime<Utc>>,
   #[builder(default, setter(into, strip_option))]
   pub level: Option<ScheduleLevel>,
+  /// Inclusive lower bound on the level. Combined with `level` and
+  /// `level_max` by intersection, so an exact `level` outside the range
+  /// matches nothing.
+  #[builder(default, setter(into, strip_option))]
+  pub level_min: Option<ScheduleLevel>,
+  /// Inclusive upper bound on the level; see `level_min`.
+  #[builder(default, setter(into, strip_option))]
+  pub level_max: Option<ScheduleLevel>,
   #[builder(default, setter(into, strip_option))]
   pub exclusive: Option<bool>,
+  /// Only include schedules carrying this tag (exact, case-sensitive).
+  #[builder(default, setter(into, strip_option))]
+  pub tag: Option<String>,
+  /// Only include schedules carrying at least one of these tags. Ignored
+  /// when empty.
+  #[serde(default)]
+  #[builder(default)]
+  pub tags_any: Vec<String>,
+  /// Only include schedules carrying every one of these tags. Ignored when
+  /// empty.
+  #[serde(default)]
+  #[builder(default)]
+  pub tags_all: Vec<String>,
+  /// Only include direct children of the given schedule.
+  #[builder(default, setter(into, strip_option))]
+  pub child_of: Option<ScheduleId>,
+  /// Only include direct parents of the given schedule.
+  #[builder(default, setter(into, strip_option))]
+  pub parent_of: Option<ScheduleId>,
+  /// Only include schedules without parents.
+  #[serde(default)]
+  #[builder(default)]
+  pub roots_only: bool,
+  /// Maximum number of results to return, applied after filtering.
+  ///
+  /// Note: without `sort`, results are collected from hash-based indices
+  /// and their order is not deterministic, so paging with `limit`/`offset`
+  /// is only stable when `sort` is set.
+  #[builder(default, setter(into, strip_option))]
+  pub limit: Option<usize>,
+  /// Number of filtered results to skip before applying `limit`.
+  #[builder(default, setter(into, strip_option))]
+  pub offset: Option<usize>,
+  /// Order of the results, applied before `offset`/`limit`.
+  #[builder(default, setter(into, strip_option))]
+  pub sort: Option<SortOrder>,
+  /// Name of a matcher registered with
+  /// [`ScheduleManager::register_matcher`], for callers that cannot pass a
+  /// closure (e.g. over IPC). ANDed with `matcher`; an unknown name matches
+  /// nothing.
+  #[builder(default, setter(into, strip_option))]
+  pub named_matcher: Option<String>,
   /// Optional custom matcher that receives a schedule and returns true when
   /// the schedule should be included. Use this to extend filtering without
   /// changing the struct.
+  ///
+  /// Further predicates can be ANDed onto it with the builder's
+  /// `and_matcher` (or [`QueryOptions::and_matcher`] on a built value).
   #[serde(skip_serializing, skip_deserializing)]
-  pub matcher: Option<Arc<dyn Fn(&Schedule) -> bool + Send + Sync>>,
+  #[builder(via_mutators, mutators(
+    /// Replace the matcher.
+    pub fn matcher(&mut self, matcher: Option<ScheduleMatcher>) {
+      self.matcher = matcher;
+    }
+    /// AND `f` onto the matcher set so far; see [`QueryOptions::and_matcher`].
+    pub fn and_matcher(&mut self, f: impl Fn(&Schedule) -> bool + Send + Sync + 'static) {
+      self.matcher = Some(compose_matcher(self.matcher.take(), f));
+    }
+  ))]
+  pub matcher: Option<ScheduleMatcher>,
 }
 
-impl Default for QueryOptions {
-  fn default() -> Self {
-    Self {
-      name: None,
-      start: None,
-      stop: None,
-      level: None,
-      exclusive: None,
-      matcher: None,
-    }
+/// `existing` AND `f`, or just `f` when there is no existing matcher.
+fn compose_matcher(
+  existing: Option<ScheduleMatcher>,
+  f: impl Fn(&Schedule) -> bool + Send + Sync + 'static,
+) -> ScheduleMatcher {
+  match existing {
+    Some(prev) => Arc::new(move |s: &Schedule| prev(s) && f(s)),
+    None => Arc::new(f),
+  }
+}
+
+impl QueryOptions {
+  /// AND an additional predicate onto `matcher`, keeping any existing one.
+  ///
+  /// Predicates are evaluated in the order they were added and stop at the
+  /// first rejection.
+  pub fn and_matcher(mut self, f: impl Fn(&Schedule) -> bool + Send + Sync + 'static) -> Self {
+    self.matcher = Some(compose_matcher(self.matcher.take(), f));
+    self
   }
 }
 
+/// End time of an open-ended ("until further notice") schedule.
+///
+/// An open-ended schedule is stored and indexed like any other, with this
+/// sentinel as its end, so it overlaps every range after its start and the
+/// exclusivity rules treat it as extending forever. Use
+/// [`Schedule::open_ended`] to create one and [`Schedule::is_open_ended`] to
+/// render it as ongoing instead of showing the sentinel date.
+pub const OPEN_END: DateTime<Utc> = DateTime::<Utc>::MAX_UTC;
+
+/// Most days [`ScheduleManager::schedules_by_day`] returns buckets for
+/// (about ten years), so a window ending at [`OPEN_END`] cannot allocate
+/// millions of empty days.
+pub const MAX_DAY_BUCKETS: i64 = 3660;
+
+/// One UTC date and the schedules overlapping it.
+type DaySchedules = (NaiveDate, Vec<(ScheduleId, Schedule)>);
+
 /// A single schedule entry.
 ///
 /// `Schedule` represents a time-bounded item with a hierarchical level and
 /// an exclusivity flag. Instances are stored in `ScheduleManager` and
 /// referenced by `ScheduleId` (a `Uuid`). The struct is serializable so it
 /// can be persisted or sent over IPC.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+///
+/// Besides [`Schedule::new`], a schedule can be built with named fields via
+/// `Schedule::builder()`, where `start`, `end`, `level` and `name` are
+/// required and the rest default (`exclusive` to false). Neither checks the
+/// time range; that happens when the schedule is handed to the manager
+/// (e.g. `create_schedule` returns `StartAfterEnd` or `ZeroLengthRange`).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TypedBuilder)]
 pub struct Schedule {
   // id: ScheduleId,
   /// Inclusive start time of the schedule (half-open semantics are used by
   /// indexing helpers: ranges are treated as `[start, end)`).
   pub start: DateTime<Utc>,
-  /// Exclusive end time of the schedule interval.
+  /// Exclusive end time of the schedule interval, or [`OPEN_END`] when the
+  /// schedule has no defined end.
   pub end: DateTime<Utc>,
   /// Numeric hierarchy level of the schedule. Lower numbers indicate
   /// higher-level (parent) schedules.
   pub level: ScheduleLevel,
   /// When true indicates this schedule must not be overlapped by other
   /// schedules at the same or lower levels (enforced by the manager).
+  #[builder(default)]
   pub exclusive: bool,
   /// Human-readable name for the schedule.
+  #[builder(setter(into))]
   pub name: String,
+  /// Optional free-form notes.
+  #[serde(default)]
+  #[builder(default, setter(into, strip_option))]
+  pub description: Option<String>,
+  /// Labels attached to the schedule, matched exactly by `QueryOptions::tag`.
+  #[serde(default)]
+  #[builder(default)]
+  pub tags: Vec<String>,
+  /// Presentation priority, higher first under `SortOrder::PriorityDesc`.
+  /// Independent of `level` and ignored by overlap/exclusivity checks.
+  #[serde(default)]
+  #[builder(default)]
+  pub priority: u8,
+  /// Optional display color (e.g. `"#ff8800"`), opaque to the manager.
+  #[serde(default)]
+  #[builder(default, setter(into, strip_option))]
+  pub color: Option<String>,
+  /// Which levels an exclusive schedule keeps free; ignored when the
+  /// schedule is not exclusive.
+  #[serde(default)]
+  #[builder(default)]
+  pub exclusive_scope: ExclusiveScope,
+}
+
+/// Which levels an exclusive schedule blocks from overlapping it.
+///
+/// Recall that numerically higher levels sit lower in the hierarchy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts-bindings", derive(ts_rs::TS), ts(export))]
+pub enum ExclusiveScope {
+  /// Its own level and every lower (numerically higher) level.
+  #[default]
+  SameAndLower,
+  /// Every level, e.g. a holiday that no booking may overlap.
+  AllLevels,
+  /// Only its own level.
+  SameLevelOnly,
+}
+
+impl ExclusiveScope {
+  /// Whether an exclusive schedule at level `own` with this scope blocks an
+  /// overlapping schedule at level `other`.
+  pub fn blocks(self, own: ScheduleLevel, other: ScheduleLevel) -> bool {
+    match self {
+      ExclusiveScope::SameAndLower => other >= own,
+      ExclusiveScope::AllLevels => true,
+      ExclusiveScope::SameLevelOnly => other == own,
+    }
+  }
+}
+
+/// A daily window of wall-clock times, such as business hours, that
+/// schedules at a level must fall within.
+///
+/// The times are read in the fixed UTC offset `offset`, so `09:00`–`17:00`
+/// at `+08:00` is `01:00`–`09:00` UTC every day. A fixed offset does not
+/// follow daylight saving changes; configure the offset in effect, or
+/// update it when the local offset changes.
+///
+/// When `end` is not after `start` the window runs overnight, ending at
+/// `end` on the following day (`22:00`–`06:00`); equal times give a 24-hour
+/// window starting at `start`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AllowedWindow {
+  pub start: NaiveTime,
+  pub end: NaiveTime,
+  pub offset: FixedOffset,
+}
+
+impl AllowedWindow {
+  /// Whether `[start, end)` lies within a single occurrence of the window.
+  pub fn contains(&self, start: DateTime<Utc>, end: DateTime<Utc>) -> bool {
+    let local_start = start.with_timezone(&self.offset).naive_local();
+    let local_end = end.with_timezone(&self.offset).naive_local();
+    let length = if self.end > self.start {
+      self.end - self.start
+    } else {
+      self.end - self.start + Duration::days(1)
+    };
+    // The occurrence holding `start` begins on its local day or, for
+    // overnight windows, on the day before.
+    let day = local_start.date();
+    [Some(day), day.pred_opt()]
+      .into_iter()
+      .flatten()
+      .any(|day| {
+        let open = day.and_time(self.start);
+        open <= local_start && local_end <= open + length
+      })
+  }
 }
 
 impl Schedule {
@@ -143,9 +519,99 @@ impl Schedule {
       level,
       exclusive,
       name,
+      description: None,
+      tags: Vec::new(),
+      priority: 0,
+      color: None,
+      exclusive_scope: ExclusiveScope::default(),
+    }
+  }
+
+  /// Create a schedule with no defined end; its end is [`OPEN_END`].
+  pub fn open_ended(
+    start: DateTime<Utc>,
+    level: ScheduleLevel,
+    exclusive: bool,
+    name: String,
+  ) -> Self {
+    Self::new(start, OPEN_END, level, exclusive, name)
+  }
+
+  /// Like [`Schedule::new`], additionally setting a description and tags.
+  pub fn new_with_meta(
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    level: ScheduleLevel,
+    exclusive: bool,
+    name: String,
+    description: Option<String>,
+    tags: Vec<String>,
+  ) -> Self {
+    Self {
+      description,
+      tags,
+      ..Self::new(start, end, level, exclusive, name)
     }
   }
 
+  /// Build a schedule from local wall-clock times in timezone `tz`.
+  ///
+  /// Times are converted to UTC for storage. Around DST transitions a wall
+  /// clock time may not map to exactly one instant: a time inside the
+  /// spring-forward gap (e.g. 02:30 when clocks jump from 02:00 to 03:00)
+  /// yields `NonexistentLocalTime`, and a time inside the repeated
+  /// fall-back hour yields `AmbiguousLocalTime`. Callers should resolve
+  /// such times explicitly rather than have one silently picked.
+  pub fn from_local<Tz: TimeZone>(
+    tz: &Tz,
+    start_local: NaiveDateTime,
+    end_local: NaiveDateTime,
+    level: ScheduleLevel,
+    exclusive: bool,
+    name: String,
+  ) -> Result<Self, ScheduleError> {
+    let to_utc = |local: NaiveDateTime| match tz.from_local_datetime(&local) {
+      LocalResult::Single(dt) => Ok(dt.with_timezone(&Utc)),
+      LocalResult::Ambiguous(_, _) => Err(ScheduleError::AmbiguousLocalTime),
+      LocalResult::None => Err(ScheduleError::NonexistentLocalTime),
+    };
+    Ok(Self::new(
+      to_utc(start_local)?,
+      to_utc(end_local)?,
+      level,
+      exclusive,
+      name,
+    ))
+  }
+
+  /// Set the presentation priority.
+  pub fn with_priority(mut self, priority: u8) -> Self {
+    self.priority = priority;
+    self
+  }
+
+  /// Set the display color.
+  pub fn with_color(mut self, color: Option<String>) -> Self {
+    self.color = color;
+    self
+  }
+
+  /// Set which levels the schedule blocks when exclusive.
+  pub fn with_exclusive_scope(mut self, scope: ExclusiveScope) -> Self {
+    self.exclusive_scope = scope;
+    self
+  }
+
+  /// Start time rendered in timezone `tz`.
+  pub fn local_start<Tz: TimeZone>(&self, tz: &Tz) -> DateTime<Tz> {
+    self.start.with_timezone(tz)
+  }
+
+  /// End time rendered in timezone `tz`.
+  pub fn local_end<Tz: TimeZone>(&self, tz: &Tz) -> DateTime<Tz> {
+    self.end.with_timezone(tz)
+  }
+
   #[allow(dead_code)]
   pub fn start(&self) -> DateTime<Utc> {
     self.start
@@ -166,6 +632,25 @@ impl Schedule {
   pub fn name(&self) -> &str {
     &self.name
   }
+  pub fn description(&self) -> Option<&str> {
+    self.description.as_deref()
+  }
+  pub fn tags(&self) -> &[String] {
+    &self.tags
+  }
+  pub fn priority(&self) -> u8 {
+    self.priority
+  }
+  /// Whether the schedule has no defined end (see [`OPEN_END`]).
+  pub fn is_open_ended(&self) -> bool {
+    self.end == OPEN_END
+  }
+  pub fn exclusive_scope(&self) -> ExclusiveScope {
+    self.exclusive_scope
+  }
+  pub fn color(&self) -> Option<&str> {
+    self.color.as_deref()
+  }
 }
 
 /// Manager that stores schedules and provides querying and validation.
@@ -184,6 +669,8 @@ impl Schedule {
 ///   records parent/child relations.
 /// - `delete_schedule` removes a schedule from indices and relation maps.
 /// - `query_schedule` filters schedules using flexible `QueryOptions`.
+/// - `undo` / `redo` step through the history of successful mutations,
+///   once enabled with `with_history_limit`.
 ///
 /// The manager is intended to be used from a single thread; if shared
 /// across threads, callers should wrap it in appropriate synchronization
@@ -196,6 +683,10 @@ pub struct ScheduleManager {
   exclusive_index: BTreeMap<ScheduleLevel, Lapper>,
   /// Interval indices for all schedules (per level).
   all_index: BTreeMap<ScheduleLevel, Lapper>,
+  /// Ids of all exclusive schedules, kept alongside `exclusive_index` so
+  /// the `exclusive` query filter needs no scan. Derived data, not part of
+  /// the serialized state.
+  exclusive_ids: HashSet<ScheduleId>,
   /// For each schedule, the set of its parents.
   parent_relations: HashMap<ScheduleId, HashSet<ScheduleId>>,
   /// For each schedule, the set of its children.
@@ -204,6 +695,40 @@ pub struct ScheduleManager {
   /// Index mapping level -> set of schedule ids at that level. Used to
   /// quickly narrow queries by level.
   level_index: HashMap<ScheduleLevel, HashSet<ScheduleId>>,
+  /// Inverted index mapping every suffix of every lowercased name token to
+  /// the ids of schedules whose name has that token, so a token containing
+  /// a query token is a prefix lookup. Used to narrow name queries before
+  /// the final substring filter; derived data that can be rebuilt from
+  /// `schedules` via `rebuild_name_index`.
+  name_index: BTreeMap<String, HashSet<ScheduleId>>,
+  /// Optional lifecycle callback. Not part of the serialized state.
+  observer: Option<ScheduleObserver>,
+  /// Optional cap on hierarchy depth (a root has depth 1). `None` means
+  /// unlimited. Configuration only, not part of the serialized state.
+  max_depth: Option<u32>,
+  /// Per-level overlap allowance; see `set_level_capacity`. Configuration
+  /// only, not part of the serialized state.
+  level_capacity: HashMap<ScheduleLevel, u32>,
+  /// Per-level daily windows; see `set_allowed_window`. Configuration only,
+  /// not part of the serialized state.
+  allowed_windows: HashMap<ScheduleLevel, AllowedWindow>,
+  /// Per-level `(min, max)` schedule length; see `set_duration_bounds`.
+  /// Configuration only, not part of the serialized state.
+  duration_bounds: HashMap<ScheduleLevel, (Option<Duration>, Option<Duration>)>,
+  /// Matchers resolvable by `QueryOptions::named_matcher`; see
+  /// `register_matcher`. Configuration only, not part of the serialized
+  /// state.
+  matchers: HashMap<String, ScheduleMatcher>,
+  /// Undo history, oldest first; each entry is one public mutation.
+  /// Not part of the serialized state.
+  undo_stack: VecDeque<Vec<HistoryChange>>,
+  /// Mutations undone since the last recorded one, most recent last.
+  redo_stack: Vec<Vec<HistoryChange>>,
+  /// Maximum number of undo entries kept; 0 disables recording.
+  history_limit: usize,
+  /// Set while a tracked mutation runs so nested calls are not recorded
+  /// separately.
+  tracking: bool,
   // Full-text search functionality disabled
   // // Tantivy full-text index for `name` field (in-memory directory).
   // #[serde(skip)]
@@ -217,6 +742,156 @@ pub struct ScheduleManager {
   // ft_pending_ops: usize,
 }
 
+// Custom serialization: only source data (schedules and relations) and the
+// level index are written. The interval indices are derivable from the
+// schedules and are rebuilt on deserialization instead of being stored.
+impl Serialize for ScheduleManager {
+  fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+  where
+    S: serde::Serializer,
+  {
+    use serde::ser::SerializeStruct;
+
+    let mut state = serializer.serialize_struct("ScheduleManager", 4)?;
+    state.serialize_field("schedules", &self.schedules)?;
+    state.serialize_field("parent_relations", &self.parent_relations)?;
+    state.serialize_field("child_relations", &self.child_relations)?;
+    state.serialize_field("level_index", &self.level_index)?;
+    state.end()
+  }
+}
+
+impl<'de> Deserialize<'de> for ScheduleManager {
+  fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+  where
+    D: serde::Deserializer<'de>,
+  {
+    // Deserialize into a helper and rebuild the indices from the schedules,
+    // mirroring `Lapper`'s approach. The stored level index is redundant and
+    // re-derived. Both relation maps are read as stored; callers loading
+    // untrusted data run `reconcile_relations` afterwards so a link recorded
+    // on only one side (e.g. after a partial write) is restored and links to
+    // missing schedules are dropped instead of failing the load.
+    #[derive(Deserialize)]
+    struct Helper {
+      schedules: HashMap<ScheduleId, Schedule>,
+      parent_relations: HashMap<ScheduleId, HashSet<ScheduleId>>,
+      #[serde(default)]
+      child_relations: HashMap<ScheduleId, HashSet<ScheduleId>>,
+    }
+
+    let helper = Helper::deserialize(deserializer)?;
+    let mut mgr = ScheduleManager::new();
+    for (id, schedule) in &helper.schedules {
+      mgr.index_schedule(*id, schedule);
+    }
+    mgr.schedules = helper.schedules;
+    mgr.parent_relations = helper.parent_relations;
+    mgr.child_relations = helper.child_relations;
+    Ok(mgr)
+  }
+}
+
+/// Current format version written by [`ScheduleManager::to_snapshot`].
+pub const SNAPSHOT_VERSION: u32 = 1;
+
+/// Versioned, serializable backup of a `ScheduleManager`.
+///
+/// Only source data is stored: the schedules and each schedule's parent
+/// set. Child relations and all indices are derived when the snapshot is
+/// loaded with [`ScheduleManager::from_snapshot`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Snapshot {
+  /// Format version, used to migrate older snapshots.
+  pub version: u32,
+  /// Stored schedules by id.
+  pub schedules: HashMap<ScheduleId, Schedule>,
+  /// For each schedule, the set of its parents.
+  pub parents: HashMap<ScheduleId, HashSet<ScheduleId>>,
+}
+
+/// Aggregate figures returned by [`ScheduleManager::statistics`].
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct ScheduleStats {
+  /// Total number of schedules.
+  pub total: usize,
+  /// Number of schedules per level.
+  pub per_level: BTreeMap<ScheduleLevel, usize>,
+  /// Number of exclusive schedules.
+  pub exclusive: usize,
+  /// Time covered by at least one schedule, per level. Overlaps within a
+  /// level are counted once.
+  pub covered_per_level: BTreeMap<ScheduleLevel, Duration>,
+  /// Earliest window `[start, stop)` in which the most schedules (across
+  /// all levels) run at once, or `None` when there are no schedules.
+  pub busiest_window: Option<(DateTime<Utc>, DateTime<Utc>)>,
+  /// Number of schedules running during `busiest_window`.
+  pub busiest_count: usize,
+}
+
+/// How [`ScheduleManager::merge_from`] treats an id present in both
+/// managers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MergeStrategy {
+  /// Keep the existing schedule and ignore the incoming one.
+  Skip,
+  /// Replace the existing schedule's data with the incoming one.
+  Overwrite,
+  /// Abort the merge with `DuplicateId` before changing anything.
+  Fail,
+}
+
+/// Outcome of [`ScheduleManager::merge_from`].
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct MergeReport {
+  /// Ids imported as new schedules, parents before children.
+  pub added: Vec<ScheduleId>,
+  /// Colliding ids left untouched under `MergeStrategy::Skip`.
+  pub skipped: Vec<ScheduleId>,
+  /// Colliding ids replaced under `MergeStrategy::Overwrite`.
+  pub overwritten: Vec<ScheduleId>,
+  /// `(child, parent)` links from the other manager whose parent exists in
+  /// neither manager; they were not imported.
+  pub dropped_parents: Vec<(ScheduleId, ScheduleId)>,
+}
+
+/// Differences returned by [`ScheduleManager::diff`], seen from the
+/// receiver towards the argument.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct ScheduleDiff {
+  /// Ids present only in the other manager.
+  pub added: HashSet<ScheduleId>,
+  /// Ids present only in this manager.
+  pub removed: HashSet<ScheduleId>,
+  /// Ids present in both whose data or parent set differs.
+  pub changed: HashSet<ScheduleId>,
+}
+
+impl ScheduleDiff {
+  /// Whether the two managers hold the same schedules and relations.
+  pub fn is_empty(&self) -> bool {
+    self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+  }
+}
+
+/// A schedule's data and parent set, as stored by the manager.
+type HistoryRecord = (Schedule, HashSet<ScheduleId>);
+
+/// The state of one schedule before and after a mutation; `None` means the
+/// schedule did not exist.
+#[derive(Debug, Clone)]
+struct HistoryChange {
+  id: ScheduleId,
+  before: Option<HistoryRecord>,
+  after: Option<HistoryRecord>,
+}
+
+impl Default for ScheduleManager {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
 impl ScheduleManager {
   /// Create a new manager using default (in-memory) storage path.
   /// Equivalent to `Self::new_from_storage(None)`.
@@ -225,6 +900,245 @@ impl ScheduleManager {
     Self::new_from_storage(None)
   }
 
+  /// Create an empty manager with room for `n` schedules, avoiding repeated
+  /// rehashing when the count is known up front (e.g. when loading).
+  ///
+  /// The schedule and relation maps are pre-sized; the level index is keyed
+  /// by level rather than by schedule, so it is left to grow on demand.
+  pub fn with_capacity(n: usize) -> Self {
+    let mut mgr = Self::new();
+    mgr.schedules.reserve(n);
+    mgr.parent_relations.reserve(n);
+    mgr.child_relations.reserve(n);
+    mgr
+  }
+
+  /// Build a manager from source data, deriving child relations and all
+  /// indices.
+  ///
+  /// Returns `ParentNotFound` if a parent set references an unknown id and
+  /// `ScheduleNotFound` if a parent set is keyed by an unknown id.
+  fn from_parts(
+    schedules: HashMap<ScheduleId, Schedule>,
+    parents: HashMap<ScheduleId, HashSet<ScheduleId>>,
+  ) -> Result<Self, ScheduleError> {
+    let mut mgr = Self::with_capacity(schedules.len());
+
+    for (id, parent_set) in &parents {
+      if !schedules.contains_key(id) {
+        return Err(ScheduleError::ScheduleNotFound);
+      }
+      for parent in parent_set {
+        if !schedules.contains_key(parent) {
+          return Err(ScheduleError::ParentNotFound);
+        }
+        mgr.child_relations.entry(*parent).or_default().insert(*id);
+      }
+    }
+
+    for (id, schedule) in &schedules {
+      mgr.index_schedule(*id, schedule);
+      mgr
+        .parent_relations
+        .insert(*id, parents.get(id).cloned().unwrap_or_default());
+    }
+    mgr.schedules = schedules;
+
+    Ok(mgr)
+  }
+
+  /// Capture the manager's schedules and relations in a [`Snapshot`].
+  pub fn to_snapshot(&self) -> Snapshot {
+    Snapshot {
+      version: SNAPSHOT_VERSION,
+      schedules: self.schedules.clone(),
+      parents: self.parent_relations.clone(),
+    }
+  }
+
+  /// Restore a manager from a [`Snapshot`], rebuilding every index.
+  ///
+  /// # Errors
+  /// - `UnsupportedSnapshotVersion` for an unknown format version.
+  /// - `ParentNotFound` if a relation references a missing parent.
+  /// - `ScheduleNotFound` if relations are recorded for a missing schedule.
+  pub fn from_snapshot(snapshot: Snapshot) -> Result<Self, ScheduleError> {
+    if snapshot.version != SNAPSHOT_VERSION {
+      return Err(ScheduleError::UnsupportedSnapshotVersion(snapshot.version));
+    }
+    Self::from_parts(snapshot.schedules, snapshot.parents)
+  }
+
+  /// Register a callback invoked after schedules are created, updated or
+  /// deleted, replacing any previous observer.
+  pub fn set_observer(&mut self, observer: ScheduleObserver) {
+    self.observer = Some(observer);
+  }
+
+  /// Remove the registered observer, if any.
+  pub fn clear_observer(&mut self) {
+    self.observer = None;
+  }
+
+  /// Limit how deep the hierarchy may grow, counting a root as depth 1.
+  ///
+  /// Creating or linking a schedule so that some chain of parents would be
+  /// longer than `max_depth` fails with `MaxDepthExceeded`. Existing data is
+  /// not checked retroactively.
+  pub fn with_max_depth(mut self, max_depth: u32) -> Self {
+    self.max_depth = Some(max_depth);
+    self
+  }
+
+  /// Let up to `capacity` schedules at `level` overlap one another, or
+  /// restore the default rules with `None`.
+  ///
+  /// By default same-level overlaps are unlimited between non-exclusive
+  /// schedules and forbidden when either is exclusive. With a capacity, a
+  /// new schedule at `level` is rejected with `TimeRangeOverlaps` only if it
+  /// overlaps `capacity` or more existing schedules at that level
+  /// (exclusive or not), so `capacity` 3 models a room holding three
+  /// bookings. The count is of overlapping schedules, not of peak
+  /// concurrency. Exclusive schedules at other levels still apply as usual,
+  /// and existing data is not re-checked.
+  pub fn set_level_capacity(&mut self, level: ScheduleLevel, capacity: Option<u32>) {
+    match capacity {
+      Some(capacity) => self.level_capacity.insert(level, capacity),
+      None => self.level_capacity.remove(&level),
+    };
+  }
+
+  /// Peak number of schedules at `level` running at the same instant
+  /// within `[start, stop)`, e.g. to check whether a room is ever
+  /// over-booked.
+  ///
+  /// Sweeps the endpoints of the overlapping intervals, clamped to the
+  /// window. Ranges are half-open, so back-to-back schedules do not count
+  /// as concurrent. Returns 0 for an empty or inverted window.
+  pub fn max_concurrency(
+    &self,
+    level: ScheduleLevel,
+    start: DateTime<Utc>,
+    stop: DateTime<Utc>,
+  ) -> usize {
+    let Some(lapper) = self.all_index.get(&level) else {
+      return 0;
+    };
+    if start >= stop {
+      return 0;
+    }
+    // Ends sort before starts at the same instant (-1 < 1).
+    let mut events: Vec<(DateTime<Utc>, i32)> = lapper
+      .find(start, stop)
+      .flat_map(|iv| [(iv.start.max(start), 1), (iv.stop.min(stop), -1)])
+      .collect();
+    events.sort_unstable();
+    let mut running = 0i32;
+    let mut peak = 0i32;
+    for (_, delta) in events {
+      running += delta;
+      peak = peak.max(running);
+    }
+    peak as usize
+  }
+
+  /// Register `matcher` under `name` so queries can select it through
+  /// `QueryOptions::named_matcher`, replacing any matcher already
+  /// registered under that name.
+  pub fn register_matcher(
+    &mut self,
+    name: impl Into<String>,
+    matcher: impl Fn(&Schedule) -> bool + Send + Sync + 'static,
+  ) {
+    self.matchers.insert(name.into(), Arc::new(matcher));
+  }
+
+  /// The capacity configured for `level`, if any.
+  pub fn level_capacity(&self, level: ScheduleLevel) -> Option<u32> {
+    self.level_capacity.get(&level).copied()
+  }
+
+  /// Only accept schedules at `level` that fit inside the daily window
+  /// `daily_start`..`daily_end`, read as wall-clock times at `offset`.
+  ///
+  /// Creating or editing a schedule at `level` that is not contained in a
+  /// single occurrence of the window fails with `OutsideAllowedWindow`; see
+  /// [`AllowedWindow`] for overnight windows and the timezone caveat.
+  /// Replaces any window already set for `level`. Existing data is not
+  /// re-checked.
+  pub fn set_allowed_window(
+    &mut self,
+    level: ScheduleLevel,
+    daily_start: NaiveTime,
+    daily_end: NaiveTime,
+    offset: FixedOffset,
+  ) {
+    let window = AllowedWindow {
+      start: daily_start,
+      end: daily_end,
+      offset,
+    };
+    self.allowed_windows.insert(level, window);
+  }
+
+  /// Remove the daily window for `level`, if any.
+  pub fn clear_allowed_window(&mut self, level: ScheduleLevel) {
+    self.allowed_windows.remove(&level);
+  }
+
+  /// The daily window configured for `level`, if any.
+  pub fn allowed_window(&self, level: ScheduleLevel) -> Option<AllowedWindow> {
+    self.allowed_windows.get(&level).copied()
+  }
+
+  /// Require schedules at `level` to last at least `min` and at most `max`,
+  /// both inclusive; `None` leaves that side unbounded.
+  ///
+  /// Creating or editing a schedule outside the bounds fails with
+  /// `DurationTooShort` or `DurationTooLong`. Open-ended schedules count as
+  /// infinitely long. Passing `None` for both removes the bounds. Existing
+  /// data is not re-checked.
+  pub fn set_duration_bounds(
+    &mut self,
+    level: ScheduleLevel,
+    min: Option<Duration>,
+    max: Option<Duration>,
+  ) {
+    if min.is_none() && max.is_none() {
+      self.duration_bounds.remove(&level);
+    } else {
+      self.duration_bounds.insert(level, (min, max));
+    }
+  }
+
+  /// The `(min, max)` duration bounds configured for `level`.
+  pub fn duration_bounds(&self, level: ScheduleLevel) -> (Option<Duration>, Option<Duration>) {
+    self
+      .duration_bounds
+      .get(&level)
+      .copied()
+      .unwrap_or_default()
+  }
+
+  /// Keep at most `limit` undo steps. History is off (0) by default, so
+  /// managers that are cloned for backups do not carry it along.
+  pub fn with_history_limit(mut self, limit: usize) -> Self {
+    self.history_limit = limit;
+    self
+  }
+
+  /// The configured depth limit, if any.
+  pub fn max_depth(&self) -> Option<u32> {
+    self.max_depth
+  }
+
+  /// Deliver `event` to the observer, if one is registered.
+  fn notify(&self, event: ScheduleEvent) {
+    if let Some(observer) = &self.observer {
+      observer(&event);
+    }
+  }
+
   /// Generate a unique schedule ID with proper error handling
   fn generate_unique_id(&self) -> Result<ScheduleId, ScheduleError> {
     const MAX_ID_ATTEMPTS: usize = 16;
@@ -237,18 +1151,47 @@ impl ScheduleManager {
     Err(ScheduleError::DuplicateId)
   }
 
-  /// Validate schedule constraints against parents and time ranges
+  /// Check that a schedule has a non-empty, correctly ordered time range.
+  fn validate_range(schedule: &Schedule) -> Result<(), ScheduleError> {
+    match schedule.start.cmp(&schedule.end) {
+      std::cmp::Ordering::Less => Ok(()),
+      std::cmp::Ordering::Equal => Err(ScheduleError::ZeroLengthRange),
+      std::cmp::Ordering::Greater => Err(ScheduleError::StartAfterEnd),
+    }
+  }
+
+  /// Validate schedule constraints against parents and time ranges.
+  ///
+  /// Intervals whose id is in `parents` or `ignore` are skipped during the
+  /// overlap checks. `ignore` lets callers validating an already stored
+  /// schedule exclude its own intervals (and those of its children).
   fn validate_schedule(
     &self,
     schedule: &Schedule,
     parents: &HashSet<ScheduleId>,
+    ignore: &HashSet<ScheduleId>,
   ) -> Result<(), ScheduleError> {
     // Validate schedule time range: require start < end (disallow zero-length)
-    if schedule.start >= schedule.end {
-      return Err(ScheduleError::StartAfterEnd);
+    Self::validate_range(schedule)?;
+
+    if let Some(window) = self.allowed_windows.get(&schedule.level)
+      && !window.contains(schedule.start, schedule.end)
+    {
+      return Err(ScheduleError::OutsideAllowedWindow);
+    }
+
+    if let Some((min, max)) = self.duration_bounds.get(&schedule.level) {
+      let length = schedule.end - schedule.start;
+      if min.is_some_and(|min| length < min) {
+        return Err(ScheduleError::DurationTooShort);
+      }
+      if max.is_some_and(|max| schedule.is_open_ended() || length > max) {
+        return Err(ScheduleError::DurationTooLong);
+      }
     }
 
     // Validate parent relationships
+    self.validate_parent_set(parents)?;
     for parent_id in parents {
       match self.schedules.get(parent_id) {
         Some(parent) => {
@@ -263,43 +1206,489 @@ impl ScheduleManager {
       }
     }
 
-    // Check for overlaps with exclusive schedules at parent or same level.
-    // Note: lower numeric values indicate higher-level (parent) schedules,
-    // so we iterate existing exclusive index keys with numeric value <=
-    // `schedule.level`. This prevents same-level exclusive peers from
-    // overlapping a non-exclusive schedule.
-    for (&level, lapper) in self.exclusive_index.range(..=schedule.level).rev() {
-      // Check for overlaps, but ignore intervals that correspond to
-      // the explicit `parents` set — a child is allowed to be contained
-      // within its parent even if the parent is exclusive.
-      for iv in lapper.find(schedule.start, schedule.end) {
-        if !parents.contains(&iv.val) {
-          return Err(ScheduleError::TimeRangeOverlaps);
-        }
-      }
+    if self.conflicts(schedule, parents, ignore).next().is_some() {
+      return Err(ScheduleError::TimeRangeOverlaps);
     }
 
-    // If this schedule is exclusive, check for overlaps with any schedules at same or lower levels
-    if schedule.exclusive {
-      for (_, lapper) in self.all_index.range(schedule.level..) {
-        for iv in lapper.find(schedule.start, schedule.end) {
-          if !parents.contains(&iv.val) {
-            return Err(ScheduleError::TimeRangeOverlaps);
-          }
-        }
-      }
-    }
+    Ok(())
+  }
+
+  /// Check that the parents in `parents` have some time in common, which a
+  /// child contained in all of them requires.
+  ///
+  /// Parents are intersected in `(start, id)` order and the first one that
+  /// empties the intersection is reported, so the error names a parent
+  /// instead of surfacing as `TimeRangeExceedsParent` for whichever parent
+  /// the child happens to be checked against. Unknown ids are skipped; they
+  /// are reported as `ParentNotFound` by `validate_schedule`.
+  fn validate_parent_set(&self, parents: &HashSet<ScheduleId>) -> Result<(), ScheduleError> {
+    if parents.len() < 2 {
+      return Ok(());
+    }
+    let mut found: Vec<(ScheduleId, &Schedule)> = parents
+      .iter()
+      .filter_map(|id| self.schedules.get(id).map(|s| (*id, s)))
+      .collect();
+    found.sort_by_key(|(id, s)| (s.start, *id));
 
+    let mut common: Option<(DateTime<Utc>, DateTime<Utc>)> = None;
+    for (id, parent) in found {
+      let (start, end) = match common {
+        Some((start, end)) => (start.max(parent.start), end.min(parent.end)),
+        None => (parent.start, parent.end),
+      };
+      if start >= end {
+        return Err(ScheduleError::InvalidParentSet { parent: id });
+      }
+      common = Some((start, end));
+    }
     Ok(())
   }
 
-  /// Execute the schedule creation transaction atomically
-  fn execute_create_transaction(
-    &mut self,
+  /// Iterate over ids of indexed schedules that `schedule` would conflict
+  /// with under the exclusivity rules. An id may be yielded more than once.
+  ///
+  /// A schedule never conflicts with its own lineage: `parents` and all of
+  /// their ancestors are skipped, as are the ids in `ignore` (the schedule
+  /// itself when it is already stored) together with their ancestors and
+  /// descendants. Everything else, including sibling subtrees, is checked.
+  fn conflicts<'a>(
+    &'a self,
+    schedule: &'a Schedule,
+    parents: &'a HashSet<ScheduleId>,
+    ignore: &'a HashSet<ScheduleId>,
+  ) -> impl Iterator<Item = ScheduleId> + 'a {
+    // With a capacity configured for this level, same-level overlaps are
+    // governed by the capacity below instead of the exclusivity rules.
+    let capacity = self.level_capacity.get(&schedule.level).copied();
+    let checked_level = move |level: &ScheduleLevel| capacity.is_none() || *level != schedule.level;
+
+    // Overlaps with existing exclusive schedules whose scope covers this
+    // schedule's level (by default: those at the same or a higher level,
+    // i.e. numerically <= `schedule.level`).
+    let exclusive_hits = self
+      .exclusive_index
+      .iter()
+      .filter(move |(level, _)| checked_level(level))
+      .flat_map(|(_, lapper)| lapper.find(schedule.start, schedule.end))
+      .filter(|iv| {
+        self
+          .schedules
+          .get(&iv.val)
+          .is_some_and(|e| e.exclusive_scope.blocks(e.level, schedule.level))
+      });
+
+    // If this schedule is exclusive, overlaps with any schedule at a level
+    // its scope covers also conflict.
+    let all_hits = self
+      .all_index
+      .iter()
+      .filter(|_| schedule.exclusive)
+      .filter(move |(level, _)| {
+        checked_level(level) && schedule.exclusive_scope.blocks(schedule.level, **level)
+      })
+      .flat_map(|(_, lapper)| lapper.find(schedule.start, schedule.end));
+
+    // A child is allowed to be contained within its ancestors even if they
+    // are exclusive, and an exclusive schedule may contain its descendants,
+    // so the whole ancestor/descendant chain is excluded.
+    let mut lineage: HashSet<ScheduleId> = parents.union(ignore).copied().collect();
+    for parent in parents {
+      lineage.extend(self.ancestors(*parent));
+    }
+    for id in ignore {
+      lineage.extend(self.ancestors(*id));
+      lineage.extend(self.descendants(*id));
+    }
+
+    // Same-level overlaps only block once `capacity` of them exist.
+    let mut at_capacity = Vec::new();
+    if let Some(capacity) = capacity
+      && let Some(lapper) = self.all_index.get(&schedule.level)
+    {
+      at_capacity = lapper
+        .find(schedule.start, schedule.end)
+        .map(|iv| iv.val)
+        .filter(|id| !lineage.contains(id))
+        .collect();
+      if at_capacity.len() < capacity as usize {
+        at_capacity.clear();
+      }
+    }
+
+    exclusive_hits
+      .chain(all_hits)
+      .map(|iv| iv.val)
+      .filter(move |id| !lineage.contains(id))
+      .chain(at_capacity)
+  }
+
+  /// Return every existing schedule that would block creating `schedule`
+  /// with the given `parents`.
+  ///
+  /// Runs the same exclusivity/overlap rules as `create_schedule` but
+  /// collects all offenders instead of stopping at the first. Ids are
+  /// deduplicated and sorted. Read-only; the manager is not modified.
+  pub fn conflicts_for(
+    &self,
+    schedule: &Schedule,
+    parents: &HashSet<ScheduleId>,
+  ) -> Vec<ScheduleId> {
+    let empty = HashSet::new();
+    let mut ids: Vec<ScheduleId> = self.conflicts(schedule, parents, &empty).collect();
+    ids.sort();
+    ids.dedup();
+    ids
+  }
+
+  /// Find a range of length `duration` inside `window` where a root
+  /// schedule at `level` (exclusive or not) could be created, placed
+  /// according to `fit`.
+  ///
+  /// Blocking intervals are the exclusive schedules whose scope covers
+  /// `level` (by default those at `level` or above, i.e. numerically lower)
+  /// and, when `exclusive` is true, every schedule at `level` or below, as
+  /// for the default `ExclusiveScope`. They are combined into one `Lapper`
+  /// whose gaps within `window` are searched for one that fits.
+  /// Returns `None` if no slot fits or `duration` is not positive. Level
+  /// capacities are not taken into account.
+  pub fn find_free_slot(
+    &self,
+    level: ScheduleLevel,
+    duration: Duration,
+    window: (DateTime<Utc>, DateTime<Utc>),
+    exclusive: bool,
+    fit: FitStrategy,
+  ) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+    self.free_slot_ignoring(level, duration, window, exclusive, fit, &HashSet::new())
+  }
+
+  /// `find_free_slot` that does not treat the schedules in `ignore` as
+  /// blocking, mirroring how validation skips a schedule's own parents.
+  fn free_slot_ignoring(
+    &self,
+    level: ScheduleLevel,
+    duration: Duration,
+    window: (DateTime<Utc>, DateTime<Utc>),
+    exclusive: bool,
+    fit: FitStrategy,
+    ignore: &HashSet<ScheduleId>,
+  ) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+    let (start, stop) = window;
+    if duration <= Duration::zero() || start >= stop {
+      return None;
+    }
+
+    let exclusive_hits = self
+      .exclusive_index
+      .values()
+      .flat_map(|lapper| lapper.find(start, stop))
+      .filter(|iv| {
+        self
+          .schedules
+          .get(&iv.val)
+          .is_some_and(|e| e.exclusive_scope.blocks(e.level, level))
+      });
+    let all_hits = self
+      .all_index
+      .range(level..)
+      .filter(|_| exclusive)
+      .flat_map(|(_, lapper)| lapper.find(start, stop));
+    let blocking = Lapper::from_vec(
+      exclusive_hits
+        .chain(all_hits)
+        .filter(|iv| !ignore.contains(&iv.val))
+        .cloned()
+        .collect(),
+    );
+
+    let mut fitting = blocking
+      .gaps(start, stop)
+      .into_iter()
+      .filter(|(s, e)| *e - *s >= duration);
+    match fit {
+      FitStrategy::Earliest => fitting.next().map(|(s, _)| (s, s + duration)),
+      // `min_by_key` keeps the first of equal keys, i.e. the earliest gap.
+      FitStrategy::BestFit => fitting
+        .min_by_key(|(s, e)| *e - *s)
+        .map(|(s, _)| (s, s + duration)),
+      FitStrategy::LatestWithinWindow => fitting.next_back().map(|(_, e)| (e - duration, e)),
+    }
+  }
+
+  /// Ensure every direct child of `schedule_id` lies within the proposed
+  /// parent window `[start, end)`.
+  ///
+  /// Used by update paths so that changing a parent's time range cannot
+  /// leave a child extending beyond it.
+  fn validate_contains_children(
+    &self,
     schedule_id: ScheduleId,
-    schedule: Schedule,
-    parents: HashSet<ScheduleId>,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+  ) -> Result<(), ScheduleError> {
+    if let Some(children) = self.child_relations.get(&schedule_id) {
+      for child_id in children {
+        if let Some(child) = self.schedules.get(child_id)
+          && (child.start < start || child.end > end)
+        {
+          return Err(ScheduleError::TimeRangeExceedsParent);
+        }
+      }
+    }
+    Ok(())
+  }
+
+  /// Ensure none of `parents` is `schedule_id` itself or one of its
+  /// descendants, which would introduce a cycle in the hierarchy.
+  fn validate_no_cycle(
+    &self,
+    schedule_id: ScheduleId,
+    parents: &HashSet<ScheduleId>,
+  ) -> Result<(), ScheduleError> {
+    for parent in parents {
+      if *parent == schedule_id || self.ancestors(*parent).contains(&schedule_id) {
+        return Err(ScheduleError::CycleDetected);
+      }
+    }
+    Ok(())
+  }
+
+  /// Run a public mutation and record its effect for `undo`.
+  ///
+  /// `affected` must list every existing schedule whose data or parent set
+  /// `op` may change; `created` extracts the ids of schedules it inserts.
+  /// Their states before and after are stored as one history entry.
+  /// Nested tracked calls (e.g. `create_schedules` calling
+  /// `create_schedule`) are folded into the outermost entry, and failed
+  /// operations, which leave the manager unchanged, record nothing.
+  fn tracked<T>(
+    &mut self,
+    affected: Vec<ScheduleId>,
+    op: impl FnOnce(&mut Self) -> Result<T, ScheduleError>,
+    created: impl FnOnce(&T) -> Vec<ScheduleId>,
+  ) -> Result<T, ScheduleError> {
+    if self.tracking || self.history_limit == 0 {
+      return op(self);
+    }
+    let before: HashMap<ScheduleId, Option<HistoryRecord>> = affected
+      .into_iter()
+      .map(|id| (id, self.history_record(id)))
+      .collect();
+
+    self.tracking = true;
+    let result = op(self);
+    self.tracking = false;
+    let value = result?;
+
+    let mut ids: Vec<ScheduleId> = before.keys().copied().collect();
+    ids.extend(
+      created(&value)
+        .into_iter()
+        .filter(|id| !before.contains_key(id)),
+    );
+    let changes: Vec<HistoryChange> = ids
+      .into_iter()
+      .filter_map(|id| {
+        let before = before.get(&id).cloned().flatten();
+        let after = self.history_record(id);
+        (before != after).then_some(HistoryChange { id, before, after })
+      })
+      .collect();
+    if !changes.is_empty() {
+      self.redo_stack.clear();
+      self.undo_stack.push_back(changes);
+      while self.undo_stack.len() > self.history_limit {
+        self.undo_stack.pop_front();
+      }
+    }
+    Ok(value)
+  }
+
+  /// Current data and parent set of `id`, if it exists.
+  fn history_record(&self, id: ScheduleId) -> Option<HistoryRecord> {
+    let schedule = self.schedules.get(&id)?.clone();
+    let parents = self.parent_relations.get(&id).cloned().unwrap_or_default();
+    Some((schedule, parents))
+  }
+
+  /// `id` followed by all of its descendants.
+  fn subtree_ids(&self, id: ScheduleId) -> Vec<ScheduleId> {
+    let mut ids = vec![id];
+    ids.extend(self.descendants(id));
+    ids
+  }
+
+  /// `id` followed by its direct children.
+  fn with_children(&self, id: ScheduleId) -> Vec<ScheduleId> {
+    let mut ids = vec![id];
+    ids.extend(self.child_relations.get(&id).into_iter().flatten().copied());
+    ids
+  }
+
+  /// Overwrite the listed schedules with the given states without
+  /// validation.
+  ///
+  /// Only used by `undo`/`redo` and to roll back failed batch operations,
+  /// which restore a complete set of changes to a valid state, so the
+  /// result is valid again. Records no history and notifies no observer;
+  /// callers send the matching events.
+  fn restore_records(&mut self, records: Vec<(ScheduleId, Option<HistoryRecord>)>) {
+    for (id, _) in &records {
+      if let Some(schedule) = self.schedules.remove(id) {
+        self.unindex_schedule(*id, &schedule);
+      }
+      for parent in self.parent_relations.remove(id).unwrap_or_default() {
+        if let Some(children) = self.child_relations.get_mut(&parent) {
+          children.remove(id);
+        }
+      }
+    }
+    for (id, record) in &records {
+      match record {
+        Some((schedule, parents)) => {
+          self.index_schedule(*id, schedule);
+          self.schedules.insert(*id, schedule.clone());
+          for parent in parents {
+            self.child_relations.entry(*parent).or_default().insert(*id);
+          }
+          self.parent_relations.insert(*id, parents.clone());
+        }
+        None => {
+          if self.child_relations.get(id).is_some_and(|c| c.is_empty()) {
+            self.child_relations.remove(id);
+          }
+        }
+      }
+    }
+  }
+
+  /// Remove the schedules a failed batch operation created so far, as if
+  /// they had never been added.
+  ///
+  /// Observers already saw a `Created` event for each of them, so each
+  /// gets a `Deleted` event, newest first.
+  fn discard_created(&mut self, created: Vec<ScheduleId>) {
+    self.restore_records(created.iter().map(|id| (*id, None)).collect());
+    for id in created.into_iter().rev() {
+      self.notify(ScheduleEvent::Deleted(id));
+    }
+  }
+
+  /// Revert the most recent recorded mutation.
+  ///
+  /// With history enabled, every public mutating method (create, import,
+  /// update, move, relink, delete, clear) records one undo step. Returns
+  /// `NothingToUndo` when the history is empty.
+  pub fn undo(&mut self) -> Result<(), ScheduleError> {
+    let changes = self
+      .undo_stack
+      .pop_back()
+      .ok_or(ScheduleError::NothingToUndo)?;
+    self.replay(&changes, |c| c.before.clone(), |c| c.after.is_some());
+    self.redo_stack.push(changes);
+    Ok(())
+  }
+
+  /// Reapply the most recently undone mutation. Recording a new mutation
+  /// discards everything that could be redone.
+  pub fn redo(&mut self) -> Result<(), ScheduleError> {
+    let changes = self.redo_stack.pop().ok_or(ScheduleError::NothingToRedo)?;
+    self.replay(&changes, |c| c.after.clone(), |c| c.before.is_some());
+    self.undo_stack.push_back(changes);
+    Ok(())
+  }
+
+  /// Returns true if `undo` would revert something.
+  pub fn can_undo(&self) -> bool {
+    !self.undo_stack.is_empty()
+  }
+
+  /// Returns true if `redo` would reapply something.
+  pub fn can_redo(&self) -> bool {
+    !self.redo_stack.is_empty()
+  }
+
+  /// Restore one side of `changes` and notify the observer. `existed`
+  /// tells whether a schedule exists before the replay.
+  fn replay(
+    &mut self,
+    changes: &[HistoryChange],
+    target: impl Fn(&HistoryChange) -> Option<HistoryRecord>,
+    existed: impl Fn(&HistoryChange) -> bool,
+  ) {
+    let records = changes.iter().map(|c| (c.id, target(c))).collect();
+    self.restore_records(records);
+    for change in changes {
+      let event = match (existed(change), self.schedules.contains_key(&change.id)) {
+        (false, true) => ScheduleEvent::Created(change.id),
+        (true, false) => ScheduleEvent::Deleted(change.id),
+        _ => ScheduleEvent::Updated(change.id),
+      };
+      self.notify(event);
+    }
+  }
+
+  /// Enforce `max_depth` for linking `schedule_id` (or a new schedule when
+  /// `None`) beneath `parents`.
+  ///
+  /// For an existing schedule the deepest chain through its descendants is
+  /// considered too, since they move down along with it.
+  fn validate_depth(
+    &self,
+    schedule_id: Option<ScheduleId>,
+    parents: &HashSet<ScheduleId>,
   ) -> Result<(), ScheduleError> {
+    let Some(max_depth) = self.max_depth else {
+      return Ok(());
+    };
+    let mut memo = HashMap::new();
+    let mut depth = 1
+      + parents
+        .iter()
+        .map(|p| Self::depth_in(&self.parent_relations, *p, &mut memo))
+        .max()
+        .unwrap_or(0);
+    if let Some(id) = schedule_id {
+      depth = depth.max(Self::depth_in(&self.parent_relations, id, &mut memo));
+      let below = Self::depth_in(&self.child_relations, id, &mut HashMap::new());
+      depth += below - 1;
+    }
+    if depth > max_depth {
+      return Err(ScheduleError::MaxDepthExceeded);
+    }
+    Ok(())
+  }
+
+  /// Length of the longest chain starting at `id` (counting `id`) when
+  /// following `relations`. The hierarchy is acyclic, so plain memoized
+  /// recursion terminates.
+  fn depth_in(
+    relations: &HashMap<ScheduleId, HashSet<ScheduleId>>,
+    id: ScheduleId,
+    memo: &mut HashMap<ScheduleId, u32>,
+  ) -> u32 {
+    if let Some(&d) = memo.get(&id) {
+      return d;
+    }
+    let d = 1
+      + relations
+        .get(&id)
+        .map(|next| {
+          next
+            .iter()
+            .map(|n| Self::depth_in(relations, *n, memo))
+            .max()
+            .unwrap_or(0)
+        })
+        .unwrap_or(0);
+    memo.insert(id, d);
+    d
+  }
+
+  /// Insert `schedule`'s interval into the exclusive/all indices and record
+  /// its id in the level index.
+  fn index_schedule(&mut self, schedule_id: ScheduleId, schedule: &Schedule) {
     // Insert into exclusive index if needed
     if schedule.exclusive {
       let lapper = self
@@ -312,6 +1701,7 @@ impl ScheduleManager {
         stop: schedule.end,
         val: schedule_id,
       });
+      self.exclusive_ids.insert(schedule_id);
     }
 
     // Insert into all index
@@ -326,6 +1716,245 @@ impl ScheduleManager {
       val: schedule_id,
     });
 
+    // Update level index
+    self
+      .level_index
+      .entry(schedule.level)
+      .or_default()
+      .insert(schedule_id);
+
+    // Update name token index
+    for token in name_keys(&schedule.name) {
+      self
+        .name_index
+        .entry(token)
+        .or_default()
+        .insert(schedule_id);
+    }
+  }
+
+  /// Remove `schedule`'s interval from the exclusive/all indices and drop
+  /// its id from the level index. Inverse of `index_schedule`.
+  fn unindex_schedule(&mut self, schedule_id: ScheduleId, schedule: &Schedule) {
+    if schedule.exclusive {
+      debug_assert!(
+        self.exclusive_index.contains_key(&schedule.level),
+        "internal invariant: missing exclusive index for schedule level"
+      );
+      // The debug_assert above guarantees the key exists in the map.
+      // Access it safely and panic with a clear message if the invariant
+      // is violated in release builds.
+      let lapper = self
+        .exclusive_index
+        .get_mut(&schedule.level)
+        .expect("internal invariant: missing exclusive index for schedule level");
+
+      lapper.remove(&super::lapper::Interval {
+        start: schedule.start,
+        stop: schedule.end,
+        val: schedule_id,
+      });
+      self.exclusive_ids.remove(&schedule_id);
+    }
+
+    debug_assert!(
+      self.all_index.contains_key(&schedule.level),
+      "internal invariant: missing all index for schedule level"
+    );
+    // The debug_assert above guarantees the key exists in the map.
+    // Access it safely and panic with a clear message if the invariant
+    // is violated in release builds.
+    let lapper = self
+      .all_index
+      .get_mut(&schedule.level)
+      .expect("internal invariant: missing all index for schedule level");
+
+    lapper.remove(&super::lapper::Interval {
+      start: schedule.start,
+      stop: schedule.end,
+      val: schedule_id,
+    });
+
+    // Remove from level index
+    if let Some(set) = self.level_index.get_mut(&schedule.level) {
+      set.remove(&schedule_id);
+      if set.is_empty() {
+        self.level_index.remove(&schedule.level);
+      }
+    }
+
+    // Remove from name token index
+    for token in name_keys(&schedule.name) {
+      if let Some(set) = self.name_index.get_mut(&token) {
+        set.remove(&schedule_id);
+        if set.is_empty() {
+          self.name_index.remove(&token);
+        }
+      }
+    }
+  }
+
+  /// Rebuild every derived index (the per-level interval indices, the level
+  /// and name indices and the exclusive id set) from the `schedules` map.
+  ///
+  /// A recovery path for indices that drifted from the schedules, e.g. after
+  /// a faulty migration; detect drift with [`ScheduleManager::check_indices`].
+  /// Each level's intervals are bulk-loaded with `Lapper::from_vec`.
+  /// Relations are left as they are (see `reconcile_relations`).
+  pub fn reindex(&mut self) {
+    let mut all: BTreeMap<ScheduleLevel, Vec<Interval>> = BTreeMap::new();
+    let mut exclusive: BTreeMap<ScheduleLevel, Vec<Interval>> = BTreeMap::new();
+    self.level_index.clear();
+    self.exclusive_ids.clear();
+    for (id, schedule) in &self.schedules {
+      let interval = Interval {
+        start: schedule.start,
+        stop: schedule.end,
+        val: *id,
+      };
+      if schedule.exclusive {
+        exclusive
+          .entry(schedule.level)
+          .or_default()
+          .push(interval.clone());
+        self.exclusive_ids.insert(*id);
+      }
+      all.entry(schedule.level).or_default().push(interval);
+      self
+        .level_index
+        .entry(schedule.level)
+        .or_default()
+        .insert(*id);
+    }
+    self.all_index = all
+      .into_iter()
+      .map(|(level, intervals)| (level, Lapper::from_vec(intervals)))
+      .collect();
+    self.exclusive_index = exclusive
+      .into_iter()
+      .map(|(level, intervals)| (level, Lapper::from_vec(intervals)))
+      .collect();
+    self.rebuild_name_index();
+  }
+
+  /// Check that the interval and level indices match the `schedules` map,
+  /// returning a description of the first inconsistency found.
+  ///
+  /// Every index `Lapper` is checked with [`Lapper::validate`] and compared
+  /// with the intervals derived from the schedules. O(n log n); intended
+  /// for diagnostics and tests. Repair drift with `reindex`.
+  pub fn check_indices(&self) -> Result<(), String> {
+    let mut all: BTreeMap<ScheduleLevel, BTreeSet<Interval>> = BTreeMap::new();
+    let mut exclusive: BTreeMap<ScheduleLevel, BTreeSet<Interval>> = BTreeMap::new();
+    for (id, schedule) in &self.schedules {
+      let interval = Interval {
+        start: schedule.start,
+        stop: schedule.end,
+        val: *id,
+      };
+      if schedule.exclusive {
+        exclusive
+          .entry(schedule.level)
+          .or_default()
+          .insert(interval.clone());
+      }
+      all.entry(schedule.level).or_default().insert(interval);
+    }
+
+    for (name, index, expected) in [
+      ("all", &self.all_index, &all),
+      ("exclusive", &self.exclusive_index, &exclusive),
+    ] {
+      for (level, lapper) in index {
+        lapper
+          .validate()
+          .map_err(|e| format!("{name} index at level {level}: {e}"))?;
+      }
+      // Indices may keep an empty `Lapper` for a level whose schedules
+      // were all removed.
+      let levels: BTreeSet<ScheduleLevel> = index
+        .iter()
+        .filter(|(_, lapper)| !lapper.intervals.is_empty())
+        .map(|(level, _)| *level)
+        .chain(expected.keys().copied())
+        .collect();
+      for level in levels {
+        let stored = index.get(&level).map(|lapper| &lapper.intervals);
+        if stored != expected.get(&level) {
+          return Err(format!(
+            "{name} index at level {level} does not match the schedules"
+          ));
+        }
+      }
+    }
+
+    for (level, ids) in &self.level_index {
+      let expected = all.get(level).map_or(0, BTreeSet::len);
+      let stale = ids
+        .iter()
+        .any(|id| self.schedules.get(id).is_none_or(|s| s.level != *level));
+      if stale || ids.len() != expected {
+        return Err(format!(
+          "level index at level {level} does not match the schedules"
+        ));
+      }
+    }
+    if self.level_index.len() != all.len() {
+      return Err("level index is missing a level".to_string());
+    }
+    Ok(())
+  }
+
+  /// Direct access to the interval index so tests can simulate drift.
+  #[cfg(test)]
+  pub(crate) fn all_index_mut(&mut self) -> &mut BTreeMap<ScheduleLevel, Lapper> {
+    &mut self.all_index
+  }
+
+  /// Rebuild the name token index from the `schedules` map.
+  fn rebuild_name_index(&mut self) {
+    self.name_index.clear();
+    for (id, schedule) in &self.schedules {
+      for token in name_keys(&schedule.name) {
+        self.name_index.entry(token).or_default().insert(*id);
+      }
+    }
+  }
+
+  /// Ids that may match a substring `name` filter according to the token
+  /// index, or `None` when the filter has no tokens to narrow by.
+  ///
+  /// Any name containing `filter` must, for every token of `filter`, have a
+  /// token that contains it, so the result is a superset of the real
+  /// matches and the caller still applies the exact filter afterwards.
+  /// Tokens containing a filter token are those with a suffix starting with
+  /// it, found by a range scan over the suffix keys.
+  fn name_candidates(&self, filter: &str) -> Option<HashSet<ScheduleId>> {
+    let mut result: Option<HashSet<ScheduleId>> = None;
+    for token in name_tokens(filter) {
+      let mut ids = HashSet::new();
+      let keys = self.name_index.range::<String, _>(&token..);
+      for (_, postings) in keys.take_while(|(key, _)| key.starts_with(&token)) {
+        ids.extend(postings.iter().copied());
+      }
+      result = Some(match result {
+        Some(prev) => prev.intersection(&ids).copied().collect(),
+        None => ids,
+      });
+    }
+    result
+  }
+
+  /// Execute the schedule creation transaction atomically
+  fn execute_create_transaction(
+    &mut self,
+    schedule_id: ScheduleId,
+    schedule: Schedule,
+    parents: HashSet<ScheduleId>,
+  ) -> Result<(), ScheduleError> {
+    // Insert into interval and level indices
+    self.index_schedule(schedule_id, &schedule);
+
     // Update parent-child relationships
     for parent in &parents {
       self
@@ -337,14 +1966,9 @@ impl ScheduleManager {
     self.parent_relations.insert(schedule_id, parents);
 
     // Insert into schedule storage (in-memory map)
-    self.schedules.insert(schedule_id, schedule.clone());
+    self.schedules.insert(schedule_id, schedule);
 
-    // Update level index
-    self
-      .level_index
-      .entry(schedule.level)
-      .or_default()
-      .insert(schedule_id);
+    self.notify(ScheduleEvent::Created(schedule_id));
 
     // Storage integration removed from uni-schedule-core (no persistent store here).
 
@@ -409,9 +2033,21 @@ impl ScheduleManager {
       schedules: HashMap::new(),
       exclusive_index: BTreeMap::new(),
       all_index: BTreeMap::new(),
+      exclusive_ids: HashSet::new(),
       parent_relations: HashMap::new(),
       child_relations: HashMap::new(),
       level_index: HashMap::new(),
+      name_index: BTreeMap::new(),
+      observer: None,
+      max_depth: None,
+      level_capacity: HashMap::new(),
+      allowed_windows: HashMap::new(),
+      duration_bounds: HashMap::new(),
+      matchers: HashMap::new(),
+      undo_stack: VecDeque::new(),
+      redo_stack: Vec::new(),
+      history_limit: 0,
+      tracking: false,
       // Full-text search fields commented out
       // fulltext_index: tantivy_index,
       // ft_id_field: id_field,
@@ -424,17 +2060,67 @@ impl ScheduleManager {
   /// Create a new manager and (previously) load persistent data from the given path.
   /// Pass `None` to use an in-memory DB.
   pub fn new_from_storage(path: Option<PathBuf>) -> Self {
-    let mgr = Self::new_base(path.clone());
+    let mut mgr = Self::new_base(path.clone());
     // Storage integration removed from uni-schedule-core: do not attempt to load persistent data.
     // mgr.load_from_storage(path);
     // mgr.init_fulltext_writer();  // Disabled - full-text search functionality removed
+    mgr.rebuild_name_index();
+    mgr.reconcile_relations();
     mgr
   }
 
-  // Full-text search functionality disabled
-  /*
-  /// Bulk rebuild tantivy name index after loading storage.
-  fn rebuild_name_index(&self) {
+  /// Make `parent_relations` and `child_relations` mirror each other.
+  ///
+  /// A link recorded on either side is kept and added to the other; links
+  /// that reference an unknown schedule or point a schedule at itself are
+  /// dropped. Every schedule ends up with a (possibly empty) parent set.
+  /// Returns the number of links repaired. Only the shape of the graph is
+  /// fixed: level, containment and cycle rules are not re-checked.
+  pub fn reconcile_relations(&mut self) -> usize {
+    let mut links: HashSet<(ScheduleId, ScheduleId)> = HashSet::new();
+    for (child, parents) in &self.parent_relations {
+      links.extend(parents.iter().map(|parent| (*parent, *child)));
+    }
+    for (parent, children) in &self.child_relations {
+      links.extend(children.iter().map(|child| (*parent, *child)));
+    }
+
+    let mut repaired = 0;
+    let mut parent_relations: HashMap<ScheduleId, HashSet<ScheduleId>> = self
+      .schedules
+      .keys()
+      .map(|id| (*id, HashSet::new()))
+      .collect();
+    let mut child_relations: HashMap<ScheduleId, HashSet<ScheduleId>> = HashMap::new();
+    for (parent, child) in links {
+      let known = parent != child
+        && self.schedules.contains_key(&parent)
+        && self.schedules.contains_key(&child);
+      let mirrored = self
+        .parent_relations
+        .get(&child)
+        .is_some_and(|p| p.contains(&parent))
+        && self
+          .child_relations
+          .get(&parent)
+          .is_some_and(|c| c.contains(&child));
+      if !known || !mirrored {
+        repaired += 1;
+      }
+      if known {
+        parent_relations.entry(child).or_default().insert(parent);
+        child_relations.entry(parent).or_default().insert(child);
+      }
+    }
+    self.parent_relations = parent_relations;
+    self.child_relations = child_relations;
+    repaired
+  }
+
+  // Full-text search functionality disabled
+  /*
+  /// Bulk rebuild tantivy name index after loading storage.
+  fn rebuild_name_index(&self) {
     if let (Some(idx), Some(id_field), Some(name_field)) =
       (&self.fulltext_index, self.ft_id_field, self.ft_name_field)
     {
@@ -511,6 +2197,7 @@ impl ScheduleManager {
   /// # Errors
   /// Returns:
   /// - `StartAfterEnd` if the schedule's start time is after its end time.
+  /// - `ZeroLengthRange` if the schedule's start time equals its end time.
   /// - `LevelExceedsParent` if the schedule's level is not lower than its parent.
   /// - `TimeRangeExceedsParent` if the schedule's time range is not within its parent's time range.
   /// - `ParentNotFound` if any parent ID does not exist.
@@ -519,9 +2206,22 @@ impl ScheduleManager {
     &mut self,
     schedule: Schedule,
     parents: HashSet<ScheduleId>,
+  ) -> Result<ScheduleId, ScheduleError> {
+    self.tracked(
+      Vec::new(),
+      |this| this.create_schedule_untracked(schedule, parents),
+      |id| vec![*id],
+    )
+  }
+
+  fn create_schedule_untracked(
+    &mut self,
+    schedule: Schedule,
+    parents: HashSet<ScheduleId>,
   ) -> Result<ScheduleId, ScheduleError> {
     // Validate the schedule and its constraints
-    self.validate_schedule(&schedule, &parents)?;
+    self.validate_schedule(&schedule, &parents, &HashSet::new())?;
+    self.validate_depth(None, &parents)?;
 
     // Generate a unique ID
     let schedule_id = self.generate_unique_id()?;
@@ -532,6 +2232,41 @@ impl ScheduleManager {
     Ok(schedule_id)
   }
 
+  /// Check whether `create_schedule(schedule, parents)` would succeed,
+  /// without inserting anything.
+  ///
+  /// Runs the same validation as creation (time range, parent existence,
+  /// level and containment rules, overlaps and the depth limit), so a UI
+  /// can give live feedback while the user edits.
+  pub fn validate_create(
+    &self,
+    schedule: &Schedule,
+    parents: &HashSet<ScheduleId>,
+  ) -> Result<(), ScheduleError> {
+    self.validate_schedule(schedule, parents, &HashSet::new())?;
+    self.validate_depth(None, parents)
+  }
+
+  /// Like [`ScheduleManager::create_schedule`], but on an overlap error
+  /// also reports which existing schedules caused it.
+  pub fn try_create_schedule(
+    &mut self,
+    schedule: Schedule,
+    parents: HashSet<ScheduleId>,
+  ) -> Result<ScheduleId, CreateConflict> {
+    let conflicts = self.conflicts_for(&schedule, &parents);
+    self
+      .create_schedule(schedule, parents)
+      .map_err(|error| CreateConflict {
+        conflicts: if error == ScheduleError::TimeRangeOverlaps {
+          conflicts
+        } else {
+          Vec::new()
+        },
+        error,
+      })
+  }
+
   /// Create a schedule using an explicit, caller-provided ID.
   ///
   /// This preserves IDs when loading from an external store. The provided
@@ -542,6 +2277,19 @@ impl ScheduleManager {
     schedule_id: ScheduleId,
     schedule: Schedule,
     parents: HashSet<ScheduleId>,
+  ) -> Result<ScheduleId, ScheduleError> {
+    self.tracked(
+      Vec::new(),
+      |this| this.create_schedule_with_id_untracked(schedule_id, schedule, parents),
+      |id| vec![*id],
+    )
+  }
+
+  fn create_schedule_with_id_untracked(
+    &mut self,
+    schedule_id: ScheduleId,
+    schedule: Schedule,
+    parents: HashSet<ScheduleId>,
   ) -> Result<ScheduleId, ScheduleError> {
     // ensure id is not already present
     if self.schedules.contains_key(&schedule_id) {
@@ -549,21 +2297,678 @@ impl ScheduleManager {
     }
 
     // Validate against parents (parents must exist)
-    self.validate_schedule(&schedule, &parents)?;
+    self.validate_schedule(&schedule, &parents, &HashSet::new())?;
+    self.validate_depth(None, &parents)?;
 
     // Execute creation using the provided id
     self.execute_create_transaction(schedule_id, schedule, parents)?;
     Ok(schedule_id)
   }
 
+  /// Create `schedule`, moving it later in time if its requested range
+  /// overlaps existing schedules.
+  ///
+  /// The requested time is tried first. On `TimeRangeOverlaps`, the
+  /// earliest free slot of the same duration starting no more than
+  /// `max_shift` later is searched (as by `find_free_slot`, not counting
+  /// `parents` as blockers) and the schedule is created there. Returns the
+  /// new id and the applied offset (zero when no shift was needed).
+  ///
+  /// # Errors
+  /// - `TimeRangeOverlaps` if no slot fits within `max_shift`, and always
+  ///   for an open-ended schedule whose requested start is blocked.
+  /// - Any other validation error from `create_schedule`, including
+  ///   `TimeRangeExceedsParent` when the shifted range leaves a parent.
+  pub fn create_or_shift(
+    &mut self,
+    schedule: Schedule,
+    parents: HashSet<ScheduleId>,
+    max_shift: Duration,
+  ) -> Result<(ScheduleId, Duration), ScheduleError> {
+    self.tracked(
+      Vec::new(),
+      |this| this.create_or_shift_untracked(schedule, parents, max_shift),
+      |(id, _)| vec![*id],
+    )
+  }
+
+  fn create_or_shift_untracked(
+    &mut self,
+    schedule: Schedule,
+    parents: HashSet<ScheduleId>,
+    max_shift: Duration,
+  ) -> Result<(ScheduleId, Duration), ScheduleError> {
+    match self.create_schedule(schedule.clone(), parents.clone()) {
+      Err(ScheduleError::TimeRangeOverlaps) => {}
+      res => return res.map(|id| (id, Duration::zero())),
+    }
+    // Shifting cannot get an open-ended schedule past a blocker.
+    if schedule.is_open_ended() {
+      return Err(ScheduleError::TimeRangeOverlaps);
+    }
+
+    // Like `conflicts`, the whole ancestor chain is exempt, not just the
+    // direct parents.
+    let mut lineage = parents.clone();
+    for parent in &parents {
+      lineage.extend(self.ancestors(*parent));
+    }
+    let duration = schedule.end - schedule.start;
+    // An unbounded `max_shift` searches up to the last representable instant.
+    let latest_end =
+      schedule
+        .end
+        .checked_add_signed(max_shift)
+        .unwrap_or(if max_shift < Duration::zero() {
+          DateTime::<Utc>::MIN_UTC
+        } else {
+          DateTime::<Utc>::MAX_UTC
+        });
+    let window = (schedule.start, latest_end);
+    let (start, end) = self
+      .free_slot_ignoring(
+        schedule.level,
+        duration,
+        window,
+        schedule.exclusive,
+        FitStrategy::Earliest,
+        &lineage,
+      )
+      .ok_or(ScheduleError::TimeRangeOverlaps)?;
+    let offset = start - schedule.start;
+    let id = self.create_schedule(
+      Schedule {
+        start,
+        end,
+        ..schedule
+      },
+      parents,
+    )?;
+    Ok((id, offset))
+  }
+
+  /// Create several schedules as one all-or-nothing operation.
+  ///
+  /// Items are created in order; an item with an explicit id is inserted
+  /// via `create_schedule_with_id`, otherwise a fresh id is generated.
+  /// Because earlier items already exist when later ones are validated, an
+  /// item may list an earlier item's explicit id among its parents. If any
+  /// item fails, every schedule created by this call is removed again.
+  ///
+  /// Returns the ids of the created schedules in input order.
+  pub fn create_schedules(
+    &mut self,
+    items: Vec<(Option<ScheduleId>, Schedule, HashSet<ScheduleId>)>,
+  ) -> Result<Vec<ScheduleId>, ScheduleError> {
+    self.tracked(
+      Vec::new(),
+      |this| this.create_schedules_untracked(items),
+      |ids| ids.clone(),
+    )
+  }
+
+  fn create_schedules_untracked(
+    &mut self,
+    items: Vec<(Option<ScheduleId>, Schedule, HashSet<ScheduleId>)>,
+  ) -> Result<Vec<ScheduleId>, ScheduleError> {
+    let mut created = Vec::with_capacity(items.len());
+    for (id, schedule, parents) in items {
+      let res = match id {
+        Some(id) => self.create_schedule_with_id(id, schedule, parents),
+        None => self.create_schedule(schedule, parents),
+      };
+      match res {
+        Ok(id) => created.push(id),
+        Err(e) => {
+          self.discard_created(created);
+          return Err(e);
+        }
+      }
+    }
+    Ok(created)
+  }
+
+  /// Expand `template` with `recurrence` and create every occurrence.
+  ///
+  /// Each occurrence is an ordinary schedule copying the template's level,
+  /// exclusivity and name, shifted to the occurrence's time, and linked to
+  /// `parents`. Occurrences are validated one by one (including against
+  /// earlier occurrences); if any fails, all occurrences created so far are
+  /// removed and the error is returned.
+  ///
+  /// Returns the ids of the created occurrences in chronological order.
+  ///
+  /// # Errors
+  /// Returns `InvalidRecurrence` if the rule cannot be expanded, or any
+  /// error of `create_schedule` for the first failing occurrence.
+  pub fn create_recurring(
+    &mut self,
+    template: Schedule,
+    recurrence: Recurrence,
+    parents: HashSet<ScheduleId>,
+  ) -> Result<Vec<ScheduleId>, ScheduleError> {
+    self.tracked(
+      Vec::new(),
+      |this| this.create_recurring_untracked(template, recurrence, parents),
+      |ids| ids.clone(),
+    )
+  }
+
+  fn create_recurring_untracked(
+    &mut self,
+    template: Schedule,
+    recurrence: Recurrence,
+    parents: HashSet<ScheduleId>,
+  ) -> Result<Vec<ScheduleId>, ScheduleError> {
+    Self::validate_range(&template)?;
+    let occurrences = recurrence
+      .occurrences(template.start, template.end)
+      .ok_or(ScheduleError::InvalidRecurrence)?;
+
+    let mut created = Vec::with_capacity(occurrences.len());
+    for (start, end) in occurrences {
+      let occurrence = Schedule {
+        start,
+        end,
+        ..template.clone()
+      };
+      match self.create_schedule(occurrence, parents.clone()) {
+        Ok(id) => created.push(id),
+        Err(e) => {
+          self.discard_created(created);
+          return Err(e);
+        }
+      }
+    }
+    Ok(created)
+  }
+
+  /// Copy `root` and all of its descendants, shifted by `offset`, under
+  /// fresh ids, e.g. to duplicate a day's plan into the next week.
+  ///
+  /// Parent links inside the subtree are reproduced between the copies;
+  /// parents outside it are not, so the copy of `root` is a new root (link
+  /// it elsewhere with `add_parents`). Open-ended schedules stay
+  /// open-ended. Copies are created parents first and validated like
+  /// `create_schedule`, including against the originals; if any is
+  /// rejected, the copies made so far are removed again.
+  ///
+  /// Returns the ids of all copies.
+  ///
+  /// # Errors
+  /// Returns `ScheduleNotFound` if `root` does not exist,
+  /// `TimeOutOfRange` if `offset` takes a copy past the representable time
+  /// range, or any error of `create_schedule` for the first rejected copy.
+  pub fn clone_subtree(
+    &mut self,
+    root: ScheduleId,
+    offset: Duration,
+  ) -> Result<HashSet<ScheduleId>, ScheduleError> {
+    self.tracked(
+      Vec::new(),
+      |this| this.clone_subtree_untracked(root, offset),
+      |ids| ids.iter().copied().collect(),
+    )
+  }
+
+  fn clone_subtree_untracked(
+    &mut self,
+    root: ScheduleId,
+    offset: Duration,
+  ) -> Result<HashSet<ScheduleId>, ScheduleError> {
+    if !self.schedules.contains_key(&root) {
+      return Err(ScheduleError::ScheduleNotFound);
+    }
+    let subtree: HashSet<ScheduleId> = self.subtree_ids(root).into_iter().collect();
+
+    // Kahn's algorithm within the subtree so every copy's parents are
+    // copied before it.
+    let mut pending: HashMap<ScheduleId, usize> = subtree
+      .iter()
+      .map(|id| {
+        let parents = self.parent_relations.get(id).into_iter().flatten();
+        (*id, parents.filter(|p| subtree.contains(p)).count())
+      })
+      .collect();
+    let mut queue = VecDeque::from([root]);
+    let mut copies: HashMap<ScheduleId, ScheduleId> = HashMap::new();
+    let mut created = Vec::with_capacity(subtree.len());
+    while let Some(id) = queue.pop_front() {
+      let copy = match Self::shifted(&self.schedules[&id], offset) {
+        Ok(copy) => copy,
+        Err(e) => {
+          self.discard_created(created);
+          return Err(e);
+        }
+      };
+      let parents: HashSet<ScheduleId> = self
+        .parent_relations
+        .get(&id)
+        .into_iter()
+        .flatten()
+        .filter_map(|p| copies.get(p).copied())
+        .collect();
+
+      match self.create_schedule(copy, parents) {
+        Ok(copy_id) => {
+          copies.insert(id, copy_id);
+          created.push(copy_id);
+        }
+        Err(e) => {
+          self.discard_created(created);
+          return Err(e);
+        }
+      }
+
+      let children = self.child_relations.get(&id).into_iter().flatten();
+      for child in children {
+        if let Some(n) = pending.get_mut(child) {
+          *n -= 1;
+          if *n == 0 {
+            queue.push_back(*child);
+          }
+        }
+      }
+    }
+    Ok(created.into_iter().collect())
+  }
+
+  /// Import VEVENTs from an iCalendar document as non-exclusive schedules
+  /// at `level`.
+  ///
+  /// `SUMMARY` becomes the name and `DTSTART`/`DTEND` the time range. Only
+  /// UTC times are accepted; floating or timezone-qualified times are
+  /// rejected instead of being assumed to be UTC. The document is parsed
+  /// in full before anything is created, and if any event fails validation
+  /// the events imported so far are removed again.
+  ///
+  /// # Errors
+  /// - `InvalidIcs` if the document cannot be parsed.
+  /// - `IcsEventRejected` wrapping the validation error (for example
+  ///   `TimeRangeOverlaps`) together with the failing event's index and
+  ///   summary.
+  pub fn import_ics(
+    &mut self,
+    ics: &str,
+    level: ScheduleLevel,
+  ) -> Result<Vec<ScheduleId>, ScheduleError> {
+    self.tracked(
+      Vec::new(),
+      |this| this.import_ics_untracked(ics, level),
+      |ids| ids.clone(),
+    )
+  }
+
+  fn import_ics_untracked(
+    &mut self,
+    ics: &str,
+    level: ScheduleLevel,
+  ) -> Result<Vec<ScheduleId>, ScheduleError> {
+    let events = ics::parse_events(ics)
+      .map_err(|(event, reason)| ScheduleError::InvalidIcs { event, reason })?;
+
+    let mut created = Vec::with_capacity(events.len());
+    for (index, event) in events.into_iter().enumerate() {
+      let summary = event.summary.clone();
+      let schedule = Schedule::new(event.start, event.end, level, false, event.summary);
+      match self.create_schedule(schedule, HashSet::new()) {
+        Ok(id) => created.push(id),
+        Err(e) => {
+          self.discard_created(created);
+          return Err(ScheduleError::IcsEventRejected {
+            event: index,
+            summary,
+            source: Box::new(e),
+          });
+        }
+      }
+    }
+    Ok(created)
+  }
+
+  /// Render the hierarchy as a Graphviz DOT digraph, for debugging.
+  ///
+  /// Each schedule becomes a node keyed by its id and labeled with its name
+  /// and level; each parent→child link becomes an edge. Nodes are ordered by
+  /// start time then id and edges by endpoints, so the output is stable.
+  /// Pipe it through `dot -Tpng` to get a picture.
+  pub fn to_dot(&self) -> String {
+    fn escape(s: &str) -> String {
+      s.replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+    }
+
+    let mut nodes: Vec<(&ScheduleId, &Schedule)> = self.schedules.iter().collect();
+    nodes.sort_by_key(|(id, s)| (s.start, **id));
+    let mut edges: Vec<(ScheduleId, ScheduleId)> = self
+      .child_relations
+      .iter()
+      .flat_map(|(parent, children)| children.iter().map(move |child| (*parent, *child)))
+      .collect();
+    edges.sort();
+
+    let mut out = String::from("digraph schedules {\n");
+    for (id, schedule) in nodes {
+      out.push_str(&format!(
+        "  \"{id}\" [label=\"{}\\nlevel {}\"];\n",
+        escape(&schedule.name),
+        schedule.level
+      ));
+    }
+    for (parent, child) in edges {
+      out.push_str(&format!("  \"{parent}\" -> \"{child}\";\n"));
+    }
+    out.push_str("}\n");
+    out
+  }
+
+  /// Export schedules matching `opts` as CSV.
+  ///
+  /// Columns are `id,start,end,level,exclusive,name,parents`; times are
+  /// RFC 3339 and `parents` is a `;`-joined list of ids. Rows are ordered
+  /// by start time then id, and fields are quoted per RFC 4180 so names
+  /// containing commas, quotes or newlines round-trip.
+  pub fn export_csv(&self, opts: QueryOptions) -> String {
+    let mut rows = self.query_schedule(opts);
+    rows.sort_by(|(a_id, a), (b_id, b)| (a.start, a_id).cmp(&(b.start, b_id)));
+
+    let mut out = String::from(CSV_HEADER);
+    out.push('\n');
+    for (id, schedule) in rows {
+      let mut parents: Vec<String> = self
+        .parent_relations
+        .get(&id)
+        .map(|p| p.iter().map(ScheduleId::to_string).collect())
+        .unwrap_or_default();
+      parents.sort();
+      let fields = [
+        id.to_string(),
+        schedule.start.to_rfc3339(),
+        schedule.end.to_rfc3339(),
+        schedule.level.to_string(),
+        schedule.exclusive.to_string(),
+        schedule.name,
+        parents.join(";"),
+      ];
+      let line: Vec<String> = fields.iter().map(|f| csv::escape_field(f)).collect();
+      out.push_str(&line.join(","));
+      out.push('\n');
+    }
+    out
+  }
+
+  /// Import schedules from CSV produced by [`ScheduleManager::export_csv`].
+  ///
+  /// Ids are preserved via `create_schedule_with_id`. Rows may reference
+  /// parents that appear later in the file: rows are created in passes,
+  /// each pass creating those whose parents already exist. If any row
+  /// fails, every schedule created by this call is removed again.
+  ///
+  /// Returns the created ids in creation order.
+  ///
+  /// # Errors
+  /// - `InvalidCsv` for a missing header or malformed row.
+  /// - Any error of `create_schedule_with_id`; rows whose parents never
+  ///   appear yield `ParentNotFound`.
+  pub fn import_csv(&mut self, input: &str) -> Result<Vec<ScheduleId>, ScheduleError> {
+    self.tracked(
+      Vec::new(),
+      |this| this.import_csv_untracked(input),
+      |ids| ids.clone(),
+    )
+  }
+
+  fn import_csv_untracked(&mut self, input: &str) -> Result<Vec<ScheduleId>, ScheduleError> {
+    let invalid = |line: usize, reason: String| ScheduleError::InvalidCsv { line, reason };
+    let records = csv::parse_records(input).map_err(|(line, reason)| invalid(line, reason))?;
+
+    let mut records = records.into_iter().enumerate();
+    match records.next() {
+      Some((_, header)) if header.join(",") == CSV_HEADER => {}
+      _ => return Err(invalid(1, format!("expected header {CSV_HEADER:?}"))),
+    }
+
+    let mut pending = Vec::new();
+    for (row, fields) in records {
+      let line = row + 1;
+      let [id, start, end, level, exclusive, name, parents]: [String; 7] = fields
+        .try_into()
+        .map_err(|f: Vec<String>| invalid(line, format!("expected 7 fields, found {}", f.len())))?;
+      let parse_time = |v: &str| {
+        DateTime::parse_from_rfc3339(v)
+          .map(|dt| dt.with_timezone(&Utc))
+          .map_err(|e| invalid(line, format!("invalid time {v:?}: {e}")))
+      };
+      let parse_id =
+        |v: &str| Uuid::parse_str(v).map_err(|e| invalid(line, format!("invalid id {v:?}: {e}")));
+      let schedule = Schedule::new(
+        parse_time(&start)?,
+        parse_time(&end)?,
+        level
+          .parse()
+          .map_err(|e| invalid(line, format!("invalid level {level:?}: {e}")))?,
+        exclusive
+          .parse()
+          .map_err(|e| invalid(line, format!("invalid exclusive flag {exclusive:?}: {e}")))?,
+        name,
+      );
+      let parents = parents
+        .split(';')
+        .filter(|p| !p.is_empty())
+        .map(parse_id)
+        .collect::<Result<HashSet<ScheduleId>, _>>()?;
+      pending.push((parse_id(&id)?, schedule, parents));
+    }
+
+    let mut created = Vec::with_capacity(pending.len());
+    let mut result = Ok(());
+    while !pending.is_empty() {
+      let (ready, waiting): (Vec<_>, Vec<_>) = pending
+        .into_iter()
+        .partition(|(_, _, parents)| parents.iter().all(|p| self.schedules.contains_key(p)));
+      // With no row ready, attempt the rest anyway to surface the error.
+      let (batch, rest) = if ready.is_empty() {
+        (waiting, Vec::new())
+      } else {
+        (ready, waiting)
+      };
+      pending = rest;
+      for (id, schedule, parents) in batch {
+        match self.create_schedule_with_id(id, schedule, parents) {
+          Ok(id) => created.push(id),
+          Err(e) => {
+            result = Err(e);
+            break;
+          }
+        }
+      }
+      if result.is_err() {
+        break;
+      }
+    }
+
+    if let Err(e) = result {
+      self.discard_created(created);
+      return Err(e);
+    }
+    Ok(created)
+  }
+
+  /// Import every schedule of `other`, preserving ids and parent links.
+  ///
+  /// Schedules are imported parents first and validated like
+  /// `create_schedule_with_id`, so they must fit this manager's hierarchy
+  /// and exclusivity rules. An id present in both managers is handled per
+  /// `on_conflict`; an overwritten schedule gets the incoming data and
+  /// keeps its existing parents, with the incoming parents linked in
+  /// addition. A parent link to an id found in neither manager is dropped
+  /// and listed in `MergeReport::dropped_parents` instead of failing the
+  /// merge.
+  ///
+  /// The merge is atomic and recorded as a single undo step: if any
+  /// schedule is rejected, the manager is restored and the error returned.
+  ///
+  /// # Errors
+  /// - `DuplicateId` for a collision under `MergeStrategy::Fail`.
+  /// - Any error of `create_schedule_with_id`, `update_schedule` or
+  ///   `add_parents` for the first rejected schedule.
+  pub fn merge_from(
+    &mut self,
+    other: &ScheduleManager,
+    on_conflict: MergeStrategy,
+  ) -> Result<MergeReport, ScheduleError> {
+    let colliding: Vec<ScheduleId> = other
+      .schedules
+      .keys()
+      .filter(|id| self.schedules.contains_key(id))
+      .copied()
+      .collect();
+    self.tracked(
+      colliding,
+      |this| this.merge_from_untracked(other, on_conflict),
+      |report| report.added.clone(),
+    )
+  }
+
+  fn merge_from_untracked(
+    &mut self,
+    other: &ScheduleManager,
+    on_conflict: MergeStrategy,
+  ) -> Result<MergeReport, ScheduleError> {
+    if on_conflict == MergeStrategy::Fail
+      && other
+        .schedules
+        .keys()
+        .any(|id| self.schedules.contains_key(id))
+    {
+      return Err(ScheduleError::DuplicateId);
+    }
+
+    // Kahn's algorithm over `other`'s relations so parents are imported
+    // before their children; roots are taken in (start, id) order.
+    let parents_in_other = |id: &ScheduleId| {
+      other
+        .parent_relations
+        .get(id)
+        .into_iter()
+        .flatten()
+        .filter(|p| other.schedules.contains_key(p))
+        .count()
+    };
+    let mut pending: HashMap<ScheduleId, usize> = other
+      .schedules
+      .keys()
+      .map(|id| (*id, parents_in_other(id)))
+      .collect();
+    let mut roots: Vec<ScheduleId> = pending
+      .iter()
+      .filter(|(_, n)| **n == 0)
+      .map(|(id, _)| *id)
+      .collect();
+    roots.sort_by_key(|id| (other.schedules[id].start, *id));
+    let mut queue: VecDeque<ScheduleId> = roots.into();
+
+    let mut report = MergeReport::default();
+    let mut before: Vec<(ScheduleId, Option<HistoryRecord>)> = Vec::new();
+    let mut result = Ok(());
+    while let Some(id) = queue.pop_front() {
+      let schedule = other.schedules[&id].clone();
+      let (parents, mut dangling): (Vec<ScheduleId>, Vec<ScheduleId>) = other
+        .parent_relations
+        .get(&id)
+        .into_iter()
+        .flatten()
+        .partition(|p| self.schedules.contains_key(p) || other.schedules.contains_key(p));
+      let parents: HashSet<ScheduleId> = parents.into_iter().collect();
+      dangling.sort();
+      report
+        .dropped_parents
+        .extend(dangling.into_iter().map(|p| (id, p)));
+
+      let step = if self.schedules.contains_key(&id) {
+        match on_conflict {
+          MergeStrategy::Overwrite => {
+            before.push((id, self.history_record(id)));
+            report.overwritten.push(id);
+            self
+              .update_schedule(id, schedule)
+              .and_then(|()| self.add_parents(id, parents))
+          }
+          _ => {
+            report.skipped.push(id);
+            Ok(())
+          }
+        }
+      } else {
+        self
+          .create_schedule_with_id(id, schedule, parents)
+          .map(|id| {
+            before.push((id, None));
+            report.added.push(id);
+          })
+      };
+      if let Err(e) = step {
+        result = Err(e);
+        break;
+      }
+
+      let mut children: Vec<ScheduleId> = other
+        .child_relations
+        .get(&id)
+        .into_iter()
+        .flatten()
+        .copied()
+        .filter(|child| {
+          pending.get_mut(child).is_some_and(|n| {
+            *n -= 1;
+            *n == 0
+          })
+        })
+        .collect();
+      children.sort_by_key(|id| (other.schedules[id].start, *id));
+      queue.extend(children);
+    }
+
+    if let Err(e) = result {
+      let events: Vec<ScheduleEvent> = before
+        .iter()
+        .map(|(id, record)| match record {
+          Some(_) => ScheduleEvent::Updated(*id),
+          None => ScheduleEvent::Deleted(*id),
+        })
+        .collect();
+      self.restore_records(before);
+      for event in events {
+        self.notify(event);
+      }
+      return Err(e);
+    }
+    Ok(report)
+  }
+
   /// Attach parent relationships to an existing schedule.
   ///
   /// Validates the constraints of the schedule against the provided parents
   /// and updates parent/child relation maps. Parents must already exist.
+  /// Returns `CycleDetected` if a parent is the schedule itself or one of
+  /// its descendants.
   pub fn add_parents(
     &mut self,
     schedule_id: ScheduleId,
     parents: HashSet<ScheduleId>,
+  ) -> Result<(), ScheduleError> {
+    self.tracked(
+      vec![schedule_id],
+      |this| this.add_parents_untracked(schedule_id, parents),
+      |_| Vec::new(),
+    )
+  }
+
+  fn add_parents_untracked(
+    &mut self,
+    schedule_id: ScheduleId,
+    parents: HashSet<ScheduleId>,
   ) -> Result<(), ScheduleError> {
     // Ensure schedule exists
     let schedule = self
@@ -572,8 +2977,15 @@ impl ScheduleManager {
       .ok_or(ScheduleError::ScheduleNotFound)?
       .clone();
 
-    // Validate constraints against the parents
-    self.validate_schedule(&schedule, &parents)?;
+    // Reject links that would make the schedule its own ancestor. The
+    // strict level rule already implies this, but keep the hierarchy
+    // invariant explicit in case levels are ever relaxed.
+    self.validate_no_cycle(schedule_id, &parents)?;
+
+    // Validate constraints against the parents, ignoring the schedule's
+    // own interval which is already indexed.
+    self.validate_schedule(&schedule, &parents, &HashSet::from([schedule_id]))?;
+    self.validate_depth(Some(schedule_id), &parents)?;
 
     // Update child relations and parent_relations map
     for parent in &parents {
@@ -590,11 +3002,404 @@ impl ScheduleManager {
       .and_modify(|p| p.extend(parents.iter().copied()))
       .or_insert(parents);
 
+    self.notify(ScheduleEvent::Updated(schedule_id));
     Ok(())
   }
+
+  /// Detach parent relationships from an existing schedule.
+  ///
+  /// Removes each of `parents` from the schedule's parent set and the
+  /// schedule from each parent's child set. Parents that were not linked
+  /// are ignored. Unlike parent deletion this never cascades: a schedule
+  /// left without parents remains as a valid root.
+  ///
+  /// # Errors
+  /// Returns `ScheduleNotFound` if `schedule_id` does not exist.
+  pub fn remove_parents(
+    &mut self,
+    schedule_id: ScheduleId,
+    parents: HashSet<ScheduleId>,
+  ) -> Result<(), ScheduleError> {
+    self.tracked(
+      vec![schedule_id],
+      |this| this.remove_parents_untracked(schedule_id, parents),
+      |_| Vec::new(),
+    )
+  }
+
+  fn remove_parents_untracked(
+    &mut self,
+    schedule_id: ScheduleId,
+    parents: HashSet<ScheduleId>,
+  ) -> Result<(), ScheduleError> {
+    if !self.schedules.contains_key(&schedule_id) {
+      return Err(ScheduleError::ScheduleNotFound);
+    }
+
+    if let Some(current) = self.parent_relations.get_mut(&schedule_id) {
+      for parent in &parents {
+        if current.remove(parent)
+          && let Some(children) = self.child_relations.get_mut(parent)
+        {
+          children.remove(&schedule_id);
+        }
+      }
+    }
+
+    self.notify(ScheduleEvent::Updated(schedule_id));
+    Ok(())
+  }
+
+  /// Replace the parent set of an existing schedule in one step, e.g. when
+  /// dragging it into another group.
+  ///
+  /// The schedule is validated against `new_parents` alone (levels,
+  /// containment, cycles, depth and overlaps, no longer exempting the old
+  /// parents); only if that succeeds are the relations swapped. An empty
+  /// set makes the schedule a root. Children are unaffected.
+  ///
+  /// # Errors
+  /// Returns `ScheduleNotFound` if `schedule_id` does not exist, or the
+  /// validation error of `add_parents`, leaving the relations unchanged.
+  pub fn reparent(
+    &mut self,
+    schedule_id: ScheduleId,
+    new_parents: HashSet<ScheduleId>,
+  ) -> Result<(), ScheduleError> {
+    self.tracked(
+      vec![schedule_id],
+      |this| this.reparent_untracked(schedule_id, new_parents),
+      |_| Vec::new(),
+    )
+  }
+
+  fn reparent_untracked(
+    &mut self,
+    schedule_id: ScheduleId,
+    new_parents: HashSet<ScheduleId>,
+  ) -> Result<(), ScheduleError> {
+    let schedule = self
+      .schedules
+      .get(&schedule_id)
+      .ok_or(ScheduleError::ScheduleNotFound)?
+      .clone();
+
+    self.validate_no_cycle(schedule_id, &new_parents)?;
+
+    // Swap before the remaining checks so depth and lineage are computed
+    // against the new parents, then put the old set back if they fail.
+    let old_parents = self.replace_parents(schedule_id, new_parents.clone());
+    let valid = self
+      .validate_schedule(&schedule, &new_parents, &HashSet::from([schedule_id]))
+      .and_then(|()| self.validate_depth(Some(schedule_id), &new_parents));
+    if let Err(e) = valid {
+      self.replace_parents(schedule_id, old_parents);
+      return Err(e);
+    }
+
+    self.notify(ScheduleEvent::Updated(schedule_id));
+    Ok(())
+  }
+
+  /// Set the parent set of `schedule_id` to `parents`, updating the child
+  /// sets on both sides, and return the previous parent set. No validation.
+  fn replace_parents(
+    &mut self,
+    schedule_id: ScheduleId,
+    parents: HashSet<ScheduleId>,
+  ) -> HashSet<ScheduleId> {
+    let old = self
+      .parent_relations
+      .remove(&schedule_id)
+      .unwrap_or_default();
+    for parent in &old {
+      if let Some(children) = self.child_relations.get_mut(parent) {
+        children.remove(&schedule_id);
+      }
+    }
+    for parent in &parents {
+      self
+        .child_relations
+        .entry(*parent)
+        .or_default()
+        .insert(schedule_id);
+    }
+    self.parent_relations.insert(schedule_id, parents);
+    old
+  }
+
+  /// Replace the data of an existing schedule while keeping its id and
+  /// relations.
+  ///
+  /// The new schedule is re-validated against the existing parents and the
+  /// exclusivity rules; the schedule's own interval and those of its direct
+  /// children are excluded from the overlap checks. Indices are updated when
+  /// the level, time range or exclusivity changes.
+  ///
+  /// # Errors
+  /// Returns:
+  /// - `ScheduleNotFound` if `schedule_id` does not exist.
+  /// - Any error of `create_schedule` for the new data against the parents.
+  /// - `LevelExceedsParent` if an existing child's level is not higher than
+  ///   the new level.
+  /// - `TimeRangeExceedsParent` if the new range no longer contains an
+  ///   existing child.
+  pub fn update_schedule(
+    &mut self,
+    schedule_id: ScheduleId,
+    new: Schedule,
+  ) -> Result<(), ScheduleError> {
+    self.tracked(
+      vec![schedule_id],
+      |this| this.update_schedule_untracked(schedule_id, new),
+      |_| Vec::new(),
+    )
+  }
+
+  fn update_schedule_untracked(
+    &mut self,
+    schedule_id: ScheduleId,
+    new: Schedule,
+  ) -> Result<(), ScheduleError> {
+    let old = self
+      .schedules
+      .get(&schedule_id)
+      .ok_or(ScheduleError::ScheduleNotFound)?
+      .clone();
+
+    let parents = self
+      .parent_relations
+      .get(&schedule_id)
+      .cloned()
+      .unwrap_or_default();
+    let children = self
+      .child_relations
+      .get(&schedule_id)
+      .cloned()
+      .unwrap_or_default();
+
+    let mut ignore = children.clone();
+    ignore.insert(schedule_id);
+    self.validate_schedule(&new, &parents, &ignore)?;
+
+    // Existing children must still sit below and inside the updated schedule
+    for child_id in &children {
+      if let Some(child) = self.schedules.get(child_id)
+        && child.level <= new.level
+      {
+        return Err(ScheduleError::LevelExceedsParent);
+      }
+    }
+    self.validate_contains_children(schedule_id, new.start, new.end)?;
+
+    self.unindex_schedule(schedule_id, &old);
+    self.index_schedule(schedule_id, &new);
+    self.schedules.insert(schedule_id, new);
+
+    self.notify(ScheduleEvent::Updated(schedule_id));
+    Ok(())
+  }
+
+  /// Shift a schedule and all of its descendants by `delta`.
+  ///
+  /// Every shifted schedule is re-validated against the rest of the
+  /// manager; intervals belonging to the moving subtree are excluded from
+  /// the overlap checks and parents outside the subtree must still contain
+  /// their children. If any position is invalid the move is rolled back and
+  /// the error returned, leaving the manager unchanged; a `delta` that
+  /// takes a schedule past the representable time range fails with
+  /// `TimeOutOfRange`.
+  pub fn move_schedule(
+    &mut self,
+    schedule_id: ScheduleId,
+    delta: Duration,
+  ) -> Result<(), ScheduleError> {
+    self.tracked(
+      self.subtree_ids(schedule_id),
+      |this| this.move_schedule_untracked(schedule_id, delta),
+      |_| Vec::new(),
+    )
+  }
+
+  fn move_schedule_untracked(
+    &mut self,
+    schedule_id: ScheduleId,
+    delta: Duration,
+  ) -> Result<(), ScheduleError> {
+    if !self.schedules.contains_key(&schedule_id) {
+      return Err(ScheduleError::ScheduleNotFound);
+    }
+
+    let mut subtree = self.descendants(schedule_id);
+    subtree.push(schedule_id);
+    let moving: HashSet<ScheduleId> = subtree.iter().copied().collect();
+
+    let originals: Vec<(ScheduleId, Schedule)> = subtree
+      .iter()
+      .filter_map(|id| self.schedules.get(id).map(|s| (*id, s.clone())))
+      .collect();
+    let mut targets = Vec::with_capacity(originals.len());
+    for (_, schedule) in &originals {
+      targets.push(Self::shifted(schedule, delta)?);
+    }
+
+    // Take the subtree out of the indices so it does not conflict with
+    // itself, then validate each shifted schedule against the remainder.
+    for (id, schedule) in &originals {
+      self.unindex_schedule(*id, schedule);
+    }
+
+    let mut shifted = Vec::with_capacity(originals.len());
+    for ((id, _), moved) in originals.iter().zip(targets) {
+      let outside_parents: HashSet<ScheduleId> = self
+        .parent_relations
+        .get(id)
+        .map(|p| p.difference(&moving).copied().collect())
+        .unwrap_or_default();
+      if let Err(e) = self.validate_schedule(&moved, &outside_parents, &moving) {
+        for (id, schedule) in &originals {
+          self.index_schedule(*id, schedule);
+        }
+        return Err(e);
+      }
+      shifted.push((*id, moved));
+    }
+
+    for (id, moved) in shifted {
+      self.index_schedule(id, &moved);
+      self.schedules.insert(id, moved);
+      self.notify(ScheduleEvent::Updated(id));
+    }
+
+    Ok(())
+  }
+
+  /// Copy of `schedule` moved by `delta`; open-ended schedules stay
+  /// open-ended. Fails with `TimeOutOfRange` instead of overflowing.
+  fn shifted(schedule: &Schedule, delta: Duration) -> Result<Schedule, ScheduleError> {
+    let mut moved = schedule.clone();
+    moved.start = moved
+      .start
+      .checked_add_signed(delta)
+      .ok_or(ScheduleError::TimeOutOfRange)?;
+    if !schedule.is_open_ended() {
+      moved.end = moved
+        .end
+        .checked_add_signed(delta)
+        .ok_or(ScheduleError::TimeOutOfRange)?;
+    }
+    Ok(moved)
+  }
+
+  /// Move every schedule at level `from` to level `to`, e.g. after
+  /// inserting a new tier into the hierarchy.
+  ///
+  /// Each re-levelled schedule is re-validated against its parents and the
+  /// rest of the manager at its new level, and its children must still sit
+  /// strictly below it. Schedules at `from` are validated as a group, so
+  /// overlaps among themselves are not re-checked. If anything fails the
+  /// whole shift is rolled back. Shifting an empty level or onto itself is a
+  /// no-op.
+  ///
+  /// # Errors
+  /// Returns:
+  /// - `LevelExceedsParent` if a parent would no longer be above, or a child
+  ///   no longer below, a shifted schedule.
+  /// - Any other error of `update_schedule` for a shifted schedule.
+  pub fn shift_level(
+    &mut self,
+    from: ScheduleLevel,
+    to: ScheduleLevel,
+  ) -> Result<(), ScheduleError> {
+    let ids = self.ids_at_level(from);
+    self.tracked(
+      ids,
+      |this| this.shift_level_untracked(from, to),
+      |_| Vec::new(),
+    )
+  }
+
+  fn shift_level_untracked(
+    &mut self,
+    from: ScheduleLevel,
+    to: ScheduleLevel,
+  ) -> Result<(), ScheduleError> {
+    let ids = self.ids_at_level(from);
+    if from == to || ids.is_empty() {
+      return Ok(());
+    }
+    let moving: HashSet<ScheduleId> = ids.iter().copied().collect();
+
+    // Children are never at `from` themselves, so their levels are fixed.
+    for id in &ids {
+      let children = self.child_relations.get(id).into_iter().flatten();
+      for child in children {
+        if self.schedules.get(child).is_some_and(|c| c.level <= to) {
+          return Err(ScheduleError::LevelExceedsParent);
+        }
+      }
+    }
+
+    let originals: Vec<(ScheduleId, Schedule)> = ids
+      .iter()
+      .filter_map(|id| self.schedules.get(id).map(|s| (*id, s.clone())))
+      .collect();
+
+    // Take the level out of the indices so the group does not conflict
+    // with itself, then validate each schedule against the remainder.
+    for (id, schedule) in &originals {
+      self.unindex_schedule(*id, schedule);
+    }
+
+    let mut shifted = Vec::with_capacity(originals.len());
+    for (id, schedule) in &originals {
+      let mut relevelled = schedule.clone();
+      relevelled.level = to;
+      let parents = self.parent_relations.get(id).cloned().unwrap_or_default();
+      if let Err(e) = self.validate_schedule(&relevelled, &parents, &moving) {
+        for (id, schedule) in &originals {
+          self.index_schedule(*id, schedule);
+        }
+        return Err(e);
+      }
+      shifted.push((*id, relevelled));
+    }
+
+    for (id, relevelled) in shifted {
+      self.index_schedule(id, &relevelled);
+      self.schedules.insert(id, relevelled);
+      self.notify(ScheduleEvent::Updated(id));
+    }
+
+    Ok(())
+  }
+
+  /// Ids of the schedules at `level`, in no particular order.
+  fn ids_at_level(&self, level: ScheduleLevel) -> Vec<ScheduleId> {
+    self
+      .level_index
+      .get(&level)
+      .map(|ids| ids.iter().copied().collect())
+      .unwrap_or_default()
+  }
+
+  /// Delete a schedule, cascading to children that lose their last parent.
+  ///
+  /// Returns the set of all removed ids, including `schedule_id` itself.
   pub fn delete_schedule(
     &mut self,
     schedule_id: ScheduleId,
+  ) -> Result<std::collections::HashSet<ScheduleId>, ScheduleError> {
+    self.tracked(
+      self.subtree_ids(schedule_id),
+      |this| this.delete_schedule_untracked(schedule_id),
+      |_| Vec::new(),
+    )
+  }
+
+  fn delete_schedule_untracked(
+    &mut self,
+    schedule_id: ScheduleId,
   ) -> Result<std::collections::HashSet<ScheduleId>, ScheduleError> {
     // Get the schedule first to validate it exists
     let schedule = self
@@ -603,44 +3408,8 @@ impl ScheduleManager {
       .ok_or(ScheduleError::ScheduleNotFound)?
       .clone();
 
-    // Remove from indices
-    if schedule.exclusive {
-      debug_assert!(
-        self.exclusive_index.contains_key(&schedule.level),
-        "internal invariant: missing exclusive index for schedule level"
-      );
-      // The debug_assert above guarantees the key exists in the map.
-      // Access it safely and panic with a clear message if the invariant
-      // is violated in release builds.
-      let lapper = self
-        .exclusive_index
-        .get_mut(&schedule.level)
-        .expect("internal invariant: missing exclusive index for schedule level");
-
-      lapper.remove(&super::lapper::Interval {
-        start: schedule.start,
-        stop: schedule.end,
-        val: schedule_id,
-      });
-    }
-
-    debug_assert!(
-      self.all_index.contains_key(&schedule.level),
-      "internal invariant: missing all index for schedule level"
-    );
-    // The debug_assert above guarantees the key exists in the map.
-    // Access it safely and panic with a clear message if the invariant
-    // is violated in release builds.
-    let lapper = self
-      .all_index
-      .get_mut(&schedule.level)
-      .expect("internal invariant: missing all index for schedule level");
-
-    lapper.remove(&super::lapper::Interval {
-      start: schedule.start,
-      stop: schedule.end,
-      val: schedule_id,
-    });
+    // Remove from interval and level indices
+    self.unindex_schedule(schedule_id, &schedule);
 
     // Aggregate set of removed ids including this schedule and any
     // recursively deleted children. We remove `schedule_id`'s child
@@ -655,20 +3424,18 @@ impl ScheduleManager {
           // If child has no remaining parents, cascade delete it
           if parents.is_empty() {
             let child_removed = self.delete_schedule(child)?;
-            removed.extend(child_removed.into_iter());
+            removed.extend(child_removed);
           }
         }
       }
     }
 
-    // Remove parent relations
-    self.parent_relations.remove(&schedule_id);
-
-    // Remove from level index
-    if let Some(set) = self.level_index.get_mut(&schedule.level) {
-      set.remove(&schedule_id);
-      if set.is_empty() {
-        self.level_index.remove(&schedule.level);
+    // Remove parent relations and unlink this schedule from its parents
+    if let Some(parents) = self.parent_relations.remove(&schedule_id) {
+      for parent in parents {
+        if let Some(children) = self.child_relations.get_mut(&parent) {
+          children.remove(&schedule_id);
+        }
       }
     }
 
@@ -677,6 +3444,7 @@ impl ScheduleManager {
 
     // include this id in the returned set
     removed.insert(schedule_id);
+    self.notify(ScheduleEvent::Deleted(schedule_id));
 
     // Update full-text index - disabled
     // self.ft_delete_schedule(schedule_id);
@@ -686,10 +3454,513 @@ impl ScheduleManager {
     Ok(removed)
   }
 
+  /// Delete several schedules at once, each with the usual cascade.
+  ///
+  /// Ids that are unknown, repeated, or already removed by an earlier
+  /// cascade in the same call are skipped rather than reported as
+  /// `ScheduleNotFound`, so a UI selection can be passed as-is. Returns the
+  /// union of all removed ids and records a single undo step.
+  pub fn delete_schedules(
+    &mut self,
+    ids: &[ScheduleId],
+  ) -> Result<HashSet<ScheduleId>, ScheduleError> {
+    let mut affected: Vec<ScheduleId> = ids
+      .iter()
+      .filter(|id| self.schedules.contains_key(id))
+      .flat_map(|id| self.subtree_ids(*id))
+      .collect();
+    affected.sort();
+    affected.dedup();
+    self.tracked(
+      affected,
+      |this| this.delete_schedules_untracked(ids),
+      |_| Vec::new(),
+    )
+  }
+
+  fn delete_schedules_untracked(
+    &mut self,
+    ids: &[ScheduleId],
+  ) -> Result<HashSet<ScheduleId>, ScheduleError> {
+    let mut removed = HashSet::new();
+    for id in ids {
+      if self.schedules.contains_key(id) {
+        removed.extend(self.delete_schedule(*id)?);
+      }
+    }
+    Ok(removed)
+  }
+
+  /// Delete a single schedule and re-home its children onto its parents.
+  ///
+  /// In contrast to [`ScheduleManager::delete_schedule`], which
+  /// cascade-deletes children left without parents, this links every child
+  /// of `schedule_id` to every parent of `schedule_id` and then removes only
+  /// `schedule_id`. Links that would violate the level or time-containment
+  /// rules are skipped; a child that ends up with no parents is kept as a
+  /// root rather than deleted.
+  ///
+  /// Returns the set of removed ids, which is normally just `schedule_id`.
+  pub fn delete_schedule_reparent(
+    &mut self,
+    schedule_id: ScheduleId,
+  ) -> Result<HashSet<ScheduleId>, ScheduleError> {
+    self.tracked(
+      self.with_children(schedule_id),
+      |this| this.delete_schedule_reparent_untracked(schedule_id),
+      |_| Vec::new(),
+    )
+  }
+
+  fn delete_schedule_reparent_untracked(
+    &mut self,
+    schedule_id: ScheduleId,
+  ) -> Result<HashSet<ScheduleId>, ScheduleError> {
+    if !self.schedules.contains_key(&schedule_id) {
+      return Err(ScheduleError::ScheduleNotFound);
+    }
+
+    let parents = self
+      .parent_relations
+      .get(&schedule_id)
+      .cloned()
+      .unwrap_or_default();
+    let children = self
+      .child_relations
+      .remove(&schedule_id)
+      .unwrap_or_default();
+
+    for child_id in children {
+      let Some(child) = self.schedules.get(&child_id) else {
+        continue;
+      };
+      let new_parents: Vec<ScheduleId> = parents
+        .iter()
+        .copied()
+        .filter(|p| {
+          self.schedules.get(p).is_some_and(|parent| {
+            parent.level < child.level && parent.start <= child.start && parent.end >= child.end
+          })
+        })
+        .collect();
+
+      for parent in &new_parents {
+        self
+          .child_relations
+          .entry(*parent)
+          .or_default()
+          .insert(child_id);
+      }
+      let child_parents = self.parent_relations.entry(child_id).or_default();
+      child_parents.remove(&schedule_id);
+      child_parents.extend(new_parents);
+      self.notify(ScheduleEvent::Updated(child_id));
+    }
+
+    // Children were detached above, so this no longer cascades.
+    self.delete_schedule(schedule_id)
+  }
+
   pub fn get_schedule(&self, schedule_id: ScheduleId) -> Option<&Schedule> {
     self.schedules.get(&schedule_id)
   }
 
+  /// Look up several schedules at once.
+  ///
+  /// Returns clones for the ids that exist, in input order; unknown ids are
+  /// skipped.
+  pub fn get_many(&self, ids: &[ScheduleId]) -> Vec<(ScheduleId, Schedule)> {
+    ids
+      .iter()
+      .filter_map(|id| self.schedules.get(id).map(|s| (*id, s.clone())))
+      .collect()
+  }
+
+  /// Direct children of `schedule_id` with their data, ordered by start
+  /// time (then id). Unknown ids yield an empty list.
+  pub fn children_of(&self, schedule_id: ScheduleId) -> Vec<(ScheduleId, Schedule)> {
+    self.resolve_sorted(self.child_relations.get(&schedule_id))
+  }
+
+  /// Direct parents of `schedule_id` with their data, ordered by start time
+  /// (then id). Unknown ids yield an empty list.
+  pub fn parents_of(&self, schedule_id: ScheduleId) -> Vec<(ScheduleId, Schedule)> {
+    self.resolve_sorted(self.parent_relations.get(&schedule_id))
+  }
+
+  /// Clone the schedules for `ids`, skipping dangling ids, sorted by
+  /// `(start, id)`.
+  fn resolve_sorted(&self, ids: Option<&HashSet<ScheduleId>>) -> Vec<(ScheduleId, Schedule)> {
+    let mut out: Vec<(ScheduleId, Schedule)> = ids
+      .into_iter()
+      .flatten()
+      .filter_map(|id| self.schedules.get(id).map(|s| (*id, s.clone())))
+      .collect();
+    out.sort_by(|(a_id, a), (b_id, b)| (a.start, a_id).cmp(&(b.start, b_id)));
+    out
+  }
+
+  /// Look up a schedule together with its direct parents and children.
+  ///
+  /// Returns `(schedule, parents, children)` or `None` for an unknown id.
+  pub fn get_schedule_with_relations(
+    &self,
+    schedule_id: ScheduleId,
+  ) -> Option<(Schedule, Vec<ScheduleId>, Vec<ScheduleId>)> {
+    let schedule = self.schedules.get(&schedule_id)?.clone();
+    let parents = self
+      .parent_relations
+      .get(&schedule_id)
+      .map(|p| p.iter().copied().collect())
+      .unwrap_or_default();
+    let children = self
+      .child_relations
+      .get(&schedule_id)
+      .map(|c| c.iter().copied().collect())
+      .unwrap_or_default();
+    Some((schedule, parents, children))
+  }
+
+  /// Run `query_schedule` and attach each result's direct parent and child
+  /// ids, as `(id, schedule, parents, children)`.
+  ///
+  /// Results keep the order of `query_schedule`; the id lists are in no
+  /// particular order.
+  pub fn query_with_relations(
+    &self,
+    opts: QueryOptions,
+  ) -> Vec<(ScheduleId, Schedule, Vec<ScheduleId>, Vec<ScheduleId>)> {
+    let related = |map: &HashMap<ScheduleId, HashSet<ScheduleId>>, id: ScheduleId| {
+      map
+        .get(&id)
+        .map(|ids| ids.iter().copied().collect())
+        .unwrap_or_default()
+    };
+    self
+      .query_schedule(opts)
+      .into_iter()
+      .map(|(id, schedule)| {
+        let parents = related(&self.parent_relations, id);
+        let children = related(&self.child_relations, id);
+        (id, schedule, parents, children)
+      })
+      .collect()
+  }
+
+  /// Return all schedules whose half-open range `[start, end)` contains
+  /// the instant `t`.
+  ///
+  /// Queries each level's interval index rather than scanning every
+  /// schedule. Results are clones, as with `query_schedule`.
+  pub fn schedules_at(&self, t: DateTime<Utc>) -> Vec<(ScheduleId, Schedule)> {
+    self
+      .all_index
+      .values()
+      .flat_map(|lapper| lapper.point_query(t))
+      .filter_map(|iv| self.schedules.get(&iv.val).map(|s| (iv.val, s.clone())))
+      .collect()
+  }
+
+  /// Return every schedule overlapping `[start, stop)` together with
+  /// whether it is exclusive, for timelines that style the two differently.
+  ///
+  /// Queries each level's interval index. Results are ordered by level,
+  /// then by start, end and id; an empty or inverted window yields nothing.
+  pub fn query_intervals(
+    &self,
+    start: DateTime<Utc>,
+    stop: DateTime<Utc>,
+  ) -> Vec<(ScheduleId, Schedule, bool)> {
+    if start >= stop {
+      return Vec::new();
+    }
+    self
+      .all_index
+      .values()
+      .flat_map(|lapper| lapper.find(start, stop))
+      .filter_map(|iv| {
+        let schedule = self.schedules.get(&iv.val)?;
+        Some((iv.val, schedule.clone(), schedule.exclusive))
+      })
+      .collect()
+  }
+
+  /// Group the schedules overlapping `[start, end)` by the UTC days they
+  /// occupy, for calendar views.
+  ///
+  /// Every UTC date touched by the window gets an entry, in order, even if
+  /// empty. A schedule appears under each day its range overlaps within the
+  /// window; ranges are half-open, so one ending exactly at midnight does
+  /// not appear on the following day. Within a day schedules are ordered by
+  /// start time, then id. An empty or inverted window yields no days.
+  ///
+  /// # Errors
+  /// Returns `WindowTooLong` if the window touches more than
+  /// [`MAX_DAY_BUCKETS`] dates.
+  pub fn schedules_by_day(
+    &self,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+  ) -> Result<Vec<DaySchedules>, ScheduleError> {
+    if start >= end {
+      return Ok(Vec::new());
+    }
+    // Date of the last instant inside a half-open range ending at `end`.
+    let last_day = |end: DateTime<Utc>| (end - Duration::nanoseconds(1)).date_naive();
+    let first = start.date_naive();
+    if (last_day(end) - first).num_days() >= MAX_DAY_BUCKETS {
+      return Err(ScheduleError::WindowTooLong);
+    }
+    let mut days: Vec<DaySchedules> = first
+      .iter_days()
+      .take_while(|day| *day <= last_day(end))
+      .map(|day| (day, Vec::new()))
+      .collect();
+
+    let mut hits: Vec<(ScheduleId, &Schedule)> = self
+      .all_index
+      .values()
+      .flat_map(|lapper| lapper.find(start, end))
+      .filter_map(|iv| self.schedules.get(&iv.val).map(|s| (iv.val, s)))
+      .collect();
+    hits.sort_by_key(|(id, s)| (s.start, *id));
+
+    for (id, schedule) in hits {
+      let from = (schedule.start.max(start).date_naive() - first).num_days() as usize;
+      let to = (last_day(schedule.end.min(end)) - first).num_days() as usize;
+      for (_, bucket) in &mut days[from..=to] {
+        bucket.push((id, schedule.clone()));
+      }
+    }
+    Ok(days)
+  }
+
+  /// Iterate over all stored schedules without cloning.
+  ///
+  /// Iteration order is unspecified.
+  pub fn iter(&self) -> impl Iterator<Item = (&ScheduleId, &Schedule)> {
+    self.schedules.iter()
+  }
+
+  /// Whether a schedule with `schedule_id` exists, without cloning it.
+  pub fn contains(&self, schedule_id: ScheduleId) -> bool {
+    self.schedules.contains_key(&schedule_id)
+  }
+
+  /// Number of stored schedules.
+  pub fn len(&self) -> usize {
+    self.schedules.len()
+  }
+
+  /// Returns true if the manager holds no schedules.
+  pub fn is_empty(&self) -> bool {
+    self.schedules.is_empty()
+  }
+
+  /// Levels that currently hold at least one schedule, ascending.
+  pub fn levels(&self) -> Vec<ScheduleLevel> {
+    let mut levels: Vec<ScheduleLevel> = self.level_index.keys().copied().collect();
+    levels.sort_unstable();
+    levels
+  }
+
+  /// Number of schedules at `level`.
+  pub fn count_at_level(&self, level: ScheduleLevel) -> usize {
+    self.level_index.get(&level).map_or(0, HashSet::len)
+  }
+
+  /// List every pair of schedules at the same level whose time ranges
+  /// overlap, for `level` only or for all levels when `None`.
+  ///
+  /// Diagnostic only: useful for auditing data imported without
+  /// exclusivity before turning it on. Each level is scanned with a sweep
+  /// line over its start-ordered intervals; within a pair the schedule that
+  /// starts first comes first. Touching ranges do not conflict.
+  pub fn find_all_conflicts(&self, level: Option<ScheduleLevel>) -> Vec<(ScheduleId, ScheduleId)> {
+    let mut pairs = Vec::new();
+    let lappers = self
+      .all_index
+      .iter()
+      .filter(|(l, _)| level.is_none_or(|level| **l == level));
+    for (_, lapper) in lappers {
+      let mut active: Vec<&super::lapper::Interval> = Vec::new();
+      for iv in lapper.iter() {
+        active.retain(|a| a.stop > iv.start);
+        pairs.extend(active.iter().map(|a| (a.val, iv.val)));
+        active.push(iv);
+      }
+    }
+    pairs
+  }
+
+  /// Distinct tags used by any schedule, sorted.
+  pub fn all_tags(&self) -> BTreeSet<String> {
+    self
+      .schedules
+      .values()
+      .flat_map(|s| s.tags.iter().cloned())
+      .collect()
+  }
+
+  /// Compute counts and density figures over all stored schedules.
+  ///
+  /// Per-level figures come from the level and interval indices. The
+  /// busiest window is found with a sweep over every interval's start and
+  /// stop, treating intervals as half-open so back-to-back schedules do
+  /// not count as concurrent.
+  pub fn statistics(&self) -> ScheduleStats {
+    let per_level = self
+      .level_index
+      .iter()
+      .filter(|(_, ids)| !ids.is_empty())
+      .map(|(level, ids)| (*level, ids.len()))
+      .collect();
+    let covered_per_level = self
+      .all_index
+      .iter()
+      .map(|(level, lapper)| (*level, lapper.covered_duration()))
+      .filter(|(_, d)| *d > Duration::zero())
+      .collect();
+    let exclusive = self.exclusive_ids.len();
+
+    // Ends sort before starts at the same instant (-1 < 1).
+    let mut events: Vec<(DateTime<Utc>, i32)> = self
+      .all_index
+      .values()
+      .flat_map(|lapper| lapper.iter())
+      .flat_map(|iv| [(iv.start, 1), (iv.stop, -1)])
+      .collect();
+    events.sort();
+    let mut busiest_window = None;
+    let mut busiest_count = 0;
+    let mut running = 0i32;
+    let mut i = 0;
+    while i < events.len() {
+      let t = events[i].0;
+      while i < events.len() && events[i].0 == t {
+        running += events[i].1;
+        i += 1;
+      }
+      if running as usize > busiest_count
+        && let Some(&(next, _)) = events.get(i)
+      {
+        busiest_count = running as usize;
+        busiest_window = Some((t, next));
+      }
+    }
+
+    ScheduleStats {
+      total: self.schedules.len(),
+      per_level,
+      exclusive,
+      covered_per_level,
+      busiest_window,
+      busiest_count,
+    }
+  }
+
+  /// Remove every schedule, relation and index entry.
+  ///
+  /// Afterwards the manager holds the same data as `ScheduleManager::new()`.
+  /// A registered observer is kept and receives one `Deleted` event per
+  /// removed schedule. With history enabled (see
+  /// `with_history_limit`), clearing can be undone like other mutations.
+  pub fn clear(&mut self) {
+    let ids = self.schedules.keys().copied().collect();
+    // Clearing cannot fail; the result only carries the history bookkeeping.
+    let _ = self.tracked(
+      ids,
+      |this| {
+        this.clear_untracked();
+        Ok(())
+      },
+      |_| Vec::new(),
+    );
+  }
+
+  fn clear_untracked(&mut self) {
+    let removed: Vec<ScheduleId> = self.schedules.keys().copied().collect();
+    self.schedules.clear();
+    self.exclusive_index.clear();
+    self.exclusive_ids.clear();
+    self.all_index.clear();
+    self.parent_relations.clear();
+    self.child_relations.clear();
+    self.level_index.clear();
+    self.name_index.clear();
+    for id in removed {
+      self.notify(ScheduleEvent::Deleted(id));
+    }
+  }
+
+  /// Compare this manager (the older state) with `other` (the newer one).
+  ///
+  /// A schedule counts as changed when any of its fields or its set of
+  /// parents differs; child sets are derived from parent sets and are not
+  /// compared separately.
+  pub fn diff(&self, other: &ScheduleManager) -> ScheduleDiff {
+    let parents_of = |mgr: &ScheduleManager, id: &ScheduleId| {
+      mgr
+        .parent_relations
+        .get(id)
+        .filter(|p| !p.is_empty())
+        .cloned()
+    };
+
+    let mut diff = ScheduleDiff::default();
+    for (id, schedule) in &self.schedules {
+      match other.schedules.get(id) {
+        None => {
+          diff.removed.insert(*id);
+        }
+        Some(theirs) => {
+          if schedule != theirs || parents_of(self, id) != parents_of(other, id) {
+            diff.changed.insert(*id);
+          }
+        }
+      }
+    }
+    diff.added = other
+      .schedules
+      .keys()
+      .filter(|id| !self.schedules.contains_key(id))
+      .copied()
+      .collect();
+    diff
+  }
+
+  /// Lazily yield schedules starting at or after `from`, in ascending start
+  /// order (ties broken by end, then id), optionally restricted to `level`.
+  ///
+  /// Unlike `query_schedule` nothing is collected up front: the per-level
+  /// interval indices are already sorted, so they are merged on the fly and
+  /// each schedule is cloned only when it is reached. Schedules that began
+  /// before `from` are not yielded even if they are still running.
+  pub fn agenda(
+    &self,
+    from: DateTime<Utc>,
+    level: Option<ScheduleLevel>,
+  ) -> impl Iterator<Item = (ScheduleId, Schedule)> + '_ {
+    let mut cursors: Vec<_> = self
+      .all_index
+      .iter()
+      .filter(|(l, _)| level.is_none_or(|level| **l == level))
+      .map(|(_, lapper)| lapper.range(from, DateTime::<Utc>::MAX_UTC).peekable())
+      .collect();
+
+    std::iter::from_fn(move || {
+      // Few levels exist in practice, so a linear scan for the smallest
+      // head is cheaper than maintaining a heap.
+      let next = cursors
+        .iter_mut()
+        .filter_map(|c| c.peek().copied().map(|iv| (iv, c)))
+        .min_by(|(a, _), (b, _)| a.cmp(b))
+        .and_then(|(_, c)| c.next())?;
+      Some(next.val)
+    })
+    .filter_map(|id| self.schedules.get(&id).map(|s| (id, s.clone())))
+  }
+
   /// Query schedules using flexible options.
   ///
   /// Returns a Vec of (ScheduleId, Schedule) matching the filters. The returned
@@ -698,6 +3969,15 @@ impl ScheduleManager {
   pub fn query_schedule(&self, opts: QueryOptions) -> Vec<(ScheduleId, Schedule)> {
     let mut out = Vec::new();
 
+    let named_matcher = match &opts.named_matcher {
+      Some(name) => match self.matchers.get(name) {
+        Some(m) => Some(m),
+        // Unknown names match nothing rather than being ignored
+        None => return out,
+      },
+      None => None,
+    };
+
     // Determine candidate set using available indexes to avoid scanning
     // all schedules when possible.
     let mut candidates: Option<HashSet<ScheduleId>> = None;
@@ -712,50 +3992,90 @@ impl ScheduleManager {
       }
     }
 
-    // Full-text search (tantivy) candidate narrowing temporarily disabled; name filtering is applied later linearly.
+    // Union the level index over an inclusive level range
+    if opts.level_min.is_some() || opts.level_max.is_some() {
+      let min = opts.level_min.unwrap_or(ScheduleLevel::MIN);
+      let max = opts.level_max.unwrap_or(ScheduleLevel::MAX);
+      let ids: HashSet<ScheduleId> = self
+        .level_index
+        .iter()
+        .filter(|(level, _)| (min..=max).contains(*level))
+        .flat_map(|(_, set)| set.iter().copied())
+        .collect();
+      candidates = Some(match candidates {
+        Some(c) => c.intersection(&ids).copied().collect(),
+        None => ids,
+      });
+    }
+
+    // Narrow by hierarchy relations
+    let mut relation_sets = Vec::new();
+    if let Some(parent) = opts.child_of {
+      relation_sets.push(
+        self
+          .child_relations
+          .get(&parent)
+          .cloned()
+          .unwrap_or_default(),
+      );
+    }
+    if let Some(child) = opts.parent_of {
+      relation_sets.push(
+        self
+          .parent_relations
+          .get(&child)
+          .cloned()
+          .unwrap_or_default(),
+      );
+    }
+    if opts.roots_only {
+      relation_sets.push(
+        self
+          .schedules
+          .keys()
+          .filter(|id| self.parent_relations.get(id).is_none_or(|p| p.is_empty()))
+          .copied()
+          .collect(),
+      );
+    }
+    for ids in relation_sets {
+      candidates = Some(match candidates {
+        Some(c) => c.intersection(&ids).copied().collect(),
+        None => ids,
+      });
+    }
+
+    // Narrow by the name token index; the candidates are a superset for
+    // every `NameMatch` mode and the exact filter is applied below.
+    if let Some(ref name_filter) = opts.name
+      && let Some(ids) = self.name_candidates(name_filter)
+    {
+      candidates = Some(match candidates {
+        Some(c) => c.intersection(&ids).copied().collect(),
+        None => ids,
+      });
+    }
 
     // If exclusive filter is specified, intersect with computed exclusive set
     if let Some(excl) = opts.exclusive {
       if excl {
-        // Compute exclusive IDs on demand from exclusive indices
-        let mut excl_ids = HashSet::new();
-        for lapper in self.exclusive_index.values() {
-          for interval in &lapper.intervals {
-            excl_ids.insert(interval.val);
-          }
-        }
         match &mut candidates {
-          Some(c) => {
-            *c = c.intersection(&excl_ids).cloned().collect();
-          }
-          None => {
-            candidates = Some(excl_ids);
-          }
+          Some(c) => c.retain(|id| self.exclusive_ids.contains(id)),
+          None => candidates = Some(self.exclusive_ids.clone()),
         }
       } else {
-        // excl == false: prefer candidates that are NOT exclusive
-        // Compute exclusive IDs on demand
-        let mut excl_ids = HashSet::new();
-        for lapper in self.exclusive_index.values() {
-          for interval in &lapper.intervals {
-            excl_ids.insert(interval.val);
-          }
-        }
+        // excl == false: keep only candidates that are NOT exclusive
         match &mut candidates {
-          Some(c) => {
-            for id in excl_ids.iter() {
-              c.remove(id);
-            }
-          }
+          Some(c) => c.retain(|id| !self.exclusive_ids.contains(id)),
           None => {
-            // build candidate set as all schedules minus exclusive ones
-            let mut s = HashSet::new();
-            for id in self.schedules.keys() {
-              if !excl_ids.contains(id) {
-                s.insert(*id);
-              }
-            }
-            candidates = Some(s);
+            candidates = Some(
+              self
+                .schedules
+                .keys()
+                .filter(|id| !self.exclusive_ids.contains(id))
+                .copied()
+                .collect(),
+            );
           }
         }
       }
@@ -770,10 +4090,22 @@ impl ScheduleManager {
     // Now apply remaining filters (name, time, matcher) on candidate ids
     for id in base_ids {
       if let Some(schedule) = self.schedules.get(&id) {
-        if let Some(ref name_filter) = opts.name {
-          if !schedule.name.contains(name_filter) {
-            continue;
-          }
+        if let Some(ref name_filter) = opts.name
+          && !opts.name_match.matches(&schedule.name, name_filter)
+        {
+          continue;
+        }
+
+        if let Some(ref tag) = opts.tag
+          && !schedule.tags.contains(tag)
+        {
+          continue;
+        }
+        if !opts.tags_any.is_empty() && !opts.tags_any.iter().any(|t| schedule.tags.contains(t)) {
+          continue;
+        }
+        if !opts.tags_all.iter().all(|t| schedule.tags.contains(t)) {
+          continue;
         }
 
         // Time filtering:
@@ -799,16 +4131,45 @@ impl ScheduleManager {
           (None, None) => {}
         }
 
-        if let Some(ref m) = opts.matcher {
-          if !(m(schedule)) {
-            continue;
-          }
+        if let Some(ref m) = opts.matcher
+          && !(m(schedule))
+        {
+          continue;
+        }
+        if let Some(m) = named_matcher
+          && !m(schedule)
+        {
+          continue;
         }
 
         out.push((id, schedule.clone()));
       }
     }
 
+    if let Some(order) = opts.sort {
+      match order {
+        SortOrder::StartAsc => {
+          out.sort_by(|(a_id, a), (b_id, b)| (a.start, a_id).cmp(&(b.start, b_id)))
+        }
+        SortOrder::StartDesc => {
+          out.sort_by(|(a_id, a), (b_id, b)| (b.start, b_id).cmp(&(a.start, a_id)))
+        }
+        SortOrder::PriorityDesc => out.sort_by(|(a_id, a), (b_id, b)| {
+          (b.priority, a.start, a_id).cmp(&(a.priority, b.start, b_id))
+        }),
+      }
+    }
+
+    // Pagination is applied last, after all filters
+    let offset = opts.offset.unwrap_or(0);
+    if offset > 0 || opts.limit.is_some() {
+      out = out
+        .into_iter()
+        .skip(offset)
+        .take(opts.limit.unwrap_or(usize::MAX))
+        .collect();
+    }
+
     out
   }
 
@@ -821,4 +4182,74 @@ impl ScheduleManager {
   pub fn child_relations(&self) -> &HashMap<ScheduleId, HashSet<ScheduleId>> {
     &self.child_relations
   }
+
+  /// Return all transitive children of `schedule_id`.
+  ///
+  /// Performs a breadth-first walk over `child_relations`. Each id is
+  /// reported once even when reachable through several parents, and the
+  /// walk terminates on cyclic graphs. Unknown ids yield an empty vector.
+  pub fn descendants(&self, schedule_id: ScheduleId) -> Vec<ScheduleId> {
+    Self::walk_relations(&self.child_relations, schedule_id)
+  }
+
+  /// Return all transitive parents of `schedule_id`.
+  ///
+  /// Upward counterpart of [`ScheduleManager::descendants`], walking
+  /// `parent_relations`.
+  pub fn ancestors(&self, schedule_id: ScheduleId) -> Vec<ScheduleId> {
+    Self::walk_relations(&self.parent_relations, schedule_id)
+  }
+
+  /// Breadth-first traversal over a relation map starting at `start`
+  /// (excluded from the result).
+  fn walk_relations(
+    relations: &HashMap<ScheduleId, HashSet<ScheduleId>>,
+    start: ScheduleId,
+  ) -> Vec<ScheduleId> {
+    let mut out = Vec::new();
+    let mut visited = HashSet::from([start]);
+    let mut queue = VecDeque::from([start]);
+    while let Some(id) = queue.pop_front() {
+      if let Some(next) = relations.get(&id) {
+        for &n in next {
+          if visited.insert(n) {
+            out.push(n);
+            queue.push_back(n);
+          }
+        }
+      }
+    }
+    out
+  }
+}
+
+/// Column header used by CSV export and expected by CSV import.
+const CSV_HEADER: &str = "id,start,end,level,exclusive,name,parents";
+
+/// Lowercase `s` one char at a time. Unlike `str::to_lowercase`, which
+/// turns a word-final 'Σ' into 'ς' but a medial one into 'σ', a letter
+/// folds the same way wherever it sits, so folding keeps substrings.
+fn fold_case(s: &str) -> String {
+  s.chars().flat_map(char::to_lowercase).collect()
+}
+
+/// Split a schedule name into lowercased alphanumeric tokens for the name
+/// index.
+fn name_tokens(name: &str) -> impl Iterator<Item = String> + '_ {
+  name
+    .split(|c: char| !c.is_alphanumeric())
+    .filter(|t| !t.is_empty())
+    .map(fold_case)
+}
+
+/// Keys stored in the name index for `name`: every suffix of each of its
+/// tokens.
+fn name_keys(name: &str) -> impl Iterator<Item = String> + '_ {
+  name_tokens(name).flat_map(|token| {
+    let suffixes: Vec<String> = token
+      .char_indices()
+      .map(|(i, _)| token[i..].to_string())
+      .collect();
+    suffixes
+  })
 }
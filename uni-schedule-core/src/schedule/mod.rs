@@ -3,12 +3,21 @@
 //! This module provides functionality for managing time-based schedules with
 //! hierarchical relationships and exclusivity constraints.
 
+mod csv;
+mod ics;
 pub mod lapper;
 pub mod manager;
+pub mod recurrence;
 
 // Re-export public types for convenience
-pub use lapper::{Interval, Lapper};
-pub use manager::{QueryOptions, Schedule, ScheduleError, ScheduleLevel, ScheduleManager};
+pub use lapper::{Interval, IntervalMode, Lapper};
+pub use manager::{
+  AllowedWindow, CreateConflict, ExclusiveScope, FitStrategy, MAX_DAY_BUCKETS, MergeReport,
+  MergeStrategy, NameMatch, OPEN_END, QueryOptions, SNAPSHOT_VERSION, Schedule, ScheduleDiff,
+  ScheduleError, ScheduleEvent, ScheduleLevel, ScheduleManager, ScheduleObserver, ScheduleStats,
+  Snapshot, SortOrder,
+};
+pub use recurrence::{Frequency, Recurrence, RecurrenceEnd};
 
 // Alias used throughout the module for schedule identifiers.
 pub type ScheduleId = uuid::Uuid;
@@ -17,7 +26,6 @@ pub type ScheduleId = uuid::Uuid;
 mod tests {
   use chrono::{DateTime, Duration, Utc};
   use std::collections::HashSet;
-  use std::sync::Arc;
   use uuid::Uuid;
 
   use super::*;
@@ -58,6 +66,11 @@ mod tests {
         level: 1,
         exclusive: false,
         name: "child".into(),
+        description: None,
+        tags: Vec::new(),
+        priority: 0,
+        color: None,
+        exclusive_scope: ExclusiveScope::default(),
       },
       parents.clone(),
     );
@@ -73,6 +86,11 @@ mod tests {
           level: 5,
           exclusive: false,
           name: "parent".into(),
+          description: None,
+          tags: Vec::new(),
+          priority: 0,
+          color: None,
+          exclusive_scope: ExclusiveScope::default(),
         },
         HashSet::new(),
       )
@@ -88,6 +106,11 @@ mod tests {
         level: 5,
         exclusive: false,
         name: "badchild".into(),
+        description: None,
+        tags: Vec::new(),
+        priority: 0,
+        color: None,
+        exclusive_scope: ExclusiveScope::default(),
       },
       parent_set,
     );
@@ -167,6 +190,11 @@ mod tests {
       level: 1,
       exclusive: true,
       name: "exclusive".into(),
+      description: None,
+      tags: Vec::new(),
+      priority: 0,
+      color: None,
+      exclusive_scope: ExclusiveScope::default(),
     };
     let id1 = mgr.create_schedule(sched1, HashSet::new()).unwrap();
 
@@ -179,6 +207,11 @@ mod tests {
       level: 2,
       exclusive: false,
       name: "blocked".into(),
+      description: None,
+      tags: Vec::new(),
+      priority: 0,
+      color: None,
+      exclusive_scope: ExclusiveScope::default(),
     };
     let res = mgr.create_schedule(sched2, HashSet::new());
     assert_eq!(res, Err(ScheduleError::TimeRangeOverlaps));
@@ -190,6 +223,11 @@ mod tests {
       level: 2,
       exclusive: false,
       name: "ok".into(),
+      description: None,
+      tags: Vec::new(),
+      priority: 0,
+      color: None,
+      exclusive_scope: ExclusiveScope::default(),
     };
     let id3 = mgr.create_schedule(sched3, HashSet::new()).unwrap();
 
@@ -200,6 +238,11 @@ mod tests {
       level: 2,
       exclusive: false,
       name: "child".into(),
+      description: None,
+      tags: Vec::new(),
+      priority: 0,
+      color: None,
+      exclusive_scope: ExclusiveScope::default(),
     };
     let mut parents = HashSet::new();
     parents.insert(id1);
@@ -227,6 +270,11 @@ mod tests {
       level: 1,
       exclusive: false,
       name: "p1".into(),
+      description: None,
+      tags: Vec::new(),
+      priority: 0,
+      color: None,
+      exclusive_scope: ExclusiveScope::default(),
     };
     let p1 = mgr.create_schedule(parent1, HashSet::new()).unwrap();
 
@@ -236,6 +284,11 @@ mod tests {
       level: 1,
       exclusive: false,
       name: "p2".into(),
+      description: None,
+      tags: Vec::new(),
+      priority: 0,
+      color: None,
+      exclusive_scope: ExclusiveScope::default(),
     };
     let p2 = mgr.create_schedule(parent2, HashSet::new()).unwrap();
 
@@ -246,6 +299,11 @@ mod tests {
       level: 2,
       exclusive: false,
       name: "child".into(),
+      description: None,
+      tags: Vec::new(),
+      priority: 0,
+      color: None,
+      exclusive_scope: ExclusiveScope::default(),
     };
     let mut parents = HashSet::new();
     parents.insert(p1);
@@ -278,6 +336,11 @@ mod tests {
       level: 1,
       exclusive: false,
       name: "s".into(),
+      description: None,
+      tags: Vec::new(),
+      priority: 0,
+      color: None,
+      exclusive_scope: ExclusiveScope::default(),
     };
     // First insertion with explicit id should succeed
     let r1 = mgr.create_schedule_with_id(id, sched.clone(), HashSet::new());
@@ -331,6 +394,11 @@ mod tests {
       level: 1,
       exclusive: false,
       name: "a".into(),
+      description: None,
+      tags: Vec::new(),
+      priority: 0,
+      color: None,
+      exclusive_scope: ExclusiveScope::default(),
     };
     let i2 = Schedule {
       start: start + Duration::hours(1),
@@ -338,6 +406,11 @@ mod tests {
       level: 1,
       exclusive: false,
       name: "b".into(),
+      description: None,
+      tags: Vec::new(),
+      priority: 0,
+      color: None,
+      exclusive_scope: ExclusiveScope::default(),
     };
     let id1 = mgr.create_schedule(i1, HashSet::new()).unwrap();
     let id2 = mgr.create_schedule(i2, HashSet::new()).unwrap();
@@ -428,4 +501,3958 @@ mod tests {
     // in the token stream, then assert the expected tokens.
     serde_test::assert_ser_tokens(&lapper.readable(), &tokens);
   }
+
+  #[test]
+  fn lapper_merge_overlapping_keeps_adjacent_separate() {
+    let start = Utc::now();
+    let lapper = Lapper::from_vec(vec![
+      create_interval(start, 2),
+      create_interval(start + Duration::hours(1), 2),
+      // Adjacent to the merged [0, 3) span: must not be merged
+      create_interval(start + Duration::hours(3), 1),
+      create_interval_with_id(start + Duration::hours(6), 1, Uuid::now_v7()),
+    ]);
+
+    let merged = lapper.merge_overlapping();
+    assert_eq!(
+      merged,
+      vec![
+        (start, start + Duration::hours(3)),
+        (start + Duration::hours(3), start + Duration::hours(4)),
+        (start + Duration::hours(6), start + Duration::hours(7)),
+      ]
+    );
+
+    let empty = Lapper::new(std::collections::BTreeSet::new());
+    assert!(empty.merge_overlapping().is_empty());
+  }
+
+  #[test]
+  fn lapper_covered_duration_counts_union_once() {
+    let start = Utc::now();
+    let mut lapper = Lapper::new(std::collections::BTreeSet::new());
+    assert_eq!(lapper.covered_duration(), Duration::zero());
+
+    lapper.insert(create_interval(start, 2));
+    assert_eq!(lapper.covered_duration(), Duration::hours(2));
+
+    lapper.insert(create_interval(start + Duration::hours(1), 2));
+    lapper.insert(create_interval(start + Duration::hours(4), 1));
+    assert_eq!(lapper.covered_duration(), Duration::hours(4));
+
+    // Window [2h, 5h) sees [2h, 3h) and [4h, 5h)
+    assert_eq!(
+      lapper.covered_duration_in(start + Duration::hours(2), start + Duration::hours(5)),
+      Duration::hours(2)
+    );
+    assert_eq!(
+      lapper.covered_duration_in(start + Duration::hours(3), start + Duration::hours(3)),
+      Duration::zero()
+    );
+  }
+
+  #[test]
+  fn lapper_gaps_complement_within_window() {
+    let start = Utc::now();
+    let h = Duration::hours;
+    let lapper = Lapper::from_vec(vec![
+      create_interval(start - h(1), 2),
+      create_interval(start + h(3), 2),
+      create_interval(start + h(4), 2),
+    ]);
+
+    assert_eq!(
+      lapper.gaps(start, start + h(8)),
+      vec![(start + h(1), start + h(3)), (start + h(6), start + h(8))]
+    );
+    // Entirely free window
+    assert_eq!(
+      lapper.gaps(start + h(10), start + h(12)),
+      vec![(start + h(10), start + h(12))]
+    );
+    // Fully covered window
+    assert!(lapper.gaps(start + h(3), start + h(6)).is_empty());
+  }
+
+  #[test]
+  fn lapper_count_overlaps_matches_find() {
+    let start = Utc::now();
+    let h = Duration::hours;
+    let lapper = Lapper::from_vec(vec![
+      create_interval(start, 2),
+      create_interval(start + h(1), 2),
+      create_interval(start + h(5), 1),
+    ]);
+
+    assert_eq!(lapper.count_overlaps(start, start + h(2)), 2);
+    assert_eq!(
+      lapper.count_overlaps(start, start + h(6)),
+      lapper.find(start, start + h(6)).count()
+    );
+    assert_eq!(lapper.count_overlaps(start + h(3), start + h(5)), 0);
+    assert_eq!(lapper.count_overlaps(start + h(1), start), 0);
+  }
+
+  #[test]
+  fn lapper_nearest_after_and_before() {
+    let start = Utc::now();
+    let h = Duration::hours;
+    let (a, b, c) = (Uuid::now_v7(), Uuid::now_v7(), Uuid::now_v7());
+    let mut lapper = Lapper::new(std::collections::BTreeSet::new());
+    lapper.insert(create_interval_with_id(start, 1, a));
+    lapper.insert(create_interval_with_id(start + h(2), 1, b));
+    lapper.insert(create_interval_with_id(start + h(4), 1, c));
+
+    assert_eq!(lapper.nearest_after(start + h(1)).map(|iv| iv.val), Some(b));
+    assert_eq!(lapper.nearest_after(start + h(2)).map(|iv| iv.val), Some(b));
+    assert!(lapper.nearest_after(start + h(5)).is_none());
+
+    assert_eq!(
+      lapper.nearest_before(start + h(2)).map(|iv| iv.val),
+      Some(a)
+    );
+    assert_eq!(
+      lapper.nearest_before(start + h(9)).map(|iv| iv.val),
+      Some(c)
+    );
+    assert!(lapper.nearest_before(start).is_none());
+  }
+
+  #[test]
+  fn lapper_update_val_swaps_id() {
+    let start = Utc::now();
+    let stop = start + Duration::hours(1);
+    let (old, new) = (Uuid::now_v7(), Uuid::now_v7());
+    let mut lapper = Lapper::new(std::collections::BTreeSet::new());
+    lapper.insert(create_interval_with_id(start, 1, old));
+
+    assert!(lapper.update_val(start, stop, old, new));
+    let ids: Vec<ScheduleId> = lapper.find(start, stop).map(|iv| iv.val).collect();
+    assert_eq!(ids, vec![new]);
+    assert_eq!(lapper.intervals.len(), 1);
+
+    // The old interval is gone, so a second update is a no-op
+    assert!(!lapper.update_val(start, stop, old, new));
+  }
+
+  #[test]
+  fn lapper_remove_in_range_drops_overlaps_only() {
+    let start = Utc::now();
+    let h = Duration::hours;
+    let (a, b, c) = (Uuid::now_v7(), Uuid::now_v7(), Uuid::now_v7());
+    let mut lapper = Lapper::from_vec(vec![
+      create_interval_with_id(start, 2, a),
+      create_interval_with_id(start + h(1), 2, b),
+      create_interval_with_id(start + h(3), 1, c),
+    ]);
+
+    // [start+2h, start+3h) hits b only; c starts exactly at the stop
+    let removed = lapper.remove_in_range(start + h(2), start + h(3));
+    assert_eq!(removed.iter().map(|iv| iv.val).collect::<Vec<_>>(), vec![b]);
+    assert_eq!(lapper.intervals.len(), 2);
+    assert_eq!(lapper.count_overlaps(start, start + h(4)), 2);
+
+    assert!(
+      lapper
+        .remove_in_range(start + h(5), start + h(6))
+        .is_empty()
+    );
+    assert_eq!(lapper.remove_in_range(start, start + h(4)).len(), 2);
+    assert!(!lapper.has_overlap(start, start + h(4)));
+  }
+
+  #[test]
+  fn lapper_iter_and_range_by_start() {
+    let start = Utc::now();
+    let h = Duration::hours;
+    let (a, b, c) = (Uuid::now_v7(), Uuid::now_v7(), Uuid::now_v7());
+    let lapper = Lapper::from_vec(vec![
+      create_interval_with_id(start + h(3), 1, c),
+      create_interval_with_id(start, 5, a),
+      create_interval_with_id(start + h(1), 1, b),
+    ]);
+
+    let all: Vec<ScheduleId> = lapper.iter().map(|iv| iv.val).collect();
+    assert_eq!(all, vec![a, b, c]);
+
+    // a overlaps the window but starts before it, so only b is yielded
+    let started: Vec<ScheduleId> = lapper
+      .range(start + h(1), start + h(3))
+      .map(|iv| iv.val)
+      .collect();
+    assert_eq!(started, vec![b]);
+    assert_eq!(lapper.range(start, start + h(4)).count(), 3);
+    assert_eq!(lapper.range(start + h(4), start + h(9)).count(), 0);
+  }
+
+  #[test]
+  fn lapper_find_rev_is_find_reversed() {
+    let start = Utc::now();
+    let h = Duration::hours;
+    let mut intervals: Vec<Interval> = (0..40)
+      .map(|i| create_interval(start + h(i % 13) + Duration::minutes(i), 1 + i % 4))
+      .collect();
+    intervals.push(create_interval(start - h(2), 30));
+    let lapper = Lapper::from_vec(intervals);
+
+    for (qs, qe) in [(3, 5), (0, 1), (10, 20), (-5, -3), (2, 2)] {
+      let (qs, qe) = (start + h(qs), start + h(qe));
+      let mut fwd: Vec<&Interval> = lapper.find(qs, qe).collect();
+      let rev: Vec<&Interval> = lapper.find_rev(qs, qe).collect();
+      fwd.reverse();
+      assert_eq!(fwd, rev);
+    }
+    assert!(lapper.find_rev(start, start + h(1)).next().is_some());
+  }
+
+  #[test]
+  fn lapper_validate_after_mixed_operations() {
+    let start = Utc::now();
+    let mut lapper = Lapper::new(std::collections::BTreeSet::new());
+    assert_eq!(lapper.validate(), Ok(()));
+
+    let intervals: Vec<Interval> = (0..64)
+      .map(|i| create_interval(start + Duration::minutes(i * 7 % 50), 1 + i % 3))
+      .collect();
+    for iv in &intervals {
+      lapper.insert(iv.clone());
+      assert_eq!(lapper.validate(), Ok(()));
+    }
+    for iv in intervals.iter().step_by(3) {
+      assert!(lapper.remove(iv));
+      assert_eq!(lapper.validate(), Ok(()));
+    }
+    lapper.remove_in_range(start, start + Duration::minutes(20));
+    assert_eq!(lapper.validate(), Ok(()));
+    assert_eq!(Lapper::from_vec(intervals).validate(), Ok(()));
+  }
+
+  #[test]
+  fn lapper_from_sorted_unique_matches_from_vec() {
+    let start = Utc::now();
+    let mut intervals: Vec<Interval> = (0..20)
+      .map(|i| create_interval(start + Duration::minutes(i * 13 % 90), 1 + i % 2))
+      .collect();
+    intervals.sort();
+    intervals.dedup();
+
+    let fast = Lapper::from_sorted_unique(intervals.clone());
+    assert_eq!(fast.validate(), Ok(()));
+    let slow = Lapper::from_vec(intervals);
+    assert!(fast.iter().eq(slow.iter()));
+    assert!(
+      Lapper::from_sorted_unique(Vec::new())
+        .iter()
+        .next()
+        .is_none()
+    );
+  }
+
+  #[test]
+  #[should_panic(expected = "strictly increasing")]
+  #[cfg(debug_assertions)]
+  fn lapper_from_sorted_unique_rejects_unsorted() {
+    let start = Utc::now();
+    Lapper::from_sorted_unique(vec![
+      create_interval(start + Duration::hours(1), 1),
+      create_interval(start, 1),
+    ]);
+  }
+
+  #[test]
+  fn lapper_node_count_height_and_rebuild() {
+    let start = Utc::now();
+    let mut lapper = Lapper::new(std::collections::BTreeSet::new());
+    assert_eq!((lapper.node_count(), lapper.height()), (0, 0));
+
+    // Ascending inserts followed by removals from one side skew the tree
+    let intervals: Vec<Interval> = (0..100)
+      .map(|i| create_interval(start + Duration::minutes(i), 1))
+      .collect();
+    for iv in &intervals {
+      lapper.insert(iv.clone());
+    }
+    for iv in &intervals[..60] {
+      lapper.remove(iv);
+    }
+    assert_eq!(lapper.node_count(), 40);
+
+    lapper.rebuild();
+    assert_eq!(lapper.validate(), Ok(()));
+    assert_eq!(lapper.node_count(), 40);
+    // ceil(log2(40 + 1)) == 6 for a perfectly balanced tree
+    assert_eq!(lapper.height(), 6);
+  }
+
+  #[test]
+  fn interval_orders_by_start_stop_val() {
+    let t = Utc::now();
+    let h = Duration::hours;
+    let low = Uuid::from_u128(1);
+    let high = Uuid::from_u128(2);
+    let iv = |start, stop, val| Interval { start, stop, val };
+
+    let mut intervals = vec![
+      iv(t + h(1), t + h(2), low),
+      iv(t, t + h(3), low),
+      iv(t, t + h(2), high),
+      iv(t, t + h(2), low),
+    ];
+    intervals.sort();
+    assert_eq!(
+      intervals,
+      vec![
+        iv(t, t + h(2), low),
+        iv(t, t + h(2), high),
+        iv(t, t + h(3), low),
+        iv(t + h(1), t + h(2), low),
+      ]
+    );
+
+    // Hash agrees with Eq
+    let set: HashSet<Interval> = intervals.iter().cloned().chain(intervals.clone()).collect();
+    assert_eq!(set.len(), 4);
+  }
+
+  #[test]
+  fn lapper_coverage_profile_matches_point_queries() {
+    let t = Utc::now();
+    let h = Duration::hours;
+    let lapper = Lapper::new(std::collections::BTreeSet::from([
+      create_interval(t, 2),
+      create_interval(t + h(1), 2),
+      create_interval(t + h(1), 1),
+      create_interval(t + h(5), 1),
+    ]));
+
+    // Points at t, t+1h, ..., t+6h; stops are exclusive
+    let profile = lapper.coverage_profile(t, h(1), 7);
+    assert_eq!(profile, vec![1, 3, 1, 0, 0, 1, 0]);
+
+    let half = Duration::minutes(30);
+    let expected: Vec<usize> = (0..14)
+      .map(|k| lapper.point_query(t - half + half * k).count())
+      .collect();
+    assert_eq!(lapper.coverage_profile(t - half, half, 14), expected);
+
+    // Non-positive steps fall back to a scan per sample
+    assert_eq!(
+      lapper.coverage_profile(t + h(1), Duration::zero(), 2),
+      vec![3, 3]
+    );
+    assert_eq!(lapper.coverage_profile(t + h(2), -h(1), 3), vec![1, 3, 1]);
+    assert!(lapper.coverage_profile(t, h(1), 0).is_empty());
+  }
+
+  #[test]
+  fn lapper_coverage_profile_ignores_closed_mode() {
+    let t = Utc::now();
+    let h = Duration::hours;
+    let intervals = std::collections::BTreeSet::from([
+      create_interval(t, 2),
+      create_interval(t + h(1), 2),
+      create_interval(t + h(1), 1),
+    ]);
+    let half_open = Lapper::new(intervals);
+    let closed = half_open.clone().with_mode(IntervalMode::Closed);
+
+    // Stops stay exclusive on both the sweep and the fallback path, even
+    // though a closed point query at t+2h also counts [t, t+2h)
+    assert_eq!(closed.point_query(t + h(2)).count(), 3);
+    let expected = vec![1, 3, 1, 0];
+    assert_eq!(half_open.coverage_profile(t, h(1), 4), expected);
+    assert_eq!(closed.coverage_profile(t, h(1), 4), expected);
+    assert_eq!(
+      closed.coverage_profile(t + h(3), -h(1), 4),
+      vec![0, 1, 3, 1]
+    );
+    assert_eq!(
+      closed.coverage_profile(t + h(2), Duration::zero(), 2),
+      half_open.coverage_profile(t + h(2), Duration::zero(), 2)
+    );
+  }
+
+  #[test]
+  fn lapper_intersect_and_difference() {
+    let t = Utc::now();
+    let h = Duration::hours;
+    let at = |from: i64, to: i64| (t + h(from), t + h(to));
+    let lapper = |spans: &[(i64, i64)]| {
+      Lapper::new(
+        spans
+          .iter()
+          .map(|(from, to)| create_interval(t + h(*from), to - from))
+          .collect(),
+      )
+    };
+    // Alice: [0, 2) + [1, 4) overlap, [6, 8), [8, 9) touch
+    let alice = lapper(&[(0, 2), (1, 4), (6, 8), (8, 9)]);
+    // Bob: [3, 7), [10, 11)
+    let bob = lapper(&[(3, 7), (10, 11)]);
+
+    assert_eq!(alice.intersect(&bob), vec![at(3, 4), at(6, 7)]);
+    assert_eq!(bob.intersect(&alice), alice.intersect(&bob));
+    assert_eq!(alice.difference(&bob), vec![at(0, 3), at(7, 9)]);
+    assert_eq!(bob.difference(&alice), vec![at(4, 6), at(10, 11)]);
+
+    let empty = Lapper::new(std::collections::BTreeSet::new());
+    assert!(alice.intersect(&empty).is_empty());
+    assert_eq!(alice.difference(&empty), vec![at(0, 4), at(6, 9)]);
+    assert!(empty.difference(&alice).is_empty());
+    assert!(alice.difference(&alice).is_empty());
+  }
+
+  #[test]
+  fn lapper_find_containing_and_contained_in() {
+    let start = Utc::now();
+    let h = Duration::hours;
+    let day = create_interval(start, 24);
+    let morning = create_interval(start, 4);
+    let meeting = create_interval(start + h(2), 1);
+    let evening = create_interval(start + h(18), 4);
+    let lapper = Lapper::from_vec(vec![
+      day.clone(),
+      morning.clone(),
+      meeting.clone(),
+      evening.clone(),
+    ]);
+    let vals = |it: Vec<&Interval>| it.into_iter().map(|iv| iv.val).collect::<HashSet<_>>();
+
+    // Windows enclosing 02:00-03:00; bounds are inclusive
+    assert_eq!(
+      vals(lapper.find_containing(start + h(2), start + h(3)).collect()),
+      HashSet::from([day.val, morning.val, meeting.val])
+    );
+    assert_eq!(
+      vals(lapper.find_containing(start + h(3), start + h(5)).collect()),
+      HashSet::from([day.val])
+    );
+
+    // Intervals inside 00:00-04:00
+    assert_eq!(
+      vals(lapper.find_contained_in(start, start + h(4)).collect()),
+      HashSet::from([morning.val, meeting.val])
+    );
+    assert_eq!(
+      lapper
+        .find_contained_in(start + h(1), start + h(12))
+        .count(),
+      1
+    );
+
+    // Empty or inverted queries yield nothing
+    assert_eq!(
+      lapper.find_containing(start + h(2), start + h(2)).count(),
+      0
+    );
+    assert_eq!(
+      lapper.find_contained_in(start + h(5), start + h(2)).count(),
+      0
+    );
+  }
+
+  #[test]
+  fn lapper_overlaps_batch_matches_has_overlap() {
+    let start = Utc::now();
+    let h = Duration::hours;
+    let lapper = Lapper::from_vec(vec![
+      create_interval(start, 2),
+      create_interval(start + h(1), 3),
+      create_interval(start + h(6), 1),
+      create_interval(start + h(7), 2),
+    ]);
+
+    let mut ranges: Vec<(DateTime<Utc>, DateTime<Utc>)> = Vec::new();
+    for a in -1..11 {
+      for len in -1..4 {
+        ranges.push((start + h(a), start + h(a + len)));
+      }
+    }
+    let expected: Vec<bool> = ranges
+      .iter()
+      .map(|&(s, e)| lapper.has_overlap(s, e))
+      .collect();
+    assert_eq!(lapper.overlaps_batch(&ranges), expected);
+
+    // Unsorted input is answered in input order
+    ranges.reverse();
+    let expected: Vec<bool> = ranges
+      .iter()
+      .map(|&(s, e)| lapper.has_overlap(s, e))
+      .collect();
+    assert_eq!(lapper.overlaps_batch(&ranges), expected);
+
+    // Back-to-back ranges do not overlap
+    assert_eq!(
+      lapper.overlaps_batch(&[(start + h(4), start + h(6)), (start + h(9), start + h(10))]),
+      vec![false, false]
+    );
+    assert!(
+      Lapper::new(std::collections::BTreeSet::new())
+        .overlaps_batch(&ranges)
+        .iter()
+        .all(|b| !b)
+    );
+  }
+
+  #[test]
+  fn lapper_closed_mode_counts_touching_endpoints() {
+    use serde_test::{Configure, Token};
+
+    let start = Utc::now();
+    let h = Duration::hours;
+    let a = create_interval(start, 2);
+    let b = create_interval(start + h(2), 2);
+    let half_open = Lapper::from_vec(vec![a.clone(), b.clone()]);
+    let closed = half_open.clone().with_mode(IntervalMode::Closed);
+    assert_eq!(half_open.mode(), IntervalMode::HalfOpen);
+
+    // Touching endpoints only overlap in closed mode
+    assert_eq!(half_open.find(start + h(2), start + h(3)).count(), 1);
+    assert_eq!(closed.find(start + h(2), start + h(3)).count(), 2);
+    assert_eq!(closed.find_rev(start + h(2), start + h(3)).count(), 2);
+    assert!(!half_open.has_overlap(start + h(4), start + h(5)));
+    assert!(closed.has_overlap(start + h(4), start + h(5)));
+    assert_eq!(half_open.point_query(start + h(2)).count(), 1);
+    assert_eq!(closed.point_query(start + h(2)).count(), 2);
+
+    // Zero-length queries are points in closed mode; inverted ones are empty
+    // in both modes
+    assert!(!half_open.has_overlap(start + h(1), start + h(1)));
+    assert!(closed.has_overlap(start + h(1), start + h(1)));
+    assert!(!closed.has_overlap(start + h(3), start + h(1)));
+    assert_eq!(closed.count_overlaps(start + h(2), start + h(2)), 2);
+    assert_eq!(
+      closed.overlaps_batch(&[(start + h(4), start + h(4)), (start - h(1), start)]),
+      vec![true, true]
+    );
+    assert_eq!(
+      half_open.overlaps_batch(&[(start + h(4), start + h(4)), (start - h(1), start)]),
+      vec![false, false]
+    );
+
+    // The mode survives serde; the default is omitted
+    let json = serde_json::to_string(&closed).unwrap();
+    let back: Lapper = serde_json::from_str(&json).unwrap();
+    assert_eq!(back.mode(), IntervalMode::Closed);
+    assert!(back.has_overlap(start + h(4), start + h(5)));
+    assert!(!serde_json::to_string(&half_open).unwrap().contains("mode"));
+
+    let mut single = std::collections::BTreeSet::new();
+    single.insert(Interval {
+      start: DateTime::parse_from_rfc3339("2025-01-01T00:00:00Z")
+        .unwrap()
+        .with_timezone(&Utc),
+      stop: DateTime::parse_from_rfc3339("2025-01-01T01:00:00Z")
+        .unwrap()
+        .with_timezone(&Utc),
+      val: Uuid::nil(),
+    });
+    let tokens = vec![
+      Token::Struct {
+        name: "Lapper",
+        len: 2,
+      },
+      Token::Str("intervals"),
+      Token::Seq { len: Some(1) },
+      Token::Struct {
+        name: "Interval",
+        len: 3,
+      },
+      Token::Str("start"),
+      Token::Str("2025-01-01T00:00:00Z"),
+      Token::Str("stop"),
+      Token::Str("2025-01-01T01:00:00Z"),
+      Token::Str("val"),
+      Token::Str("00000000-0000-0000-0000-000000000000"),
+      Token::StructEnd,
+      Token::SeqEnd,
+      Token::Str("mode"),
+      Token::UnitVariant {
+        name: "IntervalMode",
+        variant: "Closed",
+      },
+      Token::StructEnd,
+    ];
+    serde_test::assert_ser_tokens(
+      &Lapper::new(single)
+        .with_mode(IntervalMode::Closed)
+        .readable(),
+      &tokens,
+    );
+  }
+
+  #[test]
+  fn update_schedule_preserves_id_and_relations() {
+    let mut mgr = ScheduleManager::new();
+    let start = Utc::now();
+    let h = Duration::hours;
+
+    let parent = mgr
+      .create_schedule(
+        Schedule::new(start, start + h(4), 1, true, "parent".into()),
+        HashSet::new(),
+      )
+      .unwrap();
+    let child = mgr
+      .create_schedule(
+        Schedule::new(start + h(1), start + h(2), 2, false, "child".into()),
+        HashSet::from([parent]),
+      )
+      .unwrap();
+
+    // Moving within the parent and renaming keeps the id and relations
+    mgr
+      .update_schedule(
+        child,
+        Schedule::new(start + h(2), start + h(3), 3, false, "moved".into()),
+      )
+      .unwrap();
+    let updated = mgr.get_schedule(child).unwrap();
+    assert_eq!(updated.name, "moved");
+    assert_eq!(updated.start, start + h(2));
+    assert!(mgr.parent_relations()[&child].contains(&parent));
+    assert_eq!(
+      mgr
+        .query_schedule(QueryOptions::builder().level(3u32).build())
+        .len(),
+      1
+    );
+    assert!(
+      mgr
+        .query_schedule(QueryOptions::builder().level(2u32).build())
+        .is_empty()
+    );
+
+    // Exclusive parent may be resized without conflicting with itself or
+    // its child, but not so far that the child falls outside.
+    mgr
+      .update_schedule(
+        parent,
+        Schedule::new(start, start + h(5), 1, true, "parent".into()),
+      )
+      .unwrap();
+    let res = mgr.update_schedule(
+      parent,
+      Schedule::new(start, start + h(2), 1, true, "parent".into()),
+    );
+    assert_eq!(res, Err(ScheduleError::TimeRangeExceedsParent));
+
+    // Leaving the parent's range is rejected
+    let res = mgr.update_schedule(
+      child,
+      Schedule::new(start + h(4), start + h(6), 3, false, "out".into()),
+    );
+    assert_eq!(res, Err(ScheduleError::TimeRangeExceedsParent));
+
+    assert_eq!(
+      mgr.update_schedule(
+        Uuid::now_v7(),
+        Schedule::new(start, start + h(1), 1, false, "x".into())
+      ),
+      Err(ScheduleError::ScheduleNotFound)
+    );
+  }
+
+  #[test]
+  fn update_rejects_shrinking_parent_past_child() {
+    let mut mgr = ScheduleManager::new();
+    let start = Utc::now();
+    let h = Duration::hours;
+    let parent_schedule = Schedule::new(start, start + h(4), 1, false, "parent".into());
+    let parent = mgr
+      .create_schedule(parent_schedule.clone(), HashSet::new())
+      .unwrap();
+    let child = mgr
+      .create_schedule(
+        Schedule::new(start + h(1), start + h(3), 2, false, "child".into()),
+        HashSet::from([parent]),
+      )
+      .unwrap();
+
+    // Cutting off either end of the child is rejected
+    for (from, to) in [(0, 2), (2, 4)] {
+      assert_eq!(
+        mgr.update_schedule(
+          parent,
+          Schedule::new(start + h(from), start + h(to), 1, false, "short".into()),
+        ),
+        Err(ScheduleError::TimeRangeExceedsParent)
+      );
+      assert_eq!(mgr.get_schedule(parent), Some(&parent_schedule));
+      assert_eq!(
+        mgr
+          .query_schedule(QueryOptions::builder().name("short").build())
+          .len(),
+        0
+      );
+    }
+    assert_eq!(mgr.child_relations()[&parent], HashSet::from([child]));
+
+    // Shrinking to exactly the child's range is fine
+    mgr
+      .update_schedule(
+        parent,
+        Schedule::new(start + h(1), start + h(3), 1, false, "parent".into()),
+      )
+      .unwrap();
+  }
+
+  #[test]
+  fn descendants_and_ancestors_walk_transitively() {
+    let mut mgr = ScheduleManager::new();
+    let start = Utc::now();
+    let h = Duration::hours;
+
+    let root = mgr
+      .create_schedule(
+        Schedule::new(start, start + h(8), 1, false, "root".into()),
+        HashSet::new(),
+      )
+      .unwrap();
+    let left = mgr
+      .create_schedule(
+        Schedule::new(start, start + h(4), 2, false, "left".into()),
+        HashSet::from([root]),
+      )
+      .unwrap();
+    let right = mgr
+      .create_schedule(
+        Schedule::new(start, start + h(4), 2, false, "right".into()),
+        HashSet::from([root]),
+      )
+      .unwrap();
+    // Diamond: leaf has two parents but must be reported once
+    let leaf = mgr
+      .create_schedule(
+        Schedule::new(start, start + h(1), 3, false, "leaf".into()),
+        HashSet::from([left, right]),
+      )
+      .unwrap();
+
+    let desc: HashSet<ScheduleId> = mgr.descendants(root).into_iter().collect();
+    assert_eq!(desc, HashSet::from([left, right, leaf]));
+    assert_eq!(mgr.descendants(root).len(), 3);
+
+    let anc: HashSet<ScheduleId> = mgr.ancestors(leaf).into_iter().collect();
+    assert_eq!(anc, HashSet::from([left, right, root]));
+
+    assert!(mgr.descendants(leaf).is_empty());
+    assert!(mgr.ancestors(Uuid::now_v7()).is_empty());
+  }
+
+  #[test]
+  fn add_parents_rejects_cycles() {
+    let mut mgr = ScheduleManager::new();
+    let start = Utc::now();
+    let h = Duration::hours;
+
+    let root = mgr
+      .create_schedule(
+        Schedule::new(start, start + h(4), 1, false, "root".into()),
+        HashSet::new(),
+      )
+      .unwrap();
+    let child = mgr
+      .create_schedule(
+        Schedule::new(start, start + h(2), 2, false, "child".into()),
+        HashSet::from([root]),
+      )
+      .unwrap();
+
+    assert_eq!(
+      mgr.add_parents(root, HashSet::from([child])),
+      Err(ScheduleError::CycleDetected)
+    );
+    assert_eq!(
+      mgr.add_parents(child, HashSet::from([child])),
+      Err(ScheduleError::CycleDetected)
+    );
+    assert!(mgr.parent_relations()[&root].is_empty());
+  }
+
+  #[test]
+  fn remove_parents_detaches_without_cascade() {
+    let mut mgr = ScheduleManager::new();
+    let start = Utc::now();
+    let h = Duration::hours;
+
+    let p1 = mgr
+      .create_schedule(
+        Schedule::new(start, start + h(4), 1, false, "p1".into()),
+        HashSet::new(),
+      )
+      .unwrap();
+    let p2 = mgr
+      .create_schedule(
+        Schedule::new(start, start + h(4), 1, false, "p2".into()),
+        HashSet::new(),
+      )
+      .unwrap();
+    let child = mgr
+      .create_schedule(
+        Schedule::new(start, start + h(1), 2, false, "child".into()),
+        HashSet::from([p1]),
+      )
+      .unwrap();
+
+    // p2 was never linked and is silently ignored
+    mgr.remove_parents(child, HashSet::from([p1, p2])).unwrap();
+    assert!(mgr.parent_relations()[&child].is_empty());
+    assert!(!mgr.child_relations()[&p1].contains(&child));
+
+    // Deleting the former parent no longer touches the child
+    let removed = mgr.delete_schedule(p1).unwrap();
+    assert_eq!(removed, HashSet::from([p1]));
+    assert!(mgr.get_schedule(child).is_some());
+
+    assert_eq!(
+      mgr.remove_parents(Uuid::now_v7(), HashSet::new()),
+      Err(ScheduleError::ScheduleNotFound)
+    );
+  }
+
+  #[test]
+  fn delete_schedule_reparent_rehomes_children() {
+    let mut mgr = ScheduleManager::new();
+    let start = Utc::now();
+    let h = Duration::hours;
+
+    let root = mgr
+      .create_schedule(
+        Schedule::new(start, start + h(8), 1, false, "root".into()),
+        HashSet::new(),
+      )
+      .unwrap();
+    let mid = mgr
+      .create_schedule(
+        Schedule::new(start, start + h(4), 2, false, "mid".into()),
+        HashSet::from([root]),
+      )
+      .unwrap();
+    let leaf = mgr
+      .create_schedule(
+        Schedule::new(start, start + h(1), 3, false, "leaf".into()),
+        HashSet::from([mid]),
+      )
+      .unwrap();
+
+    let removed = mgr.delete_schedule_reparent(mid).unwrap();
+    assert_eq!(removed, HashSet::from([mid]));
+    assert!(mgr.get_schedule(leaf).is_some());
+    assert_eq!(mgr.parent_relations()[&leaf], HashSet::from([root]));
+    assert!(mgr.child_relations()[&root].contains(&leaf));
+    assert!(!mgr.child_relations()[&root].contains(&mid));
+
+    // Deleting the root now cascades to the re-homed leaf
+    let removed = mgr.delete_schedule(root).unwrap();
+    assert_eq!(removed, HashSet::from([root, leaf]));
+  }
+
+  #[test]
+  fn move_schedule_shifts_subtree_and_rolls_back() {
+    let mut mgr = ScheduleManager::new();
+    let start = Utc::now();
+    let h = Duration::hours;
+
+    let parent = mgr
+      .create_schedule(
+        Schedule::new(start, start + h(2), 1, true, "parent".into()),
+        HashSet::new(),
+      )
+      .unwrap();
+    let child = mgr
+      .create_schedule(
+        Schedule::new(start, start + h(1), 2, false, "child".into()),
+        HashSet::from([parent]),
+      )
+      .unwrap();
+    let blocker = mgr
+      .create_schedule(
+        Schedule::new(start + h(5), start + h(6), 2, false, "blocker".into()),
+        HashSet::new(),
+      )
+      .unwrap();
+
+    // Overlapping its own old position is fine
+    mgr.move_schedule(parent, h(1)).unwrap();
+    assert_eq!(mgr.get_schedule(parent).unwrap().start, start + h(1));
+    assert_eq!(mgr.get_schedule(child).unwrap().start, start + h(1));
+    assert!(
+      mgr
+        .query_schedule(
+          QueryOptions::builder()
+            .start(start)
+            .stop(start + h(1))
+            .build()
+        )
+        .is_empty()
+    );
+
+    // Moving onto the blocker fails and leaves everything in place
+    assert_eq!(
+      mgr.move_schedule(parent, h(4)),
+      Err(ScheduleError::TimeRangeOverlaps)
+    );
+    assert_eq!(mgr.get_schedule(parent).unwrap().start, start + h(1));
+    assert_eq!(mgr.get_schedule(child).unwrap().start, start + h(1));
+    assert_eq!(mgr.get_schedule(blocker).unwrap().start, start + h(5));
+
+    // A child cannot be dragged out of its parent on its own
+    assert_eq!(
+      mgr.move_schedule(child, h(3)),
+      Err(ScheduleError::TimeRangeExceedsParent)
+    );
+  }
+
+  #[test]
+  fn move_schedule_rejects_out_of_range_delta() {
+    let mut mgr = ScheduleManager::new();
+    let start = Utc::now();
+    let h = Duration::hours;
+    let parent = mgr
+      .create_schedule(
+        Schedule::new(start, start + h(4), 1, true, "parent".into()),
+        HashSet::new(),
+      )
+      .unwrap();
+    let child = mgr
+      .create_schedule(
+        Schedule::new(start, start + h(1), 2, false, "child".into()),
+        HashSet::from([parent]),
+      )
+      .unwrap();
+
+    for delta in [
+      Duration::days(365 * 300_000),
+      -Duration::days(365 * 300_000),
+    ] {
+      assert_eq!(
+        mgr.move_schedule(parent, delta),
+        Err(ScheduleError::TimeOutOfRange)
+      );
+    }
+
+    // Nothing was taken out of the indices
+    let at =
+      |t| -> HashSet<ScheduleId> { mgr.schedules_at(t).into_iter().map(|(id, _)| id).collect() };
+    assert_eq!(at(start), HashSet::from([parent, child]));
+    let probe = Schedule::new(start, start + h(1), 1, true, "probe".into());
+    assert!(mgr.conflicts_for(&probe, &HashSet::new()).contains(&parent));
+  }
+
+  #[test]
+  fn manager_iter_len_and_is_empty() {
+    let mut mgr = ScheduleManager::new();
+    assert!(mgr.is_empty());
+    assert_eq!(mgr.iter().count(), 0);
+
+    let start = Utc::now();
+    let a = mgr
+      .create_schedule(
+        Schedule::new(start, start + Duration::hours(1), 1, false, "a".into()),
+        HashSet::new(),
+      )
+      .unwrap();
+    let b = mgr
+      .create_schedule(
+        Schedule::new(start, start + Duration::hours(1), 1, false, "b".into()),
+        HashSet::new(),
+      )
+      .unwrap();
+
+    assert_eq!(mgr.len(), 2);
+    assert!(!mgr.is_empty());
+    let ids: HashSet<ScheduleId> = mgr.iter().map(|(id, _)| *id).collect();
+    assert_eq!(ids, HashSet::from([a, b]));
+  }
+
+  #[test]
+  fn query_schedule_limit_and_offset() {
+    let mut mgr = ScheduleManager::new();
+    let start = Utc::now();
+    for i in 0..5 {
+      let s = start + Duration::hours(i);
+      mgr
+        .create_schedule(
+          Schedule::new(s, s + Duration::hours(1), 1, false, format!("s{i}")),
+          HashSet::new(),
+        )
+        .unwrap();
+    }
+
+    let all = mgr.query_schedule(QueryOptions::default());
+    assert_eq!(all.len(), 5);
+    assert_eq!(
+      mgr
+        .query_schedule(QueryOptions::builder().limit(2usize).build())
+        .len(),
+      2
+    );
+    assert_eq!(
+      mgr
+        .query_schedule(QueryOptions::builder().offset(3usize).build())
+        .len(),
+      2
+    );
+    assert!(
+      mgr
+        .query_schedule(QueryOptions::builder().offset(10usize).build())
+        .is_empty()
+    );
+    assert!(
+      mgr
+        .query_schedule(QueryOptions::builder().limit(0usize).build())
+        .is_empty()
+    );
+  }
+
+  #[test]
+  fn query_schedule_name_uses_token_index() {
+    let mut mgr = ScheduleManager::new();
+    let start = Utc::now();
+    let end = start + Duration::hours(1);
+    let mk = |name: &str| Schedule::new(start, end, 1, false, name.into());
+
+    let standup = mgr
+      .create_schedule(mk("Team standup"), HashSet::new())
+      .unwrap();
+    let review = mgr
+      .create_schedule(mk("Code review: parser"), HashSet::new())
+      .unwrap();
+    mgr.create_schedule(mk("Lunch"), HashSet::new()).unwrap();
+
+    let ids = |name: &str, mgr: &ScheduleManager| -> HashSet<ScheduleId> {
+      mgr
+        .query_schedule(QueryOptions::builder().name(name).build())
+        .into_iter()
+        .map(|(id, _)| id)
+        .collect()
+    };
+
+    // Substrings inside tokens and across token boundaries still match
+    assert_eq!(ids("stand", &mgr), HashSet::from([standup]));
+    assert_eq!(ids("m stand", &mgr), HashSet::from([standup]));
+    assert_eq!(ids("review: par", &mgr), HashSet::from([review]));
+    // Matching stays case-sensitive
+    assert!(ids("team", &mgr).is_empty());
+
+    // The index follows renames and deletes
+    mgr
+      .update_schedule(standup, Schedule::new(start, end, 1, false, "Retro".into()))
+      .unwrap();
+    assert!(ids("stand", &mgr).is_empty());
+    assert_eq!(ids("Retro", &mgr), HashSet::from([standup]));
+    mgr.delete_schedule(review).unwrap();
+    assert!(ids("review", &mgr).is_empty());
+  }
+
+  #[test]
+  fn name_index_never_narrows_below_the_filter() {
+    let mut mgr = ScheduleManager::new();
+    let start = Utc::now();
+    let end = start + Duration::hours(1);
+    let mk = |name: &str| Schedule::new(start, end, 1, false, name.into());
+
+    let road = mgr.create_schedule(mk("ΟΔΟΣΑ"), HashSet::new()).unwrap();
+    let pg = mgr
+      .create_schedule(mk("PostgreSQL tuning"), HashSet::new())
+      .unwrap();
+
+    let ids = |name: &str, mode: NameMatch| -> HashSet<ScheduleId> {
+      mgr
+        .query_schedule(QueryOptions::builder().name(name).name_match(mode).build())
+        .into_iter()
+        .map(|(id, _)| id)
+        .collect()
+    };
+
+    // A word-final 'Σ' in the filter is medial in the name
+    assert_eq!(ids("ΟΔΟΣ", NameMatch::Contains), HashSet::from([road]));
+    assert_eq!(
+      ids("οδοσ", NameMatch::ContainsCaseInsensitive),
+      HashSet::from([road])
+    );
+    // Substrings in the middle of a token are found too
+    assert_eq!(ids("greSQ", NameMatch::Contains), HashSet::from([pg]));
+    assert_eq!(ids("QL tun", NameMatch::Contains), HashSet::from([pg]));
+  }
+
+  #[test]
+  fn query_schedule_name_match_modes() {
+    let mut mgr = ScheduleManager::new();
+    let start = Utc::now();
+    let end = start + Duration::hours(1);
+    let mk = |name: &str| Schedule::new(start, end, 1, false, name.into());
+
+    let pg = mgr
+      .create_schedule(mk("PostgreSQL tuning"), HashSet::new())
+      .unwrap();
+    let sql = mgr
+      .create_schedule(mk("SQL basics"), HashSet::new())
+      .unwrap();
+
+    let ids = |name: &str, mode: NameMatch| -> HashSet<ScheduleId> {
+      mgr
+        .query_schedule(QueryOptions::builder().name(name).name_match(mode).build())
+        .into_iter()
+        .map(|(id, _)| id)
+        .collect()
+    };
+
+    assert_eq!(ids("SQL", NameMatch::Contains), HashSet::from([pg, sql]));
+    assert!(ids("sql", NameMatch::Contains).is_empty());
+    assert_eq!(
+      ids("sql", NameMatch::ContainsCaseInsensitive),
+      HashSet::from([pg, sql])
+    );
+    assert_eq!(ids("SQL", NameMatch::WholeWord), HashSet::from([sql]));
+    assert_eq!(ids("SQL basics", NameMatch::Exact), HashSet::from([sql]));
+    assert!(ids("SQL", NameMatch::Exact).is_empty());
+  }
+
+  #[test]
+  fn query_schedule_by_relations() {
+    let mut mgr = ScheduleManager::new();
+    let start = Utc::now();
+    let h = Duration::hours;
+
+    let root = mgr
+      .create_schedule(
+        Schedule::new(start, start + h(4), 1, false, "root".into()),
+        HashSet::new(),
+      )
+      .unwrap();
+    let other_root = mgr
+      .create_schedule(
+        Schedule::new(start, start + h(4), 1, false, "other".into()),
+        HashSet::new(),
+      )
+      .unwrap();
+    let a = mgr
+      .create_schedule(
+        Schedule::new(start, start + h(1), 2, false, "a".into()),
+        HashSet::from([root]),
+      )
+      .unwrap();
+    let b = mgr
+      .create_schedule(
+        Schedule::new(start + h(2), start + h(3), 2, false, "b".into()),
+        HashSet::from([root, other_root]),
+      )
+      .unwrap();
+
+    let ids = |opts: QueryOptions| -> HashSet<ScheduleId> {
+      mgr
+        .query_schedule(opts)
+        .into_iter()
+        .map(|(id, _)| id)
+        .collect()
+    };
+
+    assert_eq!(
+      ids(QueryOptions::builder().child_of(root).build()),
+      HashSet::from([a, b])
+    );
+    assert_eq!(
+      ids(QueryOptions::builder().parent_of(b).build()),
+      HashSet::from([root, other_root])
+    );
+    assert_eq!(
+      ids(QueryOptions::builder().roots_only(true).build()),
+      HashSet::from([root, other_root])
+    );
+    // Composes with time filters
+    assert_eq!(
+      ids(
+        QueryOptions::builder()
+          .child_of(root)
+          .start(start + h(2))
+          .build()
+      ),
+      HashSet::from([b])
+    );
+  }
+
+  #[test]
+  fn get_many_and_get_schedule_with_relations() {
+    let mut mgr = ScheduleManager::new();
+    let start = Utc::now();
+    let h = Duration::hours;
+
+    let parent = mgr
+      .create_schedule(
+        Schedule::new(start, start + h(2), 1, false, "parent".into()),
+        HashSet::new(),
+      )
+      .unwrap();
+    let child = mgr
+      .create_schedule(
+        Schedule::new(start, start + h(1), 2, false, "child".into()),
+        HashSet::from([parent]),
+      )
+      .unwrap();
+
+    let got = mgr.get_many(&[child, Uuid::now_v7(), parent]);
+    let ids: Vec<ScheduleId> = got.iter().map(|(id, _)| *id).collect();
+    assert_eq!(ids, vec![child, parent]);
+
+    let (schedule, parents, children) = mgr.get_schedule_with_relations(child).unwrap();
+    assert_eq!(schedule.name, "child");
+    assert_eq!(parents, vec![parent]);
+    assert!(children.is_empty());
+    assert!(mgr.get_schedule_with_relations(Uuid::now_v7()).is_none());
+  }
+
+  #[test]
+  fn schedules_at_uses_half_open_ranges() {
+    let mut mgr = ScheduleManager::new();
+    let start = Utc::now();
+    let h = Duration::hours;
+
+    let a = mgr
+      .create_schedule(
+        Schedule::new(start, start + h(2), 1, false, "a".into()),
+        HashSet::new(),
+      )
+      .unwrap();
+    let b = mgr
+      .create_schedule(
+        Schedule::new(start + h(1), start + h(3), 2, false, "b".into()),
+        HashSet::new(),
+      )
+      .unwrap();
+
+    let at =
+      |t| -> HashSet<ScheduleId> { mgr.schedules_at(t).into_iter().map(|(id, _)| id).collect() };
+    assert_eq!(at(start), HashSet::from([a]));
+    assert_eq!(at(start + h(1)), HashSet::from([a, b]));
+    // `a` ends exactly at 2h and no longer contains it
+    assert_eq!(at(start + h(2)), HashSet::from([b]));
+    assert!(at(start + h(3)).is_empty());
+  }
+
+  #[test]
+  fn lapper_point_query_excludes_stop() {
+    let start = Utc::now();
+    let (a, b) = (Uuid::now_v7(), Uuid::now_v7());
+    let lapper = Lapper::from_vec(vec![
+      create_interval_with_id(start, 1, a),
+      create_interval_with_id(start + Duration::hours(1), 1, b),
+    ]);
+
+    let at = |t| -> Vec<ScheduleId> { lapper.point_query(t).map(|iv| iv.val).collect() };
+    assert_eq!(at(start), vec![a]);
+    assert_eq!(at(start + Duration::hours(1)), vec![b]);
+    assert_eq!(
+      at(start + Duration::hours(1) - Duration::nanoseconds(1)),
+      vec![a]
+    );
+    assert!(at(start + Duration::hours(2)).is_empty());
+  }
+
+  #[test]
+  fn point_query_at_the_last_instant_does_not_overflow() {
+    let mut mgr = ScheduleManager::new();
+    let start = Utc::now();
+    let id = mgr
+      .create_schedule(
+        Schedule::open_ended(start, 0, false, "ongoing".into()),
+        HashSet::new(),
+      )
+      .unwrap();
+
+    // OPEN_END is the exclusive end, so nothing runs at that instant
+    assert!(mgr.schedules_at(OPEN_END).is_empty());
+    assert_eq!(mgr.schedules_at(start).len(), 1);
+
+    let closed = Lapper::from_vec(vec![Interval {
+      start,
+      stop: OPEN_END,
+      val: id,
+    }])
+    .with_mode(IntervalMode::Closed);
+    assert_eq!(closed.point_query(OPEN_END).count(), 1);
+  }
+
+  #[test]
+  fn create_recurring_keeps_open_end_and_rejects_overflow() {
+    let mut mgr = ScheduleManager::new();
+    let start = Utc::now();
+    let daily = Recurrence {
+      frequency: Frequency::Daily,
+      interval: 1,
+      end: RecurrenceEnd::Count(2),
+    };
+    let ids = mgr
+      .create_recurring(
+        Schedule::open_ended(start, 0, false, "ongoing".into()),
+        daily,
+        HashSet::new(),
+      )
+      .unwrap();
+    assert_eq!(ids.len(), 2);
+    let second = mgr.get_schedule(ids[1]).unwrap();
+    assert_eq!(second.start, start + Duration::days(1));
+    assert!(second.is_open_ended());
+
+    // The second occurrence starts in range but would end past it
+    let late = OPEN_END - Duration::hours(25);
+    let template = Schedule::new(late, late + Duration::days(1), 0, false, "late".into());
+    assert_eq!(daily.occurrences(template.start, template.end), None);
+    assert_eq!(
+      mgr.create_recurring(template, daily, HashSet::new()),
+      Err(ScheduleError::InvalidRecurrence)
+    );
+    assert_eq!(mgr.len(), 2);
+  }
+
+  #[test]
+  fn create_recurring_expands_and_rolls_back() {
+    let mut mgr = ScheduleManager::new();
+    let start = DateTime::parse_from_rfc3339("2025-01-31T09:00:00Z")
+      .unwrap()
+      .with_timezone(&Utc);
+    let template = Schedule::new(start, start + Duration::hours(1), 2, true, "class".into());
+
+    let weekly = Recurrence {
+      frequency: Frequency::Weekly,
+      interval: 1,
+      end: RecurrenceEnd::Count(3),
+    };
+    let ids = mgr
+      .create_recurring(template.clone(), weekly, HashSet::new())
+      .unwrap();
+    assert_eq!(ids.len(), 3);
+    assert_eq!(
+      mgr.get_schedule(ids[2]).unwrap().start,
+      start + Duration::weeks(2)
+    );
+
+    // Monthly clamps to the end of shorter months
+    let monthly = Recurrence {
+      frequency: Frequency::Monthly,
+      interval: 1,
+      end: RecurrenceEnd::Until(start + Duration::days(40)),
+    };
+    let spans = monthly
+      .occurrences(start, start + Duration::hours(1))
+      .unwrap();
+    assert_eq!(spans.len(), 2);
+    assert_eq!(spans[1].0.to_rfc3339(), "2025-02-28T09:00:00+00:00");
+
+    // Daily occurrences collide with the weekly exclusive class on day 7;
+    // the whole batch must be rolled back.
+    let daily = Recurrence {
+      frequency: Frequency::Daily,
+      interval: 1,
+      end: RecurrenceEnd::Count(10),
+    };
+    let shifted = Schedule::new(
+      start + Duration::days(1),
+      start + Duration::days(1) + Duration::hours(1),
+      2,
+      false,
+      "standup".into(),
+    );
+    assert_eq!(
+      mgr.create_recurring(shifted, daily, HashSet::new()),
+      Err(ScheduleError::TimeRangeOverlaps)
+    );
+    assert_eq!(mgr.len(), 3);
+
+    let zero = Recurrence {
+      interval: 0,
+      ..weekly
+    };
+    assert_eq!(
+      mgr.create_recurring(template, zero, HashSet::new()),
+      Err(ScheduleError::InvalidRecurrence)
+    );
+  }
+
+  #[test]
+  fn schedule_from_local_converts_to_utc() {
+    use chrono::{FixedOffset, NaiveDate};
+
+    let tz = FixedOffset::east_opt(8 * 3600).unwrap();
+    let day = NaiveDate::from_ymd_opt(2025, 3, 1).unwrap();
+    let schedule = Schedule::from_local(
+      &tz,
+      day.and_hms_opt(9, 0, 0).unwrap(),
+      day.and_hms_opt(10, 30, 0).unwrap(),
+      1,
+      false,
+      "lecture".into(),
+    )
+    .unwrap();
+
+    assert_eq!(schedule.start.to_rfc3339(), "2025-03-01T01:00:00+00:00");
+    assert_eq!(schedule.end - schedule.start, Duration::minutes(90));
+    assert_eq!(
+      schedule.local_start(&tz).naive_local(),
+      day.and_hms_opt(9, 0, 0).unwrap()
+    );
+    assert_eq!(
+      schedule.local_end(&tz).naive_local(),
+      day.and_hms_opt(10, 30, 0).unwrap()
+    );
+  }
+
+  #[test]
+  fn import_ics_parses_utc_events_and_reports_failures() {
+    let mut mgr = ScheduleManager::new();
+    let ics = "BEGIN:VCALENDAR\r\n\
+BEGIN:VEVENT\r\n\
+SUMMARY:Algebra\\, week 1\r\n\
+DTSTART:20250106T090000Z\r\n\
+DTEND:20250106T\r\n 103000Z\r\n\
+END:VEVENT\r\n\
+BEGIN:VEVENT\r\n\
+SUMMARY:Physics\r\n\
+DTSTART:20250107T090000Z\r\n\
+DTEND:20250107T100000Z\r\n\
+END:VEVENT\r\n\
+END:VCALENDAR\r\n";
+
+    let ids = mgr.import_ics(ics, 3).unwrap();
+    assert_eq!(ids.len(), 2);
+    let first = mgr.get_schedule(ids[0]).unwrap();
+    assert_eq!(first.name, "Algebra, week 1");
+    assert_eq!(first.end.to_rfc3339(), "2025-01-06T10:30:00+00:00");
+    assert_eq!(first.level, 3);
+    assert!(!first.exclusive);
+
+    let floating = "BEGIN:VEVENT\nDTSTART:20250108T090000\nDTEND:20250108T100000Z\nEND:VEVENT\n";
+    assert!(matches!(
+      mgr.import_ics(floating, 3),
+      Err(ScheduleError::InvalidIcs { event: 0, .. })
+    ));
+
+    // An exclusive block conflicts with the second event of the batch
+    let start = DateTime::parse_from_rfc3339("2025-01-08T08:00:00Z")
+      .unwrap()
+      .with_timezone(&Utc);
+    mgr
+      .create_schedule(
+        Schedule::new(start, start + Duration::hours(4), 1, true, "exam".into()),
+        HashSet::new(),
+      )
+      .unwrap();
+    let conflicting = "BEGIN:VEVENT\nSUMMARY:Early\nDTSTART:20250108T060000Z\nDTEND:20250108T070000Z\nEND:VEVENT\n\
+BEGIN:VEVENT\nSUMMARY:Clash\nDTSTART:20250108T090000Z\nDTEND:20250108T100000Z\nEND:VEVENT\n";
+    let err = mgr.import_ics(conflicting, 3).unwrap_err();
+    assert_eq!(
+      err,
+      ScheduleError::IcsEventRejected {
+        event: 1,
+        summary: "Clash".into(),
+        source: Box::new(ScheduleError::TimeRangeOverlaps),
+      }
+    );
+    // The first event of the failed batch was rolled back
+    assert_eq!(mgr.len(), 3);
+  }
+
+  #[test]
+  fn csv_export_import_round_trip() {
+    let mut mgr = ScheduleManager::new();
+    let start = DateTime::parse_from_rfc3339("2025-01-06T09:00:00Z")
+      .unwrap()
+      .with_timezone(&Utc);
+    let h = Duration::hours;
+
+    let parent = mgr
+      .create_schedule(
+        Schedule::new(start, start + h(4), 1, true, "Week \"1\", block A".into()),
+        HashSet::new(),
+      )
+      .unwrap();
+    let child = mgr
+      .create_schedule(
+        Schedule::new(start, start + h(1), 2, false, "line\nbreak".into()),
+        HashSet::from([parent]),
+      )
+      .unwrap();
+
+    let csv = mgr.export_csv(QueryOptions::default());
+    assert!(csv.starts_with("id,start,end,level,exclusive,name,parents\n"));
+
+    // Put the child row before its parent to exercise dependency ordering
+    let mut lines: Vec<&str> = csv.splitn(2, '\n').collect();
+    let body = lines.pop().unwrap();
+    let parent_row_end = body.find(&format!("\n{child}")).unwrap() + 1;
+    let reordered = format!(
+      "{}\n{}{}",
+      lines[0],
+      &body[parent_row_end..],
+      &body[..parent_row_end]
+    );
+
+    let mut imported = ScheduleManager::new();
+    let ids = imported.import_csv(&reordered).unwrap();
+    assert_eq!(ids, vec![parent, child]);
+    assert_eq!(
+      imported.get_schedule(parent).unwrap().name,
+      "Week \"1\", block A"
+    );
+    assert_eq!(imported.get_schedule(child).unwrap().name, "line\nbreak");
+    assert!(imported.get_schedule(parent).unwrap().exclusive);
+    assert_eq!(imported.parent_relations()[&child], HashSet::from([parent]));
+    assert_eq!(imported.export_csv(QueryOptions::default()), csv);
+
+    // Unknown parents are reported and nothing is left behind
+    let orphan = format!(
+      "id,start,end,level,exclusive,name,parents\n{},{},{},2,false,x,{}\n",
+      Uuid::now_v7(),
+      start.to_rfc3339(),
+      (start + h(1)).to_rfc3339(),
+      Uuid::now_v7()
+    );
+    let mut fresh = ScheduleManager::new();
+    assert_eq!(
+      fresh.import_csv(&orphan),
+      Err(ScheduleError::ParentNotFound)
+    );
+    assert!(fresh.is_empty());
+    assert!(matches!(
+      fresh.import_csv("nope\n"),
+      Err(ScheduleError::InvalidCsv { line: 1, .. })
+    ));
+  }
+
+  #[test]
+  fn snapshot_round_trip_rebuilds_indices() {
+    let mut mgr = ScheduleManager::new();
+    let start = Utc::now();
+    let h = Duration::hours;
+
+    let parent = mgr
+      .create_schedule(
+        Schedule::new(start, start + h(4), 1, true, "parent".into()),
+        HashSet::new(),
+      )
+      .unwrap();
+    let child = mgr
+      .create_schedule(
+        Schedule::new(start, start + h(1), 2, false, "child".into()),
+        HashSet::from([parent]),
+      )
+      .unwrap();
+
+    let snapshot = mgr.to_snapshot();
+    assert_eq!(snapshot.version, SNAPSHOT_VERSION);
+    let mut restored = ScheduleManager::from_snapshot(snapshot.clone()).unwrap();
+    assert_eq!(restored.len(), 2);
+    assert_eq!(restored.child_relations()[&parent], HashSet::from([child]));
+    assert_eq!(
+      restored
+        .query_schedule(QueryOptions::builder().name("chi").build())
+        .len(),
+      1
+    );
+    // The exclusive index was rebuilt, so overlaps are still rejected
+    assert_eq!(
+      restored.create_schedule(
+        Schedule::new(start + h(2), start + h(3), 2, false, "clash".into()),
+        HashSet::new()
+      ),
+      Err(ScheduleError::TimeRangeOverlaps)
+    );
+
+    let mut broken = snapshot.clone();
+    broken.schedules.remove(&parent);
+    assert!(ScheduleManager::from_snapshot(broken).is_err());
+    let future = Snapshot {
+      version: SNAPSHOT_VERSION + 1,
+      ..snapshot
+    };
+    assert_eq!(
+      ScheduleManager::from_snapshot(future).err(),
+      Some(ScheduleError::UnsupportedSnapshotVersion(
+        SNAPSHOT_VERSION + 1
+      ))
+    );
+  }
+
+  #[test]
+  fn schedule_manager_serde_json_round_trip() {
+    let mut mgr = ScheduleManager::new();
+    let start = Utc::now();
+    let h = Duration::hours;
+
+    let parent = mgr
+      .create_schedule(
+        Schedule::new(start, start + h(4), 1, true, "parent".into()),
+        HashSet::new(),
+      )
+      .unwrap();
+    let child = mgr
+      .create_schedule(
+        Schedule::new(start, start + h(1), 2, false, "child".into()),
+        HashSet::from([parent]),
+      )
+      .unwrap();
+
+    let json = serde_json::to_string(&mgr).unwrap();
+    let mut restored: ScheduleManager = serde_json::from_str(&json).unwrap();
+
+    assert_eq!(restored.len(), 2);
+    assert_eq!(restored.parent_relations()[&child], HashSet::from([parent]));
+    assert_eq!(restored.child_relations()[&parent], HashSet::from([child]));
+
+    let ids = |m: &ScheduleManager| -> HashSet<ScheduleId> {
+      m.schedules_at(start + Duration::minutes(30))
+        .into_iter()
+        .map(|(id, _)| id)
+        .collect()
+    };
+    assert_eq!(ids(&restored), ids(&mgr));
+
+    // Overlap checks behave identically on the rebuilt indices
+    let clash = Schedule::new(start + h(2), start + h(3), 2, false, "clash".into());
+    assert_eq!(
+      restored.create_schedule(clash.clone(), HashSet::new()),
+      mgr.create_schedule(clash, HashSet::new())
+    );
+    assert!(
+      restored
+        .create_schedule(
+          Schedule::new(start + h(5), start + h(6), 2, false, "free".into()),
+          HashSet::new()
+        )
+        .is_ok()
+    );
+  }
+
+  #[test]
+  fn observer_receives_lifecycle_events() {
+    use std::sync::{Arc, Mutex};
+
+    let mut mgr = ScheduleManager::new();
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let sink = events.clone();
+    mgr.set_observer(Arc::new(move |e: &ScheduleEvent| {
+      sink.lock().unwrap().push(*e)
+    }));
+
+    let start = Utc::now();
+    let h = Duration::hours;
+    let parent = mgr
+      .create_schedule(
+        Schedule::new(start, start + h(4), 1, false, "parent".into()),
+        HashSet::new(),
+      )
+      .unwrap();
+    let child = mgr
+      .create_schedule(
+        Schedule::new(start, start + h(1), 2, false, "child".into()),
+        HashSet::from([parent]),
+      )
+      .unwrap();
+    mgr
+      .update_schedule(
+        child,
+        Schedule::new(start, start + h(2), 2, false, "child".into()),
+      )
+      .unwrap();
+    mgr.delete_schedule(parent).unwrap();
+
+    let got = events.lock().unwrap().clone();
+    assert_eq!(
+      &got[..3],
+      &[
+        ScheduleEvent::Created(parent),
+        ScheduleEvent::Created(child),
+        ScheduleEvent::Updated(child),
+      ]
+    );
+    // Cascade fires once per removed id
+    let deleted: HashSet<ScheduleEvent> = got[3..].iter().copied().collect();
+    assert_eq!(got.len(), 5);
+    assert_eq!(
+      deleted,
+      HashSet::from([
+        ScheduleEvent::Deleted(parent),
+        ScheduleEvent::Deleted(child)
+      ])
+    );
+  }
+
+  #[test]
+  fn failed_batch_rolls_back_with_compensating_events() {
+    use std::sync::{Arc, Mutex};
+
+    let mut mgr = ScheduleManager::new();
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let sink = events.clone();
+    mgr.set_observer(Arc::new(move |e: &ScheduleEvent| {
+      sink.lock().unwrap().push(*e)
+    }));
+
+    let start = Utc::now();
+    let h = Duration::hours;
+    let parent = Uuid::now_v7();
+    let res = mgr.create_schedules(vec![
+      (
+        Some(parent),
+        Schedule::new(start, start + h(4), 1, true, "parent".into()),
+        HashSet::new(),
+      ),
+      (
+        None,
+        Schedule::new(start, start + h(1), 2, false, "child".into()),
+        HashSet::from([parent]),
+      ),
+      (
+        None,
+        Schedule::new(start, start + h(1), 1, true, "clash".into()),
+        HashSet::new(),
+      ),
+    ]);
+    assert!(res.is_err());
+    assert!(mgr.is_empty());
+    assert!(mgr.parent_relations().is_empty());
+    assert!(mgr.child_relations().is_empty());
+
+    // Each announced creation is withdrawn, children first
+    let got = events.lock().unwrap().clone();
+    assert_eq!(got.len(), 4);
+    let child = match got[1] {
+      ScheduleEvent::Created(id) => id,
+      other => panic!("unexpected event {other:?}"),
+    };
+    assert_eq!(
+      got,
+      vec![
+        ScheduleEvent::Created(parent),
+        ScheduleEvent::Created(child),
+        ScheduleEvent::Deleted(child),
+        ScheduleEvent::Deleted(parent),
+      ]
+    );
+
+    // History is opt-in
+    assert!(!mgr.can_undo());
+    mgr
+      .create_schedule(
+        Schedule::new(start, start + h(1), 1, false, "kept".into()),
+        HashSet::new(),
+      )
+      .unwrap();
+    assert!(!mgr.can_undo());
+  }
+
+  #[test]
+  fn conflicts_for_reports_all_blockers() {
+    let mut mgr = ScheduleManager::new();
+    let start = Utc::now();
+    let h = Duration::hours;
+
+    let a = mgr
+      .create_schedule(
+        Schedule::new(start, start + h(1), 1, true, "a".into()),
+        HashSet::new(),
+      )
+      .unwrap();
+    let b = mgr
+      .create_schedule(
+        Schedule::new(start + h(2), start + h(3), 2, true, "b".into()),
+        HashSet::new(),
+      )
+      .unwrap();
+    let candidate = Schedule::new(start, start + h(3), 2, false, "wide".into());
+    let mut expected = vec![a, b];
+    expected.sort();
+    assert_eq!(mgr.conflicts_for(&candidate, &HashSet::new()), expected);
+    // Parents are exempt
+    assert_eq!(mgr.conflicts_for(&candidate, &HashSet::from([a])), vec![b]);
+
+    let free = Schedule::new(start + h(5), start + h(6), 2, false, "free".into());
+    assert!(mgr.conflicts_for(&free, &HashSet::new()).is_empty());
+  }
+
+  #[test]
+  fn try_create_schedule_reports_conflicts() {
+    let mut mgr = ScheduleManager::new();
+    let start = Utc::now();
+    let h = Duration::hours;
+
+    let blocker = mgr
+      .try_create_schedule(
+        Schedule::new(start, start + h(2), 1, true, "exam".into()),
+        HashSet::new(),
+      )
+      .unwrap();
+
+    let err = mgr
+      .try_create_schedule(
+        Schedule::new(start + h(1), start + h(3), 2, false, "x".into()),
+        HashSet::new(),
+      )
+      .unwrap_err();
+    assert_eq!(err.error, ScheduleError::TimeRangeOverlaps);
+    assert_eq!(err.conflicts, vec![blocker]);
+
+    let err = mgr
+      .try_create_schedule(
+        Schedule::new(start + h(1), start + h(3), 2, false, "x".into()),
+        HashSet::from([Uuid::now_v7()]),
+      )
+      .unwrap_err();
+    assert_eq!(err.error, ScheduleError::ParentNotFound);
+    assert!(err.conflicts.is_empty());
+  }
+
+  #[test]
+  fn find_free_slot_fit_strategies() {
+    let mut mgr = ScheduleManager::new();
+    let start = Utc::now();
+    let h = Duration::hours;
+    for (from, to) in [(0, 1), (3, 4), (5, 9)] {
+      mgr
+        .create_schedule(
+          Schedule::new(start + h(from), start + h(to), 1, true, "busy".into()),
+          HashSet::new(),
+        )
+        .unwrap();
+    }
+    // Gaps: [1, 3), [4, 5), [9, 12)
+    let window = (start, start + h(12));
+    let slot = |hours, fit| mgr.find_free_slot(1, h(hours), window, false, fit);
+    let at = |from, to| Some((start + h(from), start + h(to)));
+
+    assert_eq!(slot(1, FitStrategy::Earliest), at(1, 2));
+    assert_eq!(slot(1, FitStrategy::BestFit), at(4, 5));
+    assert_eq!(slot(1, FitStrategy::LatestWithinWindow), at(11, 12));
+
+    assert_eq!(slot(2, FitStrategy::Earliest), at(1, 3));
+    assert_eq!(slot(2, FitStrategy::BestFit), at(1, 3));
+    assert_eq!(slot(2, FitStrategy::LatestWithinWindow), at(10, 12));
+
+    assert_eq!(slot(3, FitStrategy::BestFit), at(9, 12));
+    assert_eq!(slot(4, FitStrategy::LatestWithinWindow), None);
+  }
+
+  #[test]
+  fn find_free_slot_skips_blocking_schedules() {
+    let mut mgr = ScheduleManager::new();
+    let start = Utc::now();
+    let h = Duration::hours;
+
+    mgr
+      .create_schedule(
+        Schedule::new(start, start + h(2), 1, true, "lecture".into()),
+        HashSet::new(),
+      )
+      .unwrap();
+    mgr
+      .create_schedule(
+        Schedule::new(start + h(3), start + h(4), 2, false, "gym".into()),
+        HashSet::new(),
+      )
+      .unwrap();
+
+    let window = (start, start + h(8));
+    // Non-exclusive level-2 work only avoids the exclusive lecture
+    assert_eq!(
+      mgr.find_free_slot(2, h(1), window, false, FitStrategy::Earliest),
+      Some((start + h(2), start + h(3)))
+    );
+    // An exclusive slot must also avoid the gym and needs 2 free hours
+    assert_eq!(
+      mgr.find_free_slot(2, h(2), window, true, FitStrategy::Earliest),
+      Some((start + h(4), start + h(6)))
+    );
+    // An exclusive level-1 slot also has to avoid the level-2 gym
+    assert_eq!(
+      mgr.find_free_slot(1, h(1), window, true, FitStrategy::Earliest),
+      Some((start + h(2), start + h(3)))
+    );
+    assert_eq!(
+      mgr.find_free_slot(2, h(9), window, false, FitStrategy::Earliest),
+      None
+    );
+  }
+
+  #[test]
+  fn create_schedules_is_all_or_nothing() {
+    let mut mgr = ScheduleManager::new();
+    let start = Utc::now();
+    let h = Duration::hours;
+    let parent_id = Uuid::now_v7();
+
+    let ids = mgr
+      .create_schedules(vec![
+        (
+          Some(parent_id),
+          Schedule::new(start, start + h(4), 1, true, "block".into()),
+          HashSet::new(),
+        ),
+        (
+          None,
+          Schedule::new(start, start + h(1), 2, false, "part".into()),
+          HashSet::from([parent_id]),
+        ),
+      ])
+      .unwrap();
+    assert_eq!(ids.len(), 2);
+    assert_eq!(ids[0], parent_id);
+    assert_eq!(mgr.parent_relations()[&ids[1]], HashSet::from([parent_id]));
+
+    // Second item overlaps the exclusive block: nothing from the batch stays
+    let res = mgr.create_schedules(vec![
+      (
+        None,
+        Schedule::new(start + h(5), start + h(6), 2, false, "ok".into()),
+        HashSet::new(),
+      ),
+      (
+        None,
+        Schedule::new(start + h(3), start + h(5), 2, false, "clash".into()),
+        HashSet::new(),
+      ),
+    ]);
+    assert_eq!(res, Err(ScheduleError::TimeRangeOverlaps));
+    assert_eq!(mgr.len(), 2);
+  }
+
+  #[test]
+  fn clear_resets_manager() {
+    let mut mgr = ScheduleManager::new();
+    let start = Utc::now();
+    let parent = mgr
+      .create_schedule(
+        Schedule::new(start, start + Duration::hours(4), 1, true, "block".into()),
+        HashSet::new(),
+      )
+      .unwrap();
+    let child = mgr
+      .create_schedule(
+        Schedule::new(start, start + Duration::hours(1), 2, false, "child".into()),
+        HashSet::from([parent]),
+      )
+      .unwrap();
+
+    mgr.clear();
+    assert_eq!(mgr.len(), 0);
+    assert!(mgr.get_schedule(parent).is_none());
+    assert!(mgr.parent_relations().is_empty());
+    assert!(mgr.query_schedule(QueryOptions::default()).is_empty());
+
+    // The previously occupied range is free again and ids are fresh
+    let id = mgr
+      .create_schedule(
+        Schedule::new(start, start + Duration::hours(4), 1, true, "block".into()),
+        HashSet::new(),
+      )
+      .unwrap();
+    assert_ne!(id, parent);
+    assert_ne!(id, child);
+    assert_eq!(mgr.len(), 1);
+  }
+
+  #[test]
+  fn zero_length_and_reversed_ranges_are_distinct_errors() {
+    let mut mgr = ScheduleManager::new();
+    let start = Utc::now();
+
+    let zero = Schedule::new(start, start, 1, false, "zero".into());
+    assert_eq!(
+      mgr.create_schedule(zero, HashSet::new()),
+      Err(ScheduleError::ZeroLengthRange)
+    );
+
+    let reversed = Schedule::new(start, start - Duration::hours(1), 1, false, "rev".into());
+    assert_eq!(
+      mgr.create_schedule(reversed, HashSet::new()),
+      Err(ScheduleError::StartAfterEnd)
+    );
+    assert!(mgr.is_empty());
+  }
+
+  #[test]
+  fn max_depth_limits_hierarchy() {
+    let mut mgr = ScheduleManager::new().with_max_depth(2);
+    let start = Utc::now();
+    let h = Duration::hours;
+
+    let root = mgr
+      .create_schedule(
+        Schedule::new(start, start + h(8), 1, false, "root".into()),
+        HashSet::new(),
+      )
+      .unwrap();
+    let mid = mgr
+      .create_schedule(
+        Schedule::new(start, start + h(4), 2, false, "mid".into()),
+        HashSet::from([root]),
+      )
+      .unwrap();
+    assert_eq!(
+      mgr.create_schedule(
+        Schedule::new(start, start + h(1), 3, false, "leaf".into()),
+        HashSet::from([mid]),
+      ),
+      Err(ScheduleError::MaxDepthExceeded)
+    );
+
+    // Linking a root that already has a child under another root would
+    // push that child to depth 3.
+    let other = mgr
+      .create_schedule(
+        Schedule::new(start, start + h(2), 3, false, "other".into()),
+        HashSet::new(),
+      )
+      .unwrap();
+    let below = mgr
+      .create_schedule(
+        Schedule::new(start, start + h(1), 4, false, "below".into()),
+        HashSet::from([other]),
+      )
+      .unwrap();
+    assert_eq!(
+      mgr.add_parents(other, HashSet::from([mid])),
+      Err(ScheduleError::MaxDepthExceeded)
+    );
+    assert!(mgr.parent_relations()[&below].contains(&other));
+
+    // Unlimited by default
+    assert_eq!(ScheduleManager::new().max_depth(), None);
+  }
+
+  #[test]
+  fn query_by_level_range() {
+    let mut mgr = ScheduleManager::new();
+    let start = Utc::now();
+    let h = Duration::hours;
+    for level in 1..=4u32 {
+      mgr
+        .create_schedule(
+          Schedule::new(start, start + h(1), level, false, format!("l{level}")),
+          HashSet::new(),
+        )
+        .unwrap();
+    }
+
+    let levels = |opts: QueryOptions| {
+      let mut l: Vec<ScheduleLevel> = mgr
+        .query_schedule(opts)
+        .into_iter()
+        .map(|(_, s)| s.level)
+        .collect();
+      l.sort();
+      l
+    };
+    assert_eq!(
+      levels(
+        QueryOptions::builder()
+          .level_min(2u32)
+          .level_max(3u32)
+          .build()
+      ),
+      vec![2, 3]
+    );
+    assert_eq!(
+      levels(QueryOptions::builder().level_min(3u32).build()),
+      vec![3, 4]
+    );
+    assert_eq!(
+      levels(QueryOptions::builder().level_max(1u32).build()),
+      vec![1]
+    );
+    // Exact level intersects with the range
+    assert_eq!(
+      levels(QueryOptions::builder().level(2u32).level_min(3u32).build()),
+      Vec::<ScheduleLevel>::new()
+    );
+    assert_eq!(
+      levels(QueryOptions::builder().level(3u32).level_max(3u32).build()),
+      vec![3]
+    );
+  }
+
+  #[test]
+  fn statistics_summarize_manager() {
+    let mut mgr = ScheduleManager::new();
+    assert_eq!(mgr.statistics(), ScheduleStats::default());
+
+    let start = Utc::now();
+    let h = Duration::hours;
+    let root = mgr
+      .create_schedule(
+        Schedule::new(start, start + h(8), 1, true, "day".into()),
+        HashSet::new(),
+      )
+      .unwrap();
+    for (offset, len) in [(0, 2), (1, 2), (6, 1)] {
+      mgr
+        .create_schedule(
+          Schedule::new(
+            start + h(offset),
+            start + h(offset + len),
+            2,
+            false,
+            "task".into(),
+          ),
+          HashSet::from([root]),
+        )
+        .unwrap();
+    }
+
+    let stats = mgr.statistics();
+    assert_eq!(stats.total, 4);
+    assert_eq!(
+      stats.per_level,
+      std::collections::BTreeMap::from([(1, 1), (2, 3)])
+    );
+    assert_eq!(stats.exclusive, 1);
+    assert_eq!(stats.covered_per_level[&1], h(8));
+    assert_eq!(stats.covered_per_level[&2], h(4));
+    // root plus both overlapping tasks during [1h, 2h)
+    assert_eq!(stats.busiest_count, 3);
+    assert_eq!(stats.busiest_window, Some((start + h(1), start + h(2))));
+  }
+
+  #[test]
+  fn find_all_conflicts_lists_same_level_overlaps() {
+    let mut mgr = ScheduleManager::new();
+    let start = Utc::now();
+    let h = Duration::hours;
+    let mut add = |offset: i64, len: i64, level: ScheduleLevel| {
+      mgr
+        .create_schedule(
+          Schedule::new(
+            start + h(offset),
+            start + h(offset + len),
+            level,
+            false,
+            "s".into(),
+          ),
+          HashSet::new(),
+        )
+        .unwrap()
+    };
+    let a = add(0, 3, 2);
+    let b = add(1, 1, 2);
+    let c = add(2, 2, 2);
+    // Touches c at its end, which is not a conflict
+    add(4, 1, 2);
+    // Overlaps a, b and c in time but sits on another level
+    add(0, 5, 3);
+
+    let mut all = mgr.find_all_conflicts(None);
+    all.sort();
+    let mut expected = vec![(a, b), (a, c)];
+    expected.sort();
+    assert_eq!(all, expected);
+    assert_eq!(mgr.find_all_conflicts(Some(2)).len(), 2);
+    assert!(mgr.find_all_conflicts(Some(3)).is_empty());
+    assert!(mgr.find_all_conflicts(Some(9)).is_empty());
+  }
+
+  #[test]
+  fn schedule_meta_defaults_and_tag_query() {
+    let mut mgr = ScheduleManager::new();
+    let start = Utc::now();
+    let h = Duration::hours;
+    let tagged = mgr
+      .create_schedule(
+        Schedule::new_with_meta(
+          start,
+          start + h(1),
+          1,
+          false,
+          "standup".into(),
+          Some("daily sync".into()),
+          vec!["work".into(), "team".into()],
+        ),
+        HashSet::new(),
+      )
+      .unwrap();
+    mgr
+      .create_schedule(
+        Schedule::new(start + h(2), start + h(3), 1, false, "gym".into()),
+        HashSet::new(),
+      )
+      .unwrap();
+
+    let found = mgr.query_schedule(QueryOptions::builder().tag("team").build());
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].0, tagged);
+    assert_eq!(found[0].1.description(), Some("daily sync"));
+    assert!(
+      mgr
+        .query_schedule(QueryOptions::builder().tag("Team").build())
+        .is_empty()
+    );
+
+    // Data persisted before these fields existed still loads
+    let old = format!(
+      r#"{{"start":"{}","end":"{}","level":1,"exclusive":false,"name":"legacy"}}"#,
+      start.to_rfc3339(),
+      (start + h(1)).to_rfc3339()
+    );
+    let legacy: Schedule = serde_json::from_str(&old).unwrap();
+    assert_eq!(legacy.description(), None);
+    assert!(legacy.tags().is_empty());
+  }
+
+  #[test]
+  fn query_tags_any_and_all() {
+    let mut mgr = ScheduleManager::new();
+    let start = Utc::now();
+    let h = Duration::hours;
+    let mut add = |offset: i64, name: &str, tags: &[&str]| {
+      mgr
+        .create_schedule(
+          Schedule::new_with_meta(
+            start + h(offset),
+            start + h(offset + 1),
+            1,
+            false,
+            name.into(),
+            None,
+            tags.iter().map(|t| t.to_string()).collect(),
+          ),
+          HashSet::new(),
+        )
+        .unwrap()
+    };
+    let a = add(0, "a", &["work", "urgent"]);
+    let b = add(1, "b", &["work"]);
+    let c = add(2, "c", &["home"]);
+    add(3, "d", &[]);
+
+    let ids = |opts: QueryOptions| {
+      let mut ids: Vec<ScheduleId> = mgr
+        .query_schedule(opts)
+        .into_iter()
+        .map(|(id, _)| id)
+        .collect();
+      ids.sort();
+      ids
+    };
+    let sorted = |mut v: Vec<ScheduleId>| {
+      v.sort();
+      v
+    };
+    assert_eq!(
+      ids(
+        QueryOptions::builder()
+          .tags_any(vec!["urgent".into(), "home".into()])
+          .build()
+      ),
+      sorted(vec![a, c])
+    );
+    assert_eq!(
+      ids(
+        QueryOptions::builder()
+          .tags_all(vec!["work".into(), "urgent".into()])
+          .build()
+      ),
+      vec![a]
+    );
+    // Filters AND together with the name filter
+    assert_eq!(
+      ids(
+        QueryOptions::builder()
+          .tags_any(vec!["work".into()])
+          .name("b")
+          .build()
+      ),
+      vec![b]
+    );
+
+    let tags: Vec<String> = mgr.all_tags().into_iter().collect();
+    assert_eq!(tags, vec!["home", "urgent", "work"]);
+  }
+
+  #[test]
+  fn sort_by_priority_and_start() {
+    let mut mgr = ScheduleManager::new();
+    let start = Utc::now();
+    let h = Duration::hours;
+    let mut add = |offset: i64, priority: u8| {
+      mgr
+        .create_schedule(
+          Schedule::new(
+            start + h(offset),
+            start + h(offset + 1),
+            1,
+            true,
+            "s".into(),
+          )
+          .with_priority(priority)
+          .with_color(Some("#ff8800".into())),
+          HashSet::new(),
+        )
+        .unwrap()
+    };
+    let low = add(0, 1);
+    let high_late = add(2, 9);
+    let high_early = add(1, 9);
+
+    let order = |sort: SortOrder| -> Vec<ScheduleId> {
+      mgr
+        .query_schedule(QueryOptions::builder().sort(sort).build())
+        .into_iter()
+        .map(|(id, _)| id)
+        .collect()
+    };
+    assert_eq!(
+      order(SortOrder::PriorityDesc),
+      vec![high_early, high_late, low]
+    );
+    assert_eq!(order(SortOrder::StartAsc), vec![low, high_early, high_late]);
+    assert_eq!(
+      order(SortOrder::StartDesc),
+      vec![high_late, high_early, low]
+    );
+
+    // Sorting happens before paging
+    let page = mgr.query_schedule(
+      QueryOptions::builder()
+        .sort(SortOrder::StartAsc)
+        .offset(1usize)
+        .limit(1usize)
+        .build(),
+    );
+    assert_eq!(page[0].0, high_early);
+    assert_eq!(page[0].1.color(), Some("#ff8800"));
+    assert_eq!(
+      Schedule::new(start, start + h(1), 1, false, "x".into()).priority(),
+      0
+    );
+  }
+
+  #[test]
+  fn create_or_shift_moves_to_next_free_slot() {
+    let mut mgr = ScheduleManager::new();
+    let start = Utc::now();
+    let h = Duration::hours;
+    let block = |offset: i64, len: i64| {
+      Schedule::new(
+        start + h(offset),
+        start + h(offset + len),
+        1,
+        true,
+        "b".into(),
+      )
+    };
+    mgr.create_schedule(block(0, 2), HashSet::new()).unwrap();
+    mgr.create_schedule(block(3, 1), HashSet::new()).unwrap();
+
+    // Free already: no shift
+    let (_, offset) = mgr
+      .create_or_shift(block(5, 1), HashSet::new(), h(4))
+      .unwrap();
+    assert_eq!(offset, Duration::zero());
+
+    // [1h, 2h) clashes; [2h, 3h) is the first free hour
+    let (id, offset) = mgr
+      .create_or_shift(block(1, 1), HashSet::new(), h(4))
+      .unwrap();
+    assert_eq!(offset, h(1));
+    assert_eq!(mgr.get_schedule(id).unwrap().start, start + h(2));
+
+    // Now everything up to 6h is taken; a 2h shift is not enough
+    assert_eq!(
+      mgr
+        .create_or_shift(block(0, 1), HashSet::new(), h(2))
+        .map(|_| ()),
+      Err(ScheduleError::TimeRangeOverlaps)
+    );
+    assert_eq!(mgr.len(), 4);
+  }
+
+  #[test]
+  fn create_or_shift_accepts_an_unbounded_max_shift() {
+    let mut mgr = ScheduleManager::new();
+    let start = Utc::now();
+    let h = Duration::hours;
+    mgr
+      .create_schedule(
+        Schedule::new(start, start + h(2), 1, true, "busy".into()),
+        HashSet::new(),
+      )
+      .unwrap();
+
+    let (id, offset) = mgr
+      .create_or_shift(
+        Schedule::new(start, start + h(1), 1, true, "moved".into()),
+        HashSet::new(),
+        Duration::MAX,
+      )
+      .unwrap();
+    assert_eq!(offset, h(2));
+    assert_eq!(mgr.get_schedule(id).unwrap().start, start + h(2));
+
+    assert_eq!(
+      mgr
+        .create_or_shift(
+          Schedule::new(start, start + h(1), 1, true, "never".into()),
+          HashSet::new(),
+          Duration::MIN,
+        )
+        .map(|(_, offset)| offset),
+      Err(ScheduleError::TimeRangeOverlaps)
+    );
+  }
+
+  #[test]
+  fn create_or_shift_ignores_exclusive_ancestors() {
+    let mut mgr = ScheduleManager::new();
+    let start = Utc::now();
+    let h = Duration::hours;
+    let grandparent = Schedule::new(start, start + h(10), 0, true, "gp".into());
+    let gp_id = mgr.create_schedule(grandparent, HashSet::new()).unwrap();
+    let parent = Schedule::new(start, start + h(10), 1, false, "p".into());
+    let p_id = mgr.create_schedule(parent, HashSet::from([gp_id])).unwrap();
+    let taken = Schedule::new(start, start + h(1), 2, true, "taken".into());
+    mgr.create_schedule(taken, HashSet::from([p_id])).unwrap();
+
+    // The exclusive grandparent contains the child, so it must not block
+    // the search for a free slot
+    let child = Schedule::new(start, start + h(1), 2, false, "child".into());
+    let (id, offset) = mgr
+      .create_or_shift(child, HashSet::from([p_id]), h(4))
+      .unwrap();
+    assert_eq!(offset, h(1));
+    assert_eq!(mgr.get_schedule(id).unwrap().start, start + h(1));
+  }
+
+  #[test]
+  fn undo_redo_reverts_mutations() {
+    let mut mgr = ScheduleManager::new().with_history_limit(10);
+    assert_eq!(mgr.undo(), Err(ScheduleError::NothingToUndo));
+    let start = Utc::now();
+    let h = Duration::hours;
+
+    let parent = mgr
+      .create_schedule(
+        Schedule::new(start, start + h(4), 1, true, "parent".into()),
+        HashSet::new(),
+      )
+      .unwrap();
+    let child = mgr
+      .create_schedule(
+        Schedule::new(start, start + h(1), 2, false, "child".into()),
+        HashSet::from([parent]),
+      )
+      .unwrap();
+    mgr
+      .update_schedule(
+        child,
+        Schedule::new(start + h(1), start + h(2), 2, false, "moved".into()),
+      )
+      .unwrap();
+
+    // Failed operations leave no history entry
+    assert!(
+      mgr
+        .create_schedule(
+          Schedule::new(start, start + h(1), 1, true, "clash".into()),
+          HashSet::new(),
+        )
+        .is_err()
+    );
+
+    // Cascade delete and its undo restore both schedules and the link
+    assert_eq!(mgr.delete_schedule(parent).unwrap().len(), 2);
+    assert!(mgr.is_empty());
+    mgr.undo().unwrap();
+    assert_eq!(mgr.len(), 2);
+    assert_eq!(mgr.get_schedule(child).unwrap().name(), "moved");
+    assert_eq!(mgr.parent_relations()[&child], HashSet::from([parent]));
+    assert_eq!(mgr.descendants(parent), vec![child]);
+    // Interval indices are restored too
+    let probe = Schedule::new(start, start + h(1), 1, true, "probe".into());
+    assert_eq!(mgr.conflicts_for(&probe, &HashSet::new()), vec![parent]);
+
+    mgr.undo().unwrap();
+    assert_eq!(mgr.get_schedule(child).unwrap().name(), "child");
+    mgr.redo().unwrap();
+    assert_eq!(mgr.get_schedule(child).unwrap().name(), "moved");
+
+    // A new mutation clears the redo stack
+    mgr.undo().unwrap();
+    assert!(mgr.can_redo());
+    mgr.move_schedule(parent, h(1)).unwrap();
+    assert!(!mgr.can_redo());
+    assert_eq!(mgr.redo(), Err(ScheduleError::NothingToRedo));
+    mgr.undo().unwrap();
+    assert_eq!(mgr.get_schedule(parent).unwrap().start, start);
+    assert_eq!(mgr.get_schedule(child).unwrap().start, start);
+
+    // Undo the two creates
+    mgr.undo().unwrap();
+    mgr.undo().unwrap();
+    assert!(mgr.is_empty());
+    assert!(
+      mgr
+        .query_schedule(QueryOptions::builder().name("parent").build())
+        .is_empty()
+    );
+    assert!(!mgr.can_undo());
+  }
+
+  #[test]
+  fn history_limit_caps_undo_steps() {
+    let mut mgr = ScheduleManager::new().with_history_limit(2);
+    let start = Utc::now();
+    for i in 0..4 {
+      mgr
+        .create_schedule(
+          Schedule::new(
+            start + Duration::hours(i),
+            start + Duration::hours(i + 1),
+            1,
+            false,
+            "s".into(),
+          ),
+          HashSet::new(),
+        )
+        .unwrap();
+    }
+    mgr.undo().unwrap();
+    mgr.undo().unwrap();
+    assert_eq!(mgr.undo(), Err(ScheduleError::NothingToUndo));
+    assert_eq!(mgr.len(), 2);
+
+    let mut off = ScheduleManager::new().with_history_limit(0);
+    off
+      .create_schedule(
+        Schedule::new(start, start + Duration::hours(1), 1, false, "s".into()),
+        HashSet::new(),
+      )
+      .unwrap();
+    assert!(!off.can_undo());
+  }
+
+  #[test]
+  fn validate_create_matches_create_without_inserting() {
+    let mut mgr = ScheduleManager::new();
+    let start = Utc::now();
+    let h = Duration::hours;
+    let block = Schedule::new(start, start + h(2), 1, true, "block".into());
+    assert_eq!(mgr.validate_create(&block, &HashSet::new()), Ok(()));
+    assert!(mgr.is_empty());
+    mgr.create_schedule(block, HashSet::new()).unwrap();
+
+    let clash = Schedule::new(start + h(1), start + h(3), 1, false, "clash".into());
+    assert_eq!(
+      mgr.validate_create(&clash, &HashSet::new()),
+      Err(ScheduleError::TimeRangeOverlaps)
+    );
+    assert_eq!(
+      mgr.validate_create(&clash, &HashSet::from([Uuid::now_v7()])),
+      Err(ScheduleError::ParentNotFound)
+    );
+    assert_eq!(mgr.len(), 1);
+  }
+
+  #[test]
+  fn children_and_parents_resolve_sorted() {
+    let mut mgr = ScheduleManager::new();
+    let start = Utc::now();
+    let h = Duration::hours;
+    let parent = mgr
+      .create_schedule(
+        Schedule::new(start, start + h(6), 1, false, "parent".into()),
+        HashSet::new(),
+      )
+      .unwrap();
+    let late = mgr
+      .create_schedule(
+        Schedule::new(start + h(4), start + h(5), 2, false, "late".into()),
+        HashSet::from([parent]),
+      )
+      .unwrap();
+    let early = mgr
+      .create_schedule(
+        Schedule::new(start, start + h(1), 2, false, "early".into()),
+        HashSet::from([parent]),
+      )
+      .unwrap();
+
+    let children: Vec<ScheduleId> = mgr
+      .children_of(parent)
+      .into_iter()
+      .map(|(id, _)| id)
+      .collect();
+    assert_eq!(children, vec![early, late]);
+    let parents = mgr.parents_of(late);
+    assert_eq!(parents.len(), 1);
+    assert_eq!(parents[0].1.name(), "parent");
+    assert!(mgr.parents_of(parent).is_empty());
+    assert!(mgr.children_of(Uuid::now_v7()).is_empty());
+  }
+
+  #[test]
+  fn exclusive_lineage_matrix() {
+    let mut mgr = ScheduleManager::new();
+    let start = Utc::now();
+    let h = Duration::hours;
+    let sched = |offset: i64, len: i64, level: ScheduleLevel, exclusive: bool| {
+      Schedule::new(
+        start + h(offset),
+        start + h(offset + len),
+        level,
+        exclusive,
+        "s".into(),
+      )
+    };
+
+    // Exclusive root > exclusive mid > leaf: a grandchild under an exclusive
+    // grandparent is allowed.
+    let root = mgr
+      .create_schedule(sched(0, 10, 1, true), HashSet::new())
+      .unwrap();
+    let mid_a = mgr
+      .create_schedule(sched(0, 4, 2, true), HashSet::from([root]))
+      .unwrap();
+    let leaf = mgr
+      .create_schedule(sched(1, 1, 3, false), HashSet::from([mid_a]))
+      .unwrap();
+
+    // A sibling subtree may sit beside mid_a but not overlap it
+    let mid_b = mgr
+      .create_schedule(sched(5, 3, 2, true), HashSet::from([root]))
+      .unwrap();
+    assert_eq!(
+      mgr.create_schedule(sched(3, 2, 2, false), HashSet::from([root])),
+      Err(ScheduleError::TimeRangeOverlaps)
+    );
+    // A leaf in mid_b's subtree cannot reach into mid_a's time either
+    assert_eq!(
+      mgr.validate_create(&sched(3, 1, 3, false), &HashSet::from([mid_b])),
+      Err(ScheduleError::TimeRangeExceedsParent)
+    );
+
+    // Updating an exclusive schedule that has grandchildren is allowed,
+    // but growing it into the sibling subtree is not
+    let mut renamed = mgr.get_schedule(mid_a).unwrap().clone();
+    renamed.name = "renamed".into();
+    assert_eq!(mgr.update_schedule(mid_a, renamed.clone()), Ok(()));
+    let mut wider = renamed;
+    wider.end = start + h(6);
+    assert_eq!(
+      mgr.update_schedule(mid_a, wider),
+      Err(ScheduleError::TimeRangeOverlaps)
+    );
+
+    // Linking the leaf directly to its exclusive grandparent as well is
+    // allowed: the existing parent chain is not a conflict
+    assert_eq!(mgr.add_parents(leaf, HashSet::from([root])), Ok(()));
+    assert_eq!(mgr.parents_of(leaf).len(), 2);
+  }
+
+  #[test]
+  fn level_capacity_allows_n_overlaps() {
+    let mut mgr = ScheduleManager::new();
+    mgr.set_level_capacity(2, Some(3));
+    assert_eq!(mgr.level_capacity(2), Some(3));
+    let start = Utc::now();
+    let h = Duration::hours;
+    let booking =
+      |exclusive: bool| Schedule::new(start, start + h(1), 2, exclusive, "booking".into());
+
+    // Exclusive bookings at a capacity level still share up to 3 slots
+    for _ in 0..3 {
+      mgr.create_schedule(booking(true), HashSet::new()).unwrap();
+    }
+    assert_eq!(
+      mgr.create_schedule(booking(false), HashSet::new()),
+      Err(ScheduleError::TimeRangeOverlaps)
+    );
+    assert_eq!(mgr.conflicts_for(&booking(false), &HashSet::new()).len(), 3);
+
+    // Exclusivity across levels is unchanged
+    assert_eq!(
+      mgr.create_schedule(
+        Schedule::new(start, start + h(1), 3, false, "below".into()),
+        HashSet::new()
+      ),
+      Err(ScheduleError::TimeRangeOverlaps)
+    );
+
+    // Removing the capacity restores zero tolerance for exclusive schedules
+    mgr.set_level_capacity(2, None);
+    let later = Schedule::new(start + h(2), start + h(3), 2, true, "later".into());
+    mgr.create_schedule(later.clone(), HashSet::new()).unwrap();
+    assert_eq!(
+      mgr.create_schedule(later, HashSet::new()),
+      Err(ScheduleError::TimeRangeOverlaps)
+    );
+  }
+
+  #[test]
+  fn agenda_merges_levels_in_start_order() {
+    let mut mgr = ScheduleManager::new();
+    let base = Utc::now();
+    let h = Duration::hours;
+    let mk = |offset: i64, level: u32, name: &str| {
+      Schedule::new(
+        base + h(offset),
+        base + h(offset + 1),
+        level,
+        false,
+        name.into(),
+      )
+    };
+    mgr
+      .create_schedule(mk(0, 1, "past"), HashSet::new())
+      .unwrap();
+    mgr.create_schedule(mk(5, 1, "e"), HashSet::new()).unwrap();
+    mgr.create_schedule(mk(2, 2, "b"), HashSet::new()).unwrap();
+    mgr.create_schedule(mk(1, 3, "a"), HashSet::new()).unwrap();
+    mgr.create_schedule(mk(3, 1, "c"), HashSet::new()).unwrap();
+    mgr.create_schedule(mk(4, 2, "d"), HashSet::new()).unwrap();
+
+    let names: Vec<String> = mgr.agenda(base + h(1), None).map(|(_, s)| s.name).collect();
+    assert_eq!(names, ["a", "b", "c", "d", "e"]);
+
+    let level_two: Vec<String> = mgr.agenda(base, Some(2)).map(|(_, s)| s.name).collect();
+    assert_eq!(level_two, ["b", "d"]);
+
+    // Lazy: taking the first item does not need the rest
+    let (_, first) = mgr.agenda(base, None).next().unwrap();
+    assert_eq!(first.name, "past");
+    assert_eq!(mgr.agenda(base + h(6), None).count(), 0);
+  }
+
+  #[test]
+  fn merge_from_handles_collisions_per_strategy() {
+    let base = Utc::now();
+    let h = Duration::hours;
+    let mut ours = ScheduleManager::new().with_history_limit(10);
+    let shared = ours
+      .create_schedule(
+        Schedule::new(base, base + h(10), 1, false, "ours".into()),
+        HashSet::new(),
+      )
+      .unwrap();
+
+    let mut theirs = ScheduleManager::new();
+    theirs
+      .create_schedule_with_id(
+        shared,
+        Schedule::new(base, base + h(12), 1, false, "theirs".into()),
+        HashSet::new(),
+      )
+      .unwrap();
+    let child = theirs
+      .create_schedule(
+        Schedule::new(base + h(1), base + h(2), 2, false, "child".into()),
+        HashSet::from([shared]),
+      )
+      .unwrap();
+    let root = theirs
+      .create_schedule(
+        Schedule::new(base + h(20), base + h(21), 1, false, "root".into()),
+        HashSet::new(),
+      )
+      .unwrap();
+
+    // Fail leaves everything untouched
+    assert_eq!(
+      ours.clone().merge_from(&theirs, MergeStrategy::Fail),
+      Err(ScheduleError::DuplicateId)
+    );
+
+    let mut skipped = ours.clone();
+    let report = skipped.merge_from(&theirs, MergeStrategy::Skip).unwrap();
+    assert_eq!(report.skipped, vec![shared]);
+    assert_eq!(report.added, vec![root, child]);
+    assert!(report.overwritten.is_empty());
+    assert_eq!(skipped.get_schedule(shared).unwrap().name, "ours");
+    assert_eq!(skipped.parent_relations()[&child], HashSet::from([shared]));
+
+    let report = ours.merge_from(&theirs, MergeStrategy::Overwrite).unwrap();
+    assert_eq!(report.overwritten, vec![shared]);
+    assert_eq!(ours.get_schedule(shared).unwrap().name, "theirs");
+    assert_eq!(ours.len(), 3);
+
+    // One undo step reverts the whole merge
+    ours.undo().unwrap();
+    assert_eq!(ours.len(), 1);
+    assert_eq!(ours.get_schedule(shared).unwrap().name, "ours");
+  }
+
+  #[test]
+  fn merge_from_rolls_back_on_rejection() {
+    let base = Utc::now();
+    let h = Duration::hours;
+    let mut ours = ScheduleManager::new();
+    ours
+      .create_schedule(
+        Schedule::new(base + h(5), base + h(6), 1, true, "busy".into()),
+        HashSet::new(),
+      )
+      .unwrap();
+
+    let mut theirs = ScheduleManager::new();
+    let parent = theirs
+      .create_schedule(
+        Schedule::new(base, base + h(2), 1, false, "parent".into()),
+        HashSet::new(),
+      )
+      .unwrap();
+    theirs
+      .create_schedule(
+        Schedule::new(base, base + h(1), 2, false, "child".into()),
+        HashSet::from([parent]),
+      )
+      .unwrap();
+    theirs
+      .create_schedule(
+        Schedule::new(base + h(5), base + h(6), 1, false, "clash".into()),
+        HashSet::new(),
+      )
+      .unwrap();
+
+    let before = ours.to_snapshot();
+    assert_eq!(
+      ours.merge_from(&theirs, MergeStrategy::Skip),
+      Err(ScheduleError::TimeRangeOverlaps)
+    );
+    assert_eq!(ours.len(), 1);
+    assert_eq!(ours.to_snapshot().schedules, before.schedules);
+  }
+
+  #[test]
+  fn merge_from_drops_parents_missing_from_both_managers() {
+    let base = Utc::now();
+    let h = Duration::hours;
+    let mut theirs = ScheduleManager::new();
+    let parent = theirs
+      .create_schedule(
+        Schedule::new(base, base + h(2), 1, false, "parent".into()),
+        HashSet::new(),
+      )
+      .unwrap();
+    let child = theirs
+      .create_schedule(
+        Schedule::new(base, base + h(1), 2, false, "child".into()),
+        HashSet::from([parent]),
+      )
+      .unwrap();
+    let orphan = theirs
+      .create_schedule(
+        Schedule::new(base + h(3), base + h(4), 2, false, "orphan".into()),
+        HashSet::new(),
+      )
+      .unwrap();
+
+    // A deserialized manager can carry links to schedules it never saved
+    let ghost = Uuid::now_v7();
+    let mut json = serde_json::to_value(&theirs).unwrap();
+    json["parent_relations"][child.to_string()] = serde_json::json!([parent, ghost]);
+    json["parent_relations"][orphan.to_string()] = serde_json::json!([ghost]);
+    let theirs: ScheduleManager = serde_json::from_value(json).unwrap();
+
+    let mut ours = ScheduleManager::new();
+    let report = ours.merge_from(&theirs, MergeStrategy::Fail).unwrap();
+    assert_eq!(report.added.len(), 3);
+    let mut dropped = report.dropped_parents.clone();
+    dropped.sort();
+    let mut expected = vec![(child, ghost), (orphan, ghost)];
+    expected.sort();
+    assert_eq!(dropped, expected);
+    assert_eq!(ours.parent_relations()[&child], HashSet::from([parent]));
+    assert!(ours.parent_relations()[&orphan].is_empty());
+    assert!(!ours.contains(ghost));
+  }
+
+  #[test]
+  fn diff_reports_added_removed_and_changed() {
+    let base = Utc::now();
+    let h = Duration::hours;
+    let mut old = ScheduleManager::new();
+    let kept = old
+      .create_schedule(
+        Schedule::new(base, base + h(4), 1, false, "kept".into()),
+        HashSet::new(),
+      )
+      .unwrap();
+    let renamed = old
+      .create_schedule(
+        Schedule::new(base + h(5), base + h(6), 1, false, "a".into()),
+        HashSet::new(),
+      )
+      .unwrap();
+    let relinked = old
+      .create_schedule(
+        Schedule::new(base + h(1), base + h(2), 2, false, "child".into()),
+        HashSet::new(),
+      )
+      .unwrap();
+    let dropped = old
+      .create_schedule(
+        Schedule::new(base + h(8), base + h(9), 1, false, "gone".into()),
+        HashSet::new(),
+      )
+      .unwrap();
+
+    let mut new = old.clone();
+    assert!(old.diff(&new).is_empty());
+
+    new
+      .update_schedule(
+        renamed,
+        Schedule::new(base + h(5), base + h(6), 1, false, "b".into()),
+      )
+      .unwrap();
+    new.add_parents(relinked, HashSet::from([kept])).unwrap();
+    new.delete_schedule(dropped).unwrap();
+    let added = new
+      .create_schedule(
+        Schedule::new(base + h(10), base + h(11), 1, false, "new".into()),
+        HashSet::new(),
+      )
+      .unwrap();
+
+    let diff = old.diff(&new);
+    assert_eq!(diff.added, HashSet::from([added]));
+    assert_eq!(diff.removed, HashSet::from([dropped]));
+    assert_eq!(diff.changed, HashSet::from([renamed, relinked]));
+
+    let reverse = new.diff(&old);
+    assert_eq!(reverse.added, diff.removed);
+    assert_eq!(reverse.removed, diff.added);
+  }
+
+  #[test]
+  fn schedule_equality_compares_all_fields() {
+    fn assert_eq_impl<T: Eq>(_: &T) {}
+    let start = Utc::now();
+    let schedule = Schedule::new(start, start + Duration::hours(1), 1, false, "a".into());
+    assert_eq_impl(&schedule);
+    assert_eq!(schedule, schedule.clone());
+    assert_ne!(schedule, schedule.clone().with_priority(1));
+    assert_ne!(
+      schedule,
+      Schedule::new(start, start + Duration::hours(1), 1, true, "a".into())
+    );
+  }
+
+  #[test]
+  fn schedule_builder_matches_new() {
+    let start = Utc::now();
+    let end = start + Duration::hours(1);
+    let built = Schedule::builder()
+      .start(start)
+      .end(end)
+      .level(1u32)
+      .name("x")
+      .build();
+    assert_eq!(built, Schedule::new(start, end, 1, false, "x".into()));
+
+    let full = Schedule::builder()
+      .start(start)
+      .end(end)
+      .level(2u32)
+      .exclusive(true)
+      .name("y")
+      .description("notes")
+      .tags(vec!["work".into()])
+      .priority(3)
+      .color("#ff8800")
+      .build();
+    assert!(full.exclusive);
+    assert_eq!(full.description(), Some("notes"));
+    assert_eq!(full.color(), Some("#ff8800"));
+
+    // Range validation is left to the manager
+    let backwards = Schedule::builder()
+      .start(end)
+      .end(start)
+      .level(1u32)
+      .name("z")
+      .build();
+    assert_eq!(
+      ScheduleManager::new().create_schedule(backwards, HashSet::new()),
+      Err(ScheduleError::StartAfterEnd)
+    );
+  }
+
+  #[test]
+  fn schedules_by_day_splits_across_utc_days() {
+    use chrono::{NaiveDate, TimeZone};
+    let at = |d: u32, h: u32| Utc.with_ymd_and_hms(2024, 3, d, h, 0, 0).unwrap();
+    let date = |d: u32| NaiveDate::from_ymd_opt(2024, 3, d).unwrap();
+    let mut mgr = ScheduleManager::new();
+    let mut add = |start, end, name: &str| {
+      mgr
+        .create_schedule(
+          Schedule::new(start, end, 1, false, name.into()),
+          HashSet::new(),
+        )
+        .unwrap()
+    };
+    let early = add(at(9, 22), at(10, 2), "early");
+    let trip = add(at(10, 12), at(12, 0), "trip");
+    let lunch = add(at(11, 12), at(11, 13), "lunch");
+    add(at(8, 1), at(8, 2), "before");
+    add(at(13, 0), at(13, 1), "after");
+
+    let days = mgr.schedules_by_day(at(10, 0), at(13, 0)).unwrap();
+    let ids: Vec<(NaiveDate, Vec<ScheduleId>)> = days
+      .into_iter()
+      .map(|(day, items)| (day, items.into_iter().map(|(id, _)| id).collect()))
+      .collect();
+    assert_eq!(
+      ids,
+      vec![
+        (date(10), vec![early, trip]),
+        (date(11), vec![trip, lunch]),
+        // `trip` ends at midnight, so it does not reach the 12th
+        (date(12), vec![]),
+      ]
+    );
+
+    assert!(
+      mgr
+        .schedules_by_day(at(10, 0), at(10, 0))
+        .unwrap()
+        .is_empty()
+    );
+    assert_eq!(mgr.schedules_by_day(at(10, 5), at(10, 6)).unwrap().len(), 1);
+
+    // Windows are capped, including one running to the open end
+    let start = at(10, 0);
+    let limit = start + Duration::days(MAX_DAY_BUCKETS);
+    assert_eq!(
+      mgr.schedules_by_day(start, limit).unwrap().len(),
+      MAX_DAY_BUCKETS as usize
+    );
+    assert_eq!(
+      mgr.schedules_by_day(start, limit + Duration::seconds(1)),
+      Err(ScheduleError::WindowTooLong)
+    );
+    assert_eq!(
+      mgr.schedules_by_day(start, OPEN_END),
+      Err(ScheduleError::WindowTooLong)
+    );
+  }
+
+  #[test]
+  fn reconcile_repairs_one_sided_and_dangling_relations() {
+    let mut mgr = ScheduleManager::new();
+    let start = Utc::now();
+    let h = Duration::hours;
+    let parent = mgr
+      .create_schedule(
+        Schedule::new(start, start + h(4), 1, false, "parent".into()),
+        HashSet::new(),
+      )
+      .unwrap();
+    let a = mgr
+      .create_schedule(
+        Schedule::new(start, start + h(1), 2, false, "a".into()),
+        HashSet::from([parent]),
+      )
+      .unwrap();
+    let b = mgr
+      .create_schedule(
+        Schedule::new(start + h(2), start + h(3), 2, false, "b".into()),
+        HashSet::from([parent]),
+      )
+      .unwrap();
+    let ghost = Uuid::now_v7();
+
+    // Simulate a partial write: `a` lost its parent link, `parent` lost
+    // its link to `b`, and `b` points at a schedule that was never saved.
+    let mut json: serde_json::Value = serde_json::to_value(&mgr).unwrap();
+    json["parent_relations"][a.to_string()] = serde_json::json!([]);
+    json["parent_relations"][b.to_string()] = serde_json::json!([parent, ghost]);
+    json["child_relations"][parent.to_string()] = serde_json::json!([a]);
+
+    let mut restored: ScheduleManager = serde_json::from_value(json).unwrap();
+    assert_eq!(restored.reconcile_relations(), 3);
+    assert_eq!(restored.parent_relations()[&a], HashSet::from([parent]));
+    assert_eq!(restored.parent_relations()[&b], HashSet::from([parent]));
+    assert_eq!(restored.child_relations()[&parent], HashSet::from([a, b]));
+    assert!(!restored.child_relations().contains_key(&ghost));
+
+    // Consistent graphs need no repair
+    assert_eq!(restored.reconcile_relations(), 0);
+    assert_eq!(mgr.reconcile_relations(), 0);
+  }
+
+  #[test]
+  fn to_dot_lists_nodes_and_edges() {
+    let mut mgr = ScheduleManager::new();
+    let start = Utc::now();
+    let h = Duration::hours;
+    let parent = mgr
+      .create_schedule(
+        Schedule::new(start, start + h(2), 1, false, "Say \"hi\"".into()),
+        HashSet::new(),
+      )
+      .unwrap();
+    let child = mgr
+      .create_schedule(
+        Schedule::new(start, start + h(1), 2, false, "child".into()),
+        HashSet::from([parent]),
+      )
+      .unwrap();
+
+    let dot = mgr.to_dot();
+    assert!(dot.starts_with("digraph schedules {\n"));
+    assert!(dot.ends_with("}\n"));
+    assert!(dot.contains(&format!(
+      "\"{parent}\" [label=\"Say \\\"hi\\\"\\nlevel 1\"];"
+    )));
+    assert!(dot.contains(&format!("\"{child}\" [label=\"child\\nlevel 2\"];")));
+    assert!(dot.contains(&format!("\"{parent}\" -> \"{child}\";")));
+    assert_eq!(dot.matches("->").count(), 1);
+  }
+
+  #[test]
+  fn delete_schedules_unions_cascades_and_skips_unknown() {
+    let mut mgr = ScheduleManager::new().with_history_limit(10);
+    let start = Utc::now();
+    let h = Duration::hours;
+    let parent = mgr
+      .create_schedule(
+        Schedule::new(start, start + h(4), 1, false, "parent".into()),
+        HashSet::new(),
+      )
+      .unwrap();
+    let child = mgr
+      .create_schedule(
+        Schedule::new(start, start + h(1), 2, false, "child".into()),
+        HashSet::from([parent]),
+      )
+      .unwrap();
+    let other = mgr
+      .create_schedule(
+        Schedule::new(start + h(5), start + h(6), 1, false, "other".into()),
+        HashSet::new(),
+      )
+      .unwrap();
+    let keep = mgr
+      .create_schedule(
+        Schedule::new(start + h(7), start + h(8), 1, false, "keep".into()),
+        HashSet::new(),
+      )
+      .unwrap();
+
+    // `child` is listed explicitly but already goes with `parent`
+    let removed = mgr
+      .delete_schedules(&[parent, child, Uuid::now_v7(), other, other])
+      .unwrap();
+    assert_eq!(removed, HashSet::from([parent, child, other]));
+    assert_eq!(mgr.len(), 1);
+    assert!(mgr.get_schedule(keep).is_some());
+
+    // One undo step restores everything
+    mgr.undo().unwrap();
+    assert_eq!(mgr.len(), 4);
+    assert_eq!(mgr.parent_relations()[&child], HashSet::from([parent]));
+    assert_eq!(mgr.delete_schedules(&[]).unwrap(), HashSet::new());
+  }
+
+  #[test]
+  fn query_with_relations_attaches_parents_and_children() {
+    let mut mgr = ScheduleManager::new();
+    let start = Utc::now();
+    let h = Duration::hours;
+    let parent = mgr
+      .create_schedule(
+        Schedule::new(start, start + h(4), 1, false, "parent".into()),
+        HashSet::new(),
+      )
+      .unwrap();
+    let child = mgr
+      .create_schedule(
+        Schedule::new(start, start + h(1), 2, false, "child".into()),
+        HashSet::from([parent]),
+      )
+      .unwrap();
+
+    let results =
+      mgr.query_with_relations(QueryOptions::builder().sort(SortOrder::StartAsc).build());
+    assert_eq!(results.len(), 2);
+    let find = |id| results.iter().find(|(rid, ..)| *rid == id).unwrap();
+    let (_, schedule, parents, children) = find(parent);
+    assert_eq!(schedule.name, "parent");
+    assert!(parents.is_empty());
+    assert_eq!(children, &vec![child]);
+    let (_, _, parents, children) = find(child);
+    assert_eq!(parents, &vec![parent]);
+    assert!(children.is_empty());
+
+    let only_children = mgr.query_with_relations(QueryOptions::builder().level(2u32).build());
+    assert_eq!(only_children.len(), 1);
+  }
+
+  #[test]
+  fn query_options_and_matcher_composes_predicates() {
+    let mut mgr = ScheduleManager::new();
+    let start = Utc::now();
+    let h = Duration::hours;
+    for (offset, name) in [(0, "alpha one"), (2, "alpha two"), (4, "beta one")] {
+      mgr
+        .create_schedule(
+          Schedule::new(
+            start + h(offset),
+            start + h(offset + 1),
+            1,
+            false,
+            name.into(),
+          ),
+          HashSet::new(),
+        )
+        .unwrap();
+    }
+    let names = |opts: QueryOptions| -> Vec<String> {
+      let mut names: Vec<String> = mgr
+        .query_schedule(opts)
+        .into_iter()
+        .map(|(_, s)| s.name)
+        .collect();
+      names.sort();
+      names
+    };
+
+    let opts = QueryOptions::builder()
+      .and_matcher(|s| s.name.starts_with("alpha"))
+      .and_matcher(|s| s.name.ends_with("one"))
+      .build();
+    assert_eq!(names(opts), ["alpha one"]);
+
+    // The plain setter still works and composes with the value method
+    let opts = QueryOptions::builder()
+      .matcher(Some(std::sync::Arc::new(|s: &Schedule| {
+        s.name.ends_with("one")
+      })))
+      .build()
+      .and_matcher(|s| s.name.starts_with("beta"));
+    assert_eq!(names(opts), ["beta one"]);
+    assert_eq!(
+      names(QueryOptions::default().and_matcher(|_| true)).len(),
+      3
+    );
+  }
+
+  #[test]
+  fn contains_reports_membership() {
+    let mut mgr = ScheduleManager::new();
+    let start = Utc::now();
+    let id = mgr
+      .create_schedule(
+        Schedule::new(start, start + Duration::hours(1), 1, false, "a".into()),
+        HashSet::new(),
+      )
+      .unwrap();
+    assert!(mgr.contains(id));
+    assert!(!mgr.contains(Uuid::now_v7()));
+    mgr.delete_schedule(id).unwrap();
+    assert!(!mgr.contains(id));
+  }
+
+  #[test]
+  fn exclusive_scope_controls_blocked_levels() {
+    let mut mgr = ScheduleManager::new();
+    let start = Utc::now();
+    let h = Duration::hours;
+    let at = |level: ScheduleLevel, exclusive: bool, name: &str| {
+      Schedule::new(start, start + h(1), level, exclusive, name.into())
+    };
+
+    // Default scope: an exclusive level-2 schedule blocks level 3 but not level 1
+    assert_eq!(
+      at(2, true, "x").exclusive_scope(),
+      ExclusiveScope::SameAndLower
+    );
+    let default_id = mgr
+      .create_schedule(at(2, true, "default"), HashSet::new())
+      .unwrap();
+    assert_eq!(
+      mgr.create_schedule(at(3, false, "below"), HashSet::new()),
+      Err(ScheduleError::TimeRangeOverlaps)
+    );
+    mgr
+      .create_schedule(at(1, false, "above"), HashSet::new())
+      .unwrap();
+    mgr.delete_schedule(default_id).unwrap();
+
+    // AllLevels also blocks higher levels, in both directions
+    let holiday = at(2, true, "holiday").with_exclusive_scope(ExclusiveScope::AllLevels);
+    assert_eq!(
+      mgr.create_schedule(holiday.clone(), HashSet::new()),
+      Err(ScheduleError::TimeRangeOverlaps)
+    );
+    let mut empty = ScheduleManager::new();
+    empty.create_schedule(holiday, HashSet::new()).unwrap();
+    assert_eq!(
+      empty.create_schedule(at(0, false, "top"), HashSet::new()),
+      Err(ScheduleError::TimeRangeOverlaps)
+    );
+
+    // SameLevelOnly leaves child levels free
+    let mut mgr = ScheduleManager::new();
+    mgr
+      .create_schedule(
+        at(2, true, "room").with_exclusive_scope(ExclusiveScope::SameLevelOnly),
+        HashSet::new(),
+      )
+      .unwrap();
+    mgr
+      .create_schedule(at(3, false, "task"), HashSet::new())
+      .unwrap();
+    assert_eq!(
+      mgr.create_schedule(at(2, false, "peer"), HashSet::new()),
+      Err(ScheduleError::TimeRangeOverlaps)
+    );
+  }
+
+  #[test]
+  fn allowed_window_rejects_schedules_outside_business_hours() {
+    use chrono::{FixedOffset, NaiveTime, TimeZone};
+
+    let mut mgr = ScheduleManager::new();
+    let tz = FixedOffset::east_opt(8 * 3600).unwrap();
+    let time = |h: u32| NaiveTime::from_hms_opt(h, 0, 0).unwrap();
+    mgr.set_allowed_window(2, time(9), time(17), tz);
+    assert_eq!(mgr.allowed_window(2).map(|w| w.offset), Some(tz));
+
+    // Local +08:00 times on 2025-03-03
+    let at = |h: u32, len: i64, level: ScheduleLevel| {
+      let start = tz.with_ymd_and_hms(2025, 3, 3, h, 0, 0).unwrap().to_utc();
+      Schedule::new(
+        start,
+        start + Duration::hours(len),
+        level,
+        false,
+        "s".into(),
+      )
+    };
+
+    // Exactly the window is accepted; spilling either side is not
+    mgr.create_schedule(at(9, 8, 2), HashSet::new()).unwrap();
+    assert_eq!(
+      mgr.create_schedule(at(8, 2, 2), HashSet::new()),
+      Err(ScheduleError::OutsideAllowedWindow)
+    );
+    assert_eq!(
+      mgr.create_schedule(at(16, 2, 2), HashSet::new()),
+      Err(ScheduleError::OutsideAllowedWindow)
+    );
+    // Other levels are unconstrained
+    mgr.create_schedule(at(20, 2, 3), HashSet::new()).unwrap();
+
+    // Edits are checked too
+    let id = mgr.create_schedule(at(10, 1, 2), HashSet::new()).unwrap();
+    assert_eq!(
+      mgr.update_schedule(id, at(18, 1, 2)),
+      Err(ScheduleError::OutsideAllowedWindow)
+    );
+
+    // Overnight windows span midnight, including the early-morning part
+    mgr.set_allowed_window(4, time(22), time(6), tz);
+    mgr.create_schedule(at(23, 4, 4), HashSet::new()).unwrap();
+    mgr.create_schedule(at(1, 2, 4), HashSet::new()).unwrap();
+    assert_eq!(
+      mgr.create_schedule(at(5, 2, 4), HashSet::new()),
+      Err(ScheduleError::OutsideAllowedWindow)
+    );
+
+    mgr.clear_allowed_window(2);
+    mgr.create_schedule(at(18, 1, 2), HashSet::new()).unwrap();
+  }
+
+  #[test]
+  fn exclusive_filter_tracks_mutations() {
+    let mut mgr = ScheduleManager::new().with_history_limit(10);
+    let start = Utc::now();
+    let h = Duration::hours;
+    let ids_where = |mgr: &ScheduleManager, exclusive: bool| {
+      let mut ids: Vec<ScheduleId> = mgr
+        .query_schedule(QueryOptions::builder().exclusive(exclusive).build())
+        .into_iter()
+        .map(|(id, _)| id)
+        .collect();
+      ids.sort();
+      ids
+    };
+
+    let a = mgr
+      .create_schedule(
+        Schedule::new(start, start + h(1), 1, true, "a".into()),
+        HashSet::new(),
+      )
+      .unwrap();
+    let b = mgr
+      .create_schedule(
+        Schedule::new(start + h(2), start + h(3), 1, false, "b".into()),
+        HashSet::new(),
+      )
+      .unwrap();
+    assert_eq!(ids_where(&mgr, true), vec![a]);
+    assert_eq!(ids_where(&mgr, false), vec![b]);
+
+    // Editing exclusivity moves the id between the two result sets
+    mgr
+      .update_schedule(
+        b,
+        Schedule::new(start + h(2), start + h(3), 1, true, "b".into()),
+      )
+      .unwrap();
+    let mut both = vec![a, b];
+    both.sort();
+    assert_eq!(ids_where(&mgr, true), both);
+
+    mgr.delete_schedule(a).unwrap();
+    assert_eq!(ids_where(&mgr, true), vec![b]);
+    mgr.undo().unwrap();
+    assert_eq!(ids_where(&mgr, true), both);
+
+    // Rebuilt on load
+    let json = serde_json::to_string(&mgr).unwrap();
+    let loaded: ScheduleManager = serde_json::from_str(&json).unwrap();
+    assert_eq!(ids_where(&loaded, true), both);
+    assert!(ids_where(&loaded, false).is_empty());
+
+    mgr.clear();
+    assert!(ids_where(&mgr, true).is_empty());
+  }
+
+  #[test]
+  fn levels_and_count_at_level() {
+    let mut mgr = ScheduleManager::new();
+    assert!(mgr.levels().is_empty());
+    let start = Utc::now();
+    let h = Duration::hours;
+    let mut last = None;
+    for (i, level) in [3, 1, 3].into_iter().enumerate() {
+      let s = start + h(i as i64 * 2);
+      last = Some(
+        mgr
+          .create_schedule(
+            Schedule::new(s, s + h(1), level, false, "s".into()),
+            HashSet::new(),
+          )
+          .unwrap(),
+      );
+    }
+    assert_eq!(mgr.levels(), vec![1, 3]);
+    assert_eq!(mgr.count_at_level(3), 2);
+    assert_eq!(mgr.count_at_level(2), 0);
+
+    mgr.delete_schedule(last.unwrap()).unwrap();
+    assert_eq!(mgr.count_at_level(3), 1);
+  }
+
+  #[test]
+  fn open_ended_schedule_extends_forever() {
+    let mut mgr = ScheduleManager::new();
+    let start = Utc::now();
+    let h = Duration::hours;
+    let contract = Schedule::open_ended(start, 1, true, "contract".into());
+    assert!(contract.is_open_ended());
+    assert_eq!(contract.end(), OPEN_END);
+    let id = mgr.create_schedule(contract, HashSet::new()).unwrap();
+
+    // Exclusivity blocks same-level schedules arbitrarily far in the future
+    let far = start + Duration::days(365 * 100);
+    assert_eq!(
+      mgr.create_schedule(
+        Schedule::new(far, far + h(1), 1, false, "late".into()),
+        HashSet::new()
+      ),
+      Err(ScheduleError::TimeRangeOverlaps)
+    );
+    // but not before it starts
+    mgr
+      .create_schedule(
+        Schedule::new(start - h(2), start, 1, false, "before".into()),
+        HashSet::new(),
+      )
+      .unwrap();
+
+    // Any window ending after the start includes it
+    let in_window = |from, to| {
+      mgr
+        .query_schedule(QueryOptions::builder().start(from).stop(to).build())
+        .iter()
+        .any(|(i, _)| *i == id)
+    };
+    assert!(in_window(far, far + h(1)));
+    assert!(!in_window(start - h(2), start));
+
+    // Children may be bounded or open-ended themselves
+    let child = mgr
+      .create_schedule(
+        Schedule::open_ended(far, 2, false, "child".into()),
+        HashSet::from([id]),
+      )
+      .unwrap();
+
+    // Moving keeps the end open
+    assert_eq!(
+      mgr.move_schedule(id, -h(1)),
+      Err(ScheduleError::TimeRangeOverlaps)
+    );
+    mgr.move_schedule(child, h(1)).unwrap();
+    assert!(mgr.get_schedule(child).unwrap().is_open_ended());
+    assert_eq!(mgr.get_schedule(child).unwrap().start(), far + h(1));
+  }
+
+  #[test]
+  fn reparent_replaces_parent_set_atomically() {
+    let mut mgr = ScheduleManager::new().with_history_limit(10);
+    let start = Utc::now();
+    let h = Duration::hours;
+    let mut add = |level, exclusive, from: i64, to: i64, parents: HashSet<ScheduleId>| {
+      let s = Schedule::new(start + h(from), start + h(to), level, exclusive, "s".into());
+      mgr.create_schedule(s, parents).unwrap()
+    };
+    let a = add(1, true, 0, 4, HashSet::new());
+    let b = add(0, false, 0, 4, HashSet::new());
+    let far = add(1, false, 10, 14, HashSet::new());
+    let child = add(2, false, 1, 2, HashSet::from([a]));
+    let parents_of =
+      |mgr: &ScheduleManager, id| mgr.parent_relations().get(&id).cloned().unwrap_or_default();
+
+    // Invalid targets leave the relations untouched
+    assert_eq!(
+      mgr.reparent(child, HashSet::from([far])),
+      Err(ScheduleError::TimeRangeExceedsParent)
+    );
+    assert_eq!(
+      mgr.reparent(a, HashSet::from([child])),
+      Err(ScheduleError::CycleDetected)
+    );
+    // Leaving `a` makes the child overlap it, as it is exclusive
+    assert_eq!(
+      mgr.reparent(child, HashSet::from([b])),
+      Err(ScheduleError::TimeRangeOverlaps)
+    );
+    assert_eq!(parents_of(&mgr, child), HashSet::from([a]));
+    assert!(
+      !mgr
+        .child_relations()
+        .get(&b)
+        .is_some_and(|c| c.contains(&child))
+    );
+
+    // A valid swap updates both sides and is a single undo step
+    mgr.reparent(child, HashSet::from([a, b])).unwrap();
+    assert_eq!(parents_of(&mgr, child), HashSet::from([a, b]));
+    assert!(mgr.child_relations()[&b].contains(&child));
+    mgr.undo().unwrap();
+    assert_eq!(parents_of(&mgr, child), HashSet::from([a]));
+    assert!(
+      !mgr
+        .child_relations()
+        .get(&b)
+        .is_some_and(|c| c.contains(&child))
+    );
+    assert_eq!(
+      mgr.reparent(Uuid::now_v7(), HashSet::new()),
+      Err(ScheduleError::ScheduleNotFound)
+    );
+  }
+
+  #[test]
+  fn schedule_id_round_trips_through_json_as_string() {
+    // JavaScript numbers lose precision above 2^53, so ids must cross the
+    // IPC boundary as strings, never as integers.
+    let id: ScheduleId = Uuid::now_v7();
+    assert!(id.as_u128() > 1 << 53);
+    let json = serde_json::to_string(&id).unwrap();
+    assert_eq!(json, format!("\"{id}\""));
+    assert_eq!(serde_json::from_str::<ScheduleId>(&json).unwrap(), id);
+    // The integer form is not accepted
+    assert!(serde_json::from_str::<ScheduleId>(&id.as_u128().to_string()).is_err());
+
+    // Ids nested in relation maps stay strings too
+    let mut mgr = ScheduleManager::new();
+    let start = Utc::now();
+    let parent = mgr
+      .create_schedule(
+        Schedule::new(start, start + Duration::hours(2), 1, false, "p".into()),
+        HashSet::new(),
+      )
+      .unwrap();
+    let child = mgr
+      .create_schedule(
+        Schedule::new(start, start + Duration::hours(1), 2, false, "c".into()),
+        HashSet::from([parent]),
+      )
+      .unwrap();
+    let value = serde_json::to_value(mgr.to_snapshot()).unwrap();
+    assert_eq!(value["parents"][child.to_string()][0], parent.to_string());
+  }
+
+  #[test]
+  fn with_capacity_starts_empty() {
+    let mut mgr = ScheduleManager::with_capacity(1_000);
+    assert!(mgr.is_empty());
+    let start = Utc::now();
+    let id = mgr
+      .create_schedule(
+        Schedule::new(start, start + Duration::hours(1), 1, false, "a".into()),
+        HashSet::new(),
+      )
+      .unwrap();
+    assert!(mgr.contains(id));
+    assert_eq!(mgr.levels(), vec![1]);
+  }
+
+  #[test]
+  fn reindex_repairs_drifted_indices() {
+    let mut mgr = ScheduleManager::new();
+    let start = Utc::now();
+    let h = Duration::hours;
+    let a = mgr
+      .create_schedule(
+        Schedule::new(start, start + h(1), 1, true, "a".into()),
+        HashSet::new(),
+      )
+      .unwrap();
+    let b = mgr
+      .create_schedule(
+        Schedule::new(start + h(2), start + h(3), 2, false, "b".into()),
+        HashSet::new(),
+      )
+      .unwrap();
+    mgr.delete_schedule(b).unwrap();
+    assert_eq!(mgr.check_indices(), Ok(()));
+    let in_window = |mgr: &ScheduleManager| {
+      mgr.schedules_by_day(start, start + h(1)).unwrap()[0]
+        .1
+        .len()
+    };
+    assert_eq!(in_window(&mgr), 1);
+
+    // A structurally valid index that lost `a` behind the manager's back
+    mgr
+      .all_index_mut()
+      .insert(1, Lapper::new(std::collections::BTreeSet::new()));
+    assert!(mgr.check_indices().unwrap_err().contains("level 1"));
+    assert_eq!(in_window(&mgr), 0);
+
+    // A tree that disagrees with its sorted set is reported too
+    let lapper = mgr.all_index_mut().get_mut(&1).unwrap();
+    lapper
+      .intervals
+      .insert(create_interval_with_id(start, 1, a));
+    assert!(mgr.check_indices().is_err());
+
+    mgr.reindex();
+    assert_eq!(mgr.check_indices(), Ok(()));
+    assert_eq!(in_window(&mgr), 1);
+    assert_eq!(
+      mgr.create_schedule(
+        Schedule::new(start, start + h(1), 1, false, "y".into()),
+        HashSet::new()
+      ),
+      Err(ScheduleError::TimeRangeOverlaps)
+    );
+    assert_eq!(mgr.levels(), vec![1]);
+  }
+
+  #[test]
+  fn query_intervals_flags_exclusive_schedules() {
+    let mut mgr = ScheduleManager::new();
+    let start = Utc::now();
+    let h = Duration::hours;
+    let mut add = |level, exclusive, from: i64, to: i64| {
+      let s = Schedule::new(start + h(from), start + h(to), level, exclusive, "s".into());
+      mgr.create_schedule(s, HashSet::new()).unwrap()
+    };
+    let shift = add(2, true, 0, 4);
+    let note = add(1, false, 1, 2);
+    let later = add(2, false, 5, 6);
+
+    let hits = mgr.query_intervals(start, start + h(5));
+    let flags: Vec<(ScheduleId, bool)> = hits.iter().map(|(id, _, e)| (*id, *e)).collect();
+    assert_eq!(flags, vec![(note, false), (shift, true)]);
+    assert_eq!(hits[1].1.level(), 2);
+
+    assert_eq!(mgr.query_intervals(start + h(4), start + h(6))[0].0, later);
+    assert!(mgr.query_intervals(start + h(2), start + h(2)).is_empty());
+  }
+
+  #[test]
+  fn duration_bounds_are_inclusive() {
+    let mut mgr = ScheduleManager::new();
+    let start = Utc::now();
+    let m = Duration::minutes;
+    mgr.set_duration_bounds(2, Some(m(15)), Some(m(240)));
+    assert_eq!(mgr.duration_bounds(2), (Some(m(15)), Some(m(240))));
+    assert_eq!(mgr.duration_bounds(3), (None, None));
+    let mut at = 0;
+    let mut make = |len: Duration, level| {
+      at += 1;
+      let s = start + Duration::days(at);
+      Schedule::new(s, s + len, level, false, "s".into())
+    };
+
+    mgr.create_schedule(make(m(15), 2), HashSet::new()).unwrap();
+    mgr
+      .create_schedule(make(m(240), 2), HashSet::new())
+      .unwrap();
+    assert_eq!(
+      mgr.create_schedule(make(m(14), 2), HashSet::new()),
+      Err(ScheduleError::DurationTooShort)
+    );
+    assert_eq!(
+      mgr.create_schedule(make(m(241), 2), HashSet::new()),
+      Err(ScheduleError::DurationTooLong)
+    );
+    assert_eq!(
+      mgr.create_schedule(
+        Schedule::open_ended(start, 2, false, "open".into()),
+        HashSet::new()
+      ),
+      Err(ScheduleError::DurationTooLong)
+    );
+    // Other levels are unbounded
+    mgr
+      .create_schedule(make(Duration::nanoseconds(1), 3), HashSet::new())
+      .unwrap();
+
+    // Edits are checked, and a lone minimum allows open-ended schedules
+    let id = mgr.create_schedule(make(m(30), 2), HashSet::new()).unwrap();
+    assert_eq!(
+      mgr.update_schedule(id, make(m(5), 2)),
+      Err(ScheduleError::DurationTooShort)
+    );
+    mgr.set_duration_bounds(2, Some(m(15)), None);
+    mgr
+      .create_schedule(
+        Schedule::open_ended(start + Duration::days(100), 2, false, "o".into()),
+        HashSet::new(),
+      )
+      .unwrap();
+    mgr.set_duration_bounds(2, None, None);
+    assert_eq!(mgr.duration_bounds(2), (None, None));
+  }
+
+  #[test]
+  fn max_concurrency_counts_peak_overlap() {
+    let mut mgr = ScheduleManager::new();
+    let start = Utc::now();
+    let h = Duration::hours;
+    for (from, to) in [(0, 4), (1, 3), (2, 5), (5, 6), (6, 7)] {
+      let s = Schedule::new(start + h(from), start + h(to), 2, false, "b".into());
+      mgr.create_schedule(s, HashSet::new()).unwrap();
+    }
+
+    assert_eq!(mgr.max_concurrency(2, start, start + h(8)), 3);
+    // Back-to-back schedules are not concurrent
+    assert_eq!(mgr.max_concurrency(2, start + h(4), start + h(8)), 1);
+    // Clamped to the window: only the tails before 02:00 count
+    assert_eq!(mgr.max_concurrency(2, start, start + h(2)), 2);
+    assert_eq!(mgr.max_concurrency(3, start, start + h(8)), 0);
+    assert_eq!(mgr.max_concurrency(2, start + h(2), start + h(2)), 0);
+  }
+
+  #[test]
+  fn shift_level_relevels_and_rolls_back() {
+    let mut mgr = ScheduleManager::new().with_history_limit(10);
+    let start = Utc::now();
+    let h = Duration::hours;
+    let root = Schedule::new(start, start + h(10), 0, false, "root".into());
+    let root_id = mgr.create_schedule(root, HashSet::new()).unwrap();
+    let mid = Schedule::new(start + h(1), start + h(5), 1, true, "mid".into())
+      .with_exclusive_scope(ExclusiveScope::SameLevelOnly);
+    let mid_id = mgr.create_schedule(mid, HashSet::from([root_id])).unwrap();
+    let leaf = Schedule::new(start + h(2), start + h(3), 2, false, "leaf".into());
+    let leaf_id = mgr.create_schedule(leaf, HashSet::from([mid_id])).unwrap();
+
+    // The child at level 2 would no longer sit below `mid`
+    assert_eq!(
+      mgr.shift_level(1, 2).unwrap_err(),
+      ScheduleError::LevelExceedsParent
+    );
+    // `root` at level 0 would no longer sit above `mid`
+    assert_eq!(
+      mgr.shift_level(1, 0).unwrap_err(),
+      ScheduleError::LevelExceedsParent
+    );
+    assert_eq!(mgr.get_schedule(mid_id).unwrap().level(), 1);
+    assert_eq!(mgr.check_indices(), Ok(()));
+
+    mgr.shift_level(2, 5).unwrap();
+    mgr.shift_level(1, 3).unwrap();
+    assert_eq!(mgr.get_schedule(mid_id).unwrap().level(), 3);
+    assert_eq!(mgr.get_schedule(leaf_id).unwrap().level(), 5);
+    assert_eq!(mgr.levels(), vec![0, 3, 5]);
+    assert_eq!(mgr.check_indices(), Ok(()));
+
+    // Exclusivity is re-checked at the new level
+    let other = Schedule::new(start + h(2), start + h(4), 4, false, "other".into());
+    mgr
+      .create_schedule(other, HashSet::from([root_id]))
+      .unwrap();
+    assert_eq!(
+      mgr.shift_level(3, 4).unwrap_err(),
+      ScheduleError::TimeRangeOverlaps
+    );
+    assert_eq!(mgr.get_schedule(mid_id).unwrap().level(), 3);
+    assert_eq!(mgr.check_indices(), Ok(()));
+
+    mgr.undo().unwrap();
+    mgr.undo().unwrap();
+    assert_eq!(mgr.get_schedule(mid_id).unwrap().level(), 1);
+    assert_eq!(mgr.check_indices(), Ok(()));
+  }
+
+  #[test]
+  fn named_matcher_resolves_registered_predicate() {
+    let mut mgr = ScheduleManager::new();
+    let start = Utc::now();
+    let short = Schedule::new(start, start + Duration::hours(1), 0, false, "short".into());
+    mgr.create_schedule(short, HashSet::new()).unwrap();
+    let long = Schedule::new(start, start + Duration::hours(5), 1, false, "long".into());
+    let long_id = mgr.create_schedule(long, HashSet::new()).unwrap();
+    mgr.register_matcher("long", |s: &Schedule| {
+      s.end() - s.start() > Duration::hours(2)
+    });
+
+    // The name survives a JSON round trip, unlike a closure
+    let opts = QueryOptions::builder().named_matcher("long").build();
+    let json = serde_json::to_string(&opts).unwrap();
+    let opts: QueryOptions = serde_json::from_str(&json).unwrap();
+    let ids: Vec<ScheduleId> = mgr
+      .query_schedule(opts)
+      .into_iter()
+      .map(|(id, _)| id)
+      .collect();
+    assert_eq!(ids, vec![long_id]);
+
+    // ANDed with the closure matcher
+    let opts = QueryOptions::builder()
+      .named_matcher("long")
+      .and_matcher(|s: &Schedule| s.level() == 0)
+      .build();
+    assert!(mgr.query_schedule(opts).is_empty());
+
+    let opts = QueryOptions::builder().named_matcher("missing").build();
+    assert!(mgr.query_schedule(opts).is_empty());
+  }
+
+  #[test]
+  fn clone_subtree_copies_structure_with_offset() {
+    let mut mgr = ScheduleManager::new().with_history_limit(10);
+    let start = Utc::now();
+    let h = Duration::hours;
+    let week = Duration::days(7);
+    let outer = Schedule::new(start - h(1), start + week + h(10), 0, false, "outer".into());
+    let outer_id = mgr.create_schedule(outer, HashSet::new()).unwrap();
+    let day = Schedule::new(start, start + h(8), 1, true, "day".into());
+    let day_id = mgr.create_schedule(day, HashSet::from([outer_id])).unwrap();
+    let a = Schedule::new(start + h(1), start + h(3), 2, false, "a".into());
+    let a_id = mgr.create_schedule(a, HashSet::from([day_id])).unwrap();
+    let b = Schedule::new(start + h(2), start + h(3), 3, false, "b".into());
+    mgr
+      .create_schedule(b, HashSet::from([day_id, a_id]))
+      .unwrap();
+
+    // A copy at the same time collides with the exclusive original
+    assert_eq!(
+      mgr.clone_subtree(day_id, Duration::zero()).unwrap_err(),
+      ScheduleError::TimeRangeOverlaps
+    );
+    assert_eq!(mgr.len(), 4);
+
+    let copies = mgr.clone_subtree(day_id, week).unwrap();
+    assert_eq!(copies.len(), 3);
+    assert_eq!(mgr.len(), 7);
+    let by_name = |name: &str| {
+      *copies
+        .iter()
+        .find(|id| mgr.get_schedule(**id).unwrap().name() == name)
+        .unwrap()
+    };
+    let (day2, a2, b2) = (by_name("day"), by_name("a"), by_name("b"));
+    assert_eq!(mgr.get_schedule(day2).unwrap().start(), start + week);
+    assert_eq!(mgr.get_schedule(b2).unwrap().end(), start + week + h(3));
+    // The copy's root is a new root; internal links are reproduced
+    let parent_ids =
+      |id| -> HashSet<ScheduleId> { mgr.parents_of(id).into_iter().map(|(id, _)| id).collect() };
+    assert!(parent_ids(day2).is_empty());
+    assert_eq!(parent_ids(b2), HashSet::from([day2, a2]));
+    assert!(!copies.contains(&day_id));
+
+    mgr.undo().unwrap();
+    assert_eq!(mgr.len(), 4);
+    assert_eq!(
+      mgr.clone_subtree(Uuid::nil(), week).unwrap_err(),
+      ScheduleError::ScheduleNotFound
+    );
+  }
+
+  #[test]
+  fn clone_subtree_rolls_back_when_a_copy_leaves_the_time_range() {
+    let mut mgr = ScheduleManager::new();
+    let start = Utc::now();
+    let h = Duration::hours;
+    let root = mgr
+      .create_schedule(
+        Schedule::open_ended(start, 1, false, "contract".into()),
+        HashSet::new(),
+      )
+      .unwrap();
+    mgr
+      .create_schedule(
+        Schedule::new(start + h(1), start + h(2), 2, false, "kickoff".into()),
+        HashSet::from([root]),
+      )
+      .unwrap();
+
+    // The open-ended root still fits; its child's end would overflow
+    let offset = OPEN_END - start - h(1);
+    assert_eq!(
+      mgr.clone_subtree(root, offset),
+      Err(ScheduleError::TimeOutOfRange)
+    );
+    assert_eq!(mgr.len(), 2);
+    let late: Vec<ScheduleId> = mgr
+      .schedules_at(OPEN_END - h(1))
+      .into_iter()
+      .map(|(id, _)| id)
+      .collect();
+    assert_eq!(late, vec![root]);
+  }
+
+  #[test]
+  fn disjoint_parents_are_an_invalid_parent_set() {
+    let mut mgr = ScheduleManager::new();
+    let start = Utc::now();
+    let h = Duration::hours;
+    let morning = Schedule::new(start, start + h(4), 0, false, "morning".into());
+    let morning_id = mgr.create_schedule(morning, HashSet::new()).unwrap();
+    let evening = Schedule::new(start + h(6), start + h(10), 1, false, "evening".into());
+    let evening_id = mgr.create_schedule(evening, HashSet::new()).unwrap();
+    let overlapping = Schedule::new(start + h(3), start + h(7), 1, false, "overlapping".into());
+    let overlapping_id = mgr.create_schedule(overlapping, HashSet::new()).unwrap();
+
+    // No child can sit inside both; the later-starting parent is named
+    let child = Schedule::new(start + h(1), start + h(2), 2, false, "child".into());
+    let err = mgr
+      .create_schedule(child.clone(), HashSet::from([morning_id, evening_id]))
+      .unwrap_err();
+    assert_eq!(err, ScheduleError::InvalidParentSet { parent: evening_id });
+    assert!(err.to_string().contains(&evening_id.to_string()));
+
+    // Parents that share time fall back to the per-parent checks
+    assert_eq!(
+      mgr
+        .create_schedule(child, HashSet::from([morning_id, overlapping_id]))
+        .unwrap_err(),
+      ScheduleError::TimeRangeExceedsParent
+    );
+    let child = Schedule::new(start + h(3), start + h(4), 2, false, "child".into());
+    mgr
+      .create_schedule(child, HashSet::from([morning_id, overlapping_id]))
+      .unwrap();
+  }
 }
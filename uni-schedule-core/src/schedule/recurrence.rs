@@ -0,0 +1,88 @@
+use chrono::{DateTime, Duration, Months, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::manager::OPEN_END;
+
+/// How often a recurring schedule repeats.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Frequency {
+  Daily,
+  Weekly,
+  Monthly,
+}
+
+/// When a recurrence stops producing occurrences.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RecurrenceEnd {
+  /// Produce exactly this many occurrences (including the first).
+  Count(u32),
+  /// Produce occurrences whose start is at or before this instant.
+  Until(DateTime<Utc>),
+}
+
+/// A minimal recurrence rule (a small subset of iCalendar RRULE).
+///
+/// Occurrence `k` starts at the template start advanced by
+/// `k * interval` units of `frequency`. Monthly steps use calendar months
+/// and clamp to the last day of shorter months (Jan 31 -> Feb 28); offsets
+/// are always computed from the original start so clamping does not drift.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Recurrence {
+  pub frequency: Frequency,
+  /// Number of `frequency` units between occurrences. Must be at least 1.
+  pub interval: u32,
+  pub end: RecurrenceEnd,
+}
+
+impl Recurrence {
+  /// Expand the rule into concrete `(start, end)` ranges for a template
+  /// spanning `[start, end)`.
+  ///
+  /// An open-ended template (`end == OPEN_END`) yields open-ended
+  /// occurrences. Returns `None` if `interval` is zero or an occurrence's
+  /// end is not representable. Expansion stops early if a start would leave
+  /// chrono's representable range.
+  pub fn occurrences(
+    &self,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+  ) -> Option<Vec<(DateTime<Utc>, DateTime<Utc>)>> {
+    if self.interval == 0 {
+      return None;
+    }
+    let length = end - start;
+    let mut out = Vec::new();
+    for k in 0u32.. {
+      if let RecurrenceEnd::Count(n) = self.end
+        && k >= n
+      {
+        break;
+      }
+      let Some(s) = self.nth_start(start, k) else {
+        break;
+      };
+      if let RecurrenceEnd::Until(until) = self.end
+        && s > until
+      {
+        break;
+      }
+      let e = if end == OPEN_END {
+        OPEN_END
+      } else {
+        s.checked_add_signed(length)?
+      };
+      out.push((s, e));
+    }
+    Some(out)
+  }
+
+  /// Start of occurrence `k`, or `None` on overflow.
+  fn nth_start(&self, start: DateTime<Utc>, k: u32) -> Option<DateTime<Utc>> {
+    let steps = i64::from(k) * i64::from(self.interval);
+    match self.frequency {
+      Frequency::Daily => start.checked_add_signed(Duration::try_days(steps)?),
+      Frequency::Weekly => start.checked_add_signed(Duration::try_weeks(steps)?),
+      Frequency::Monthly => start.checked_add_months(Months::new(u32::try_from(steps).ok()?)),
+    }
+  }
+}
@@ -1,7 +1,10 @@
 import { invoke, isTauri } from "@tauri-apps/api/core";
 import type {
   CreateSchedulePayload,
+  DayBucket,
+  Page,
   Schedule,
+  ScheduleNode,
   QueryScheduleOptions,
 } from "../types";
 
@@ -12,7 +15,7 @@ import type {
 // ---------------------------------------------------------------------------
 
 // Legacy exports for backward compatibility
-export type { CreateSchedulePayload } from "../types";
+export type { CreateSchedulePayload, DayBucket, Page } from "../types";
 export type ScheduleDto = Schedule;
 export interface QueryOptions extends QueryScheduleOptions {}
 
@@ -154,11 +157,37 @@ class InMemoryScheduleManager {
     return null;
   }
 
+  reparent(id: string, parents: string[]): string | null {
+    const sched = this.schedules.get(id);
+    if (!sched) return "ScheduleNotFound";
+    for (const pid of parents) {
+      const p = this.schedules.get(pid);
+      if (!p) return "ParentNotFound";
+      if (!(sched.level > p.level)) return "LevelExceedsParent";
+      if (!(sched.start >= p.start && sched.end <= p.end)) return "TimeRangeExceedsParent";
+    }
+    for (const pid of sched.parents) this.schedules.get(pid)?.children.delete(id);
+    sched.parents = new Set(parents);
+    for (const pid of parents) this.schedules.get(pid)!.children.add(id);
+    return null;
+  }
+
   get(id: string): ScheduleDto | null {
     const s = this.schedules.get(id);
     return s ? this.toDto(s) : null;
   }
 
+  getNode(id: string): ScheduleNode | null {
+    const s = this.schedules.get(id);
+    return s
+      ? {
+          ...this.toDto(s),
+          parents: Array.from(s.parents),
+          children: Array.from(s.children),
+        }
+      : null;
+  }
+
   query(opts: QueryOptions): ScheduleDto[] {
     let ids: Iterable<string> = this.schedules.keys();
     if (typeof opts.level === "number") {
@@ -176,6 +205,8 @@ class InMemoryScheduleManager {
     for (const id of ids) {
       const s = this.schedules.get(id)!;
       if (nameFilter && !s.name.toLowerCase().includes(nameFilter)) continue;
+      if (opts.level_min != null && s.level < opts.level_min) continue;
+      if (opts.level_max != null && s.level > opts.level_max) continue;
       if (exclusiveFilter !== null && s.exclusive !== exclusiveFilter) continue;
       if (startFilter && !(s.start >= startFilter)) continue; // contained semantics
       if (stopFilter && !(s.end <= stopFilter)) continue;
@@ -205,8 +236,11 @@ class InMemoryScheduleManager {
       level: s.level,
       exclusive: s.exclusive,
       name: s.name,
-      parents: Array.from(s.parents),
-      children: Array.from(s.children),
+      description: null,
+      tags: [],
+      priority: 0,
+      color: null,
+      exclusive_scope: "SameAndLower",
     };
   }
 }
@@ -253,6 +287,38 @@ export async function deleteSchedule(id: string): Promise<void> {
   }
 }
 
+export async function reparentSchedule(
+  id: string,
+  parents: string[],
+): Promise<ScheduleNode> {
+  if (isTauri()) {
+    return invoke<ScheduleNode>("reparent_schedule", { id, parents });
+  } else {
+    const mgr = devManager!;
+    const err = mgr.reparent(id, parents);
+    if (err) throw new Error(err);
+    return mgr.getNode(id)!;
+  }
+}
+
+/**
+ * Stop persisting after every change, e.g. for a large import. Call
+ * `endBulk` afterwards to write everything in one pass. No-op in dev mode,
+ * which never persists.
+ */
+export async function beginBulk(): Promise<void> {
+  if (isTauri()) {
+    return invoke<void>("begin_bulk");
+  }
+}
+
+/** Persist all changes made since `beginBulk` and resume normal saving. */
+export async function endBulk(): Promise<void> {
+  if (isTauri()) {
+    return invoke<void>("end_bulk");
+  }
+}
+
 export async function getSchedule(id: string): Promise<ScheduleDto | null> {
   if (isTauri()) {
     return invoke<ScheduleDto | null>("get_schedule", { id });
@@ -261,6 +327,14 @@ export async function getSchedule(id: string): Promise<ScheduleDto | null> {
   }
 }
 
+export async function scheduleExists(id: string): Promise<boolean> {
+  if (isTauri()) {
+    return invoke<boolean>("schedule_exists", { id });
+  } else {
+    return devManager!.get(id) !== null;
+  }
+}
+
 export async function querySchedules(
   opts: QueryOptions = {},
 ): Promise<ScheduleDto[]> {
@@ -271,6 +345,64 @@ export async function querySchedules(
   }
 }
 
+export async function querySchedulesPaged(
+  opts: QueryOptions = {},
+  page = 0,
+  pageSize = 50,
+): Promise<Page<ScheduleDto>> {
+  if (isTauri()) {
+    return invoke<Page<ScheduleDto>>("query_schedules_paged", {
+      req: opts,
+      page,
+      pageSize,
+    });
+  } else {
+    const all = devManager!
+      .query(opts)
+      .sort((a, b) => a.start.localeCompare(b.start));
+    return {
+      items: all.slice(page * pageSize, (page + 1) * pageSize),
+      total: all.length,
+      page,
+      page_size: pageSize,
+    };
+  }
+}
+
+export async function queryDays(start: string, end: string): Promise<DayBucket[]> {
+  if (isTauri()) {
+    return invoke<DayBucket[]>("query_days", { start, end });
+  } else {
+    const from = new Date(start);
+    const to = new Date(end);
+    if (from >= to) return [];
+    // Same cap as MAX_DAY_BUCKETS in uni-schedule-core
+    const dayIndex = (ms: number) => Math.floor(ms / 86_400_000);
+    if (dayIndex(to.getTime() - 1) - dayIndex(from.getTime()) >= 3660) {
+      throw new Error("Window spans more than 3660 days");
+    }
+    const all = devManager!
+      .query({})
+      .sort((a, b) => a.start.localeCompare(b.start));
+    const days: DayBucket[] = [];
+    const day = new Date(
+      Date.UTC(from.getUTCFullYear(), from.getUTCMonth(), from.getUTCDate()),
+    );
+    for (; day < to; day.setUTCDate(day.getUTCDate() + 1)) {
+      const dayStart = new Date(Math.max(day.getTime(), from.getTime()));
+      const next = new Date(day.getTime() + 86_400_000);
+      const dayEnd = new Date(Math.min(next.getTime(), to.getTime()));
+      days.push({
+        date: day.toISOString().slice(0, 10),
+        schedules: all.filter((s) =>
+          overlaps(new Date(s.start), new Date(s.end), dayStart, dayEnd),
+        ),
+      });
+    }
+    return days;
+  }
+}
+
 // Optional utility for dev/testing to seed sample data when NOT using Tauri.
 export function devSeedSample(count = 3) {
   if (isTauri()) return; // ignore in prod
@@ -0,0 +1,9 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ExclusiveScope } from "./ExclusiveScope";
+
+export type BatchCreateItem = { 
+/**
+ * Optional caller-chosen id so later items in the same batch can
+ * reference this one as a parent.
+ */
+id?: string | null, start: string, end: string, level: number, exclusive: boolean, name: string, description?: string | null, tags?: Array<string>, priority?: number, color?: string | null, exclusive_scope?: ExclusiveScope, parents: Array<string>, };
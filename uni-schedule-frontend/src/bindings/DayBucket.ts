@@ -0,0 +1,8 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { QueryItem } from "./QueryItem";
+
+export type DayBucket = { 
+/**
+ * UTC date as `YYYY-MM-DD`.
+ */
+date: string, schedules: Array<QueryItem>, };
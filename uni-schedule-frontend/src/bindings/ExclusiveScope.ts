@@ -0,0 +1,8 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Which levels an exclusive schedule blocks from overlapping it.
+ *
+ * Recall that numerically higher levels sit lower in the hierarchy.
+ */
+export type ExclusiveScope = "SameAndLower" | "AllLevels" | "SameLevelOnly";
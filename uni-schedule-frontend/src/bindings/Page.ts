@@ -0,0 +1,11 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type Page<T> = { items: Array<T>, 
+/**
+ * Number of matches across all pages.
+ */
+total: number, 
+/**
+ * Zero-based index of this page.
+ */
+page: number, page_size: number, };
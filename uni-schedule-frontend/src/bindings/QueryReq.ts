@@ -0,0 +1,9 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { SortOrder } from "./SortOrder";
+
+export type QueryReq = { name?: string | null, tag?: string | null, tags_any?: Array<string>, tags_all?: Array<string>, start?: string | null, stop?: string | null, level?: number | null, level_min?: number | null, level_max?: number | null, exclusive?: boolean | null, sort?: SortOrder | null, 
+/**
+ * Name of a matcher registered on the manager; see
+ * `QueryOptions::named_matcher`.
+ */
+named_matcher?: string | null, };
@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Result ordering for `QueryOptions::sort`. Ties are broken by start time
+ * and then id so the order is total and paging is stable.
+ */
+export type SortOrder = "StartAsc" | "StartDesc" | "PriorityDesc";
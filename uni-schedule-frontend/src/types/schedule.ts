@@ -1,31 +1,30 @@
+import type { CreateScheduleReq } from "../bindings/CreateScheduleReq";
+import type { DayBucket } from "../bindings/DayBucket";
+import type { ExclusiveScope } from "../bindings/ExclusiveScope";
+import type { Page } from "../bindings/Page";
+import type { QueryItem } from "../bindings/QueryItem";
+import type { QueryReq } from "../bindings/QueryReq";
+import type { ScheduleNode } from "../bindings/ScheduleNode";
+import type { SortOrder } from "../bindings/SortOrder";
+
+// Types generated from the Rust DTOs (see `bun run bindings`)
+export type {
+  DayBucket,
+  ExclusiveScope,
+  Page,
+  QueryItem,
+  QueryReq,
+  ScheduleNode,
+  SortOrder,
+};
+
 // Core schedule types
-export interface Schedule {
-  id: string;
-  name: string;
-  start: string; // ISO 8601 format
-  end: string; // ISO 8601 format
-  level: number;
-  exclusive: boolean;
-  parents?: string[];
-  children?: string[];
-}
+/** A schedule as returned by queries. */
+export type Schedule = QueryItem;
 
-export interface CreateSchedulePayload {
-  name: string;
-  start: string; // ISO 8601 format
-  end: string; // ISO 8601 format
-  level: number;
-  exclusive: boolean;
-  parents: string[];
-}
+export type CreateSchedulePayload = CreateScheduleReq;
 
-export interface QueryScheduleOptions {
-  name?: string;
-  start?: string; // ISO 8601 format
-  stop?: string; // ISO 8601 format
-  level?: number;
-  exclusive?: boolean;
-}
+export type QueryScheduleOptions = QueryReq;
 
 export interface ScheduleError {
   type: